@@ -124,5 +124,77 @@ fn netlib(c: &mut Criterion) {
   group.finish();
 }
 
-criterion_group!(benches, netlib);
+#[cfg(all(feature = "cache", not(feature = "trace")))]
+fn cache(c: &mut Criterion) {
+  use mps::cache;
+  use mps::model::{Model, SparseModel};
+  let files = [
+    ("dfl001", include_str!("../tests/data/netlib/dfl001")),
+    ("pilot87", include_str!("../tests/data/netlib/pilot87")),
+  ];
+
+  let mut group = c.benchmark_group("cache");
+  for (name, content) in files.iter() {
+    let model =
+      Model::try_from(Parser::<f64>::parse(content).unwrap()).unwrap();
+    let checksum = cache::checksum(content.as_bytes());
+    let mut snapshot = Vec::new();
+    cache::write(&model, checksum, &mut snapshot).unwrap();
+    let mut sparse_snapshot = Vec::new();
+    cache::write(&model.to_sparse().unwrap(), checksum, &mut sparse_snapshot)
+      .unwrap();
+    group.bench_function(format!("Model::try_from({})", name), |b| {
+      b.iter(|| Model::try_from(Parser::<f64>::parse(content).unwrap()))
+    });
+    group.bench_function(format!("cache::read::<Model>({})", name), |b| {
+      b.iter(|| {
+        let checksum = cache::checksum(content.as_bytes());
+        cache::read::<Model<f64>, _>(snapshot.as_slice(), checksum)
+      })
+    });
+    group.bench_function(
+      format!("cache::read::<SparseModel>({})", name),
+      |b| {
+        b.iter(|| {
+          let checksum = cache::checksum(content.as_bytes());
+          cache::read::<SparseModel<f64>, _>(
+            sparse_snapshot.as_slice(),
+            checksum,
+          )
+        })
+      },
+    );
+  }
+  group.finish();
+}
+
+#[cfg(not(all(feature = "cache", not(feature = "trace"))))]
+fn cache(_: &mut Criterion) {}
+
+#[cfg(all(feature = "rayon", not(feature = "trace")))]
+fn parallel(c: &mut Criterion) {
+  let files = [
+    ("pilot87", include_str!("../tests/data/netlib/pilot87")),
+    ("wood1p", include_str!("../tests/data/netlib/wood1p")),
+    ("dfl001", include_str!("../tests/data/netlib/dfl001")),
+    ("d6cube", include_str!("../tests/data/netlib/d6cube")),
+  ];
+
+  let mut group = c.benchmark_group("parallel");
+  for (name, content) in files.iter() {
+    group.throughput(Throughput::Bytes(content.len() as u64));
+    group.bench_function(format!("Parser::parse({})", name), |b| {
+      b.iter(|| Parser::<f64>::parse(content))
+    });
+    group.bench_function(format!("Parser::parse_parallel({})", name), |b| {
+      b.iter(|| Parser::<f64>::parse_parallel(content))
+    });
+  }
+  group.finish();
+}
+
+#[cfg(not(all(feature = "rayon", not(feature = "trace"))))]
+fn parallel(_: &mut Criterion) {}
+
+criterion_group!(benches, netlib, cache, parallel);
 criterion_main!(benches);
@@ -0,0 +1,250 @@
+//! Binary snapshots of a `Model`, `SparseModel` or `ParserOwned`.
+//!
+//! Parsing and validating a large MPS file costs far more than reading back
+//! its result. A snapshot stores the result in bincode behind a small header:
+//!
+//! | bytes | content                                               |
+//! |-------|-------------------------------------------------------|
+//! | 8     | magic `MPSCACHE`                                      |
+//! | 4     | format version, little endian                         |
+//! | 2     | kind of snapshot and size of the value type in bytes  |
+//! | 8     | checksum of the MPS source, little endian             |
+//!
+//! A snapshot only applies to the exact source it was built from and to the
+//! same version, kind and value type. `read` returns `None` for any other
+//! snapshot so that the caller parses again; `load_or_parse` does this for
+//! files.
+//!
+//! `SparseModel` snapshots are the fastest to load, since they hold each name
+//! once and everything else as flat vectors, while a `Model` keys every
+//! coefficient by its row and column names. `ParserOwned` snapshots skip only
+//! the parse, for callers that want the file's sections as written.
+use crate::input;
+use crate::model::{Model, SparseModel};
+use crate::{Parser, ParserOwned};
+use color_eyre::{eyre::eyre, Result};
+use fast_float2::FastFloat;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"MPSCACHE";
+
+/// Version of the snapshot layout; bump it whenever a snapshot type changes
+/// shape.
+pub const VERSION: u32 = 3;
+
+/// A type that can be stored in a snapshot.
+pub trait Snapshot: Serialize + DeserializeOwned {
+  /// Tells snapshots of different types apart.
+  const KIND: [u8; 2];
+
+  /// Builds the value from MPS source, as on a cache miss.
+  fn from_source(source: &str) -> Result<Self>;
+}
+
+impl<T: FastFloat + Serialize + DeserializeOwned> Snapshot for Model<T> {
+  const KIND: [u8; 2] = [b'M', std::mem::size_of::<T>() as u8];
+
+  fn from_source(source: &str) -> Result<Self> {
+    Model::try_from(Parser::<T>::parse(source)?)
+  }
+}
+
+impl<T: FastFloat + Serialize + DeserializeOwned> Snapshot for SparseModel<T> {
+  const KIND: [u8; 2] = [b'S', std::mem::size_of::<T>() as u8];
+
+  fn from_source(source: &str) -> Result<Self> {
+    Model::<T>::from_source(source)?.to_sparse()
+  }
+}
+
+impl<T: FastFloat + Serialize + DeserializeOwned> Snapshot for ParserOwned<T> {
+  const KIND: [u8; 2] = [b'P', std::mem::size_of::<T>() as u8];
+
+  fn from_source(source: &str) -> Result<Self> {
+    Ok(Parser::<T>::parse(source)?.into_owned())
+  }
+}
+
+/// Computes the checksum of an MPS source.
+///
+/// The source is hashed eight bytes at a time with FNV-1a style multiplies
+/// and a rotation, so that the checksum costs a small fraction of loading a
+/// snapshot.
+pub fn checksum(source: &[u8]) -> u64 {
+  const PRIME: u64 = 0x0100_0000_01b3;
+  let mix =
+    |hash: u64, word: u64| (hash ^ word).wrapping_mul(PRIME).rotate_left(29);
+  let words = source.chunks_exact(8);
+  let tail = words.remainder();
+  let hash = words.fold(0xcbf2_9ce4_8422_2325, |hash, word| {
+    mix(hash, u64::from_le_bytes(word.try_into().unwrap()))
+  });
+  let mut last = [0; 8];
+  last[..tail.len()].copy_from_slice(tail);
+  mix(mix(hash, u64::from_le_bytes(last)), source.len() as u64)
+}
+
+/// Writes a snapshot of `value`, built from a source with checksum `checksum`.
+pub fn write<S: Snapshot, W: Write>(
+  value: &S,
+  checksum: u64,
+  writer: W,
+) -> Result<()> {
+  let mut writer = BufWriter::new(writer);
+  writer.write_all(MAGIC)?;
+  writer.write_all(&VERSION.to_le_bytes())?;
+  writer.write_all(&S::KIND)?;
+  writer.write_all(&checksum.to_le_bytes())?;
+  bincode::serialize_into(&mut writer, value)?;
+  writer.flush()?;
+  Ok(())
+}
+
+/// Reads a snapshot, returning `None` if it was written by another version,
+/// for another type or from a source whose checksum is not `checksum`.
+///
+/// # Errors
+///
+/// Returns an error if the input is not a snapshot or is corrupt.
+pub fn read<S: Snapshot, R: Read>(
+  reader: R,
+  checksum: u64,
+) -> Result<Option<S>> {
+  let mut reader = BufReader::new(reader);
+  let mut header = [0; 22];
+  reader
+    .read_exact(&mut header)
+    .map_err(|_| eyre!("not an mps cache"))?;
+  if &header[..8] != MAGIC {
+    return Err(eyre!("not an mps cache"));
+  }
+  let version = u32::from_le_bytes(header[8..12].try_into()?);
+  let source = u64::from_le_bytes(header[14..22].try_into()?);
+  if version != VERSION || header[12..14] != S::KIND || source != checksum {
+    return Ok(None);
+  }
+  Ok(Some(bincode::deserialize_from(reader)?))
+}
+
+/// Builds the `Model`, `SparseModel` or `ParserOwned` of the MPS file at
+/// `path`, reusing the snapshot at `cache_path` if it matches the file and
+/// writing a new one otherwise.
+///
+/// The file may be compressed (see `input`). The checksum covers the
+/// decompressed text, so recompressing a file keeps its snapshot valid.
+pub fn load_or_parse<S: Snapshot>(
+  path: impl AsRef<Path>,
+  cache_path: impl AsRef<Path>,
+) -> Result<S> {
+  let cache_path = cache_path.as_ref();
+  let source = input::read_to_string(path)?;
+  let checksum = checksum(source.as_bytes());
+  if let Ok(file) = File::open(cache_path) {
+    if let Ok(Some(value)) = read(file, checksum) {
+      return Ok(value);
+    }
+  }
+  let value = S::from_source(&source)?;
+  // Write beside the snapshot first so that readers never see half of it
+  let partial = cache_path.with_extension("partial");
+  write(&value, checksum, File::create(&partial)?)?;
+  fs::rename(&partial, cache_path)?;
+  Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_checksum() {
+    assert_ne!(checksum(b"NAME A"), checksum(b"NAME B"));
+    assert_ne!(checksum(b"NAME"), checksum(b"NAME\0"));
+    assert_ne!(checksum(b"ROWS    COLUMNS "), checksum(b"COLUMNS ROWS    "));
+  }
+
+  #[test]
+  fn test_round_trip() -> Result<()> {
+    let source = include_str!("../tests/data/netlib/afiro");
+    let model = Model::try_from(Parser::<f64>::parse(source)?)?;
+    let mut bytes = Vec::new();
+    write(&model, checksum(source.as_bytes()), &mut bytes)?;
+    let read_back =
+      read::<Model<f64>, _>(bytes.as_slice(), checksum(source.as_bytes()))?;
+    assert_eq!(read_back, Some(model));
+    // Stale snapshots
+    assert_eq!(read::<Model<f64>, _>(bytes.as_slice(), 0)?, None);
+    assert_eq!(
+      read::<Model<f32>, _>(bytes.as_slice(), checksum(source.as_bytes()))?,
+      None
+    );
+    let mut other = bytes.clone();
+    other[8] += 1;
+    assert_eq!(
+      read::<Model<f64>, _>(other.as_slice(), checksum(source.as_bytes()))?,
+      None
+    );
+    // Not a snapshot
+    assert!(read::<Model<f64>, _>(source.as_bytes(), 0).is_err());
+    assert!(read::<Model<f64>, _>(&bytes[..10], 0).is_err());
+    // Another kind of snapshot
+    assert_eq!(
+      read::<SparseModel<f64>, _>(
+        bytes.as_slice(),
+        checksum(source.as_bytes())
+      )?,
+      None
+    );
+    let sparse = model_sparse(source)?;
+    let mut bytes = Vec::new();
+    write(&sparse, checksum(source.as_bytes()), &mut bytes)?;
+    assert_eq!(
+      read::<SparseModel<f64>, _>(
+        bytes.as_slice(),
+        checksum(source.as_bytes())
+      )?,
+      Some(sparse)
+    );
+    let owned = Parser::<f64>::parse(source)?.into_owned();
+    let mut bytes = Vec::new();
+    write(&owned, checksum(source.as_bytes()), &mut bytes)?;
+    assert_eq!(
+      read::<ParserOwned<f64>, _>(
+        bytes.as_slice(),
+        checksum(source.as_bytes())
+      )?,
+      Some(owned)
+    );
+    Ok(())
+  }
+
+  fn model_sparse(source: &str) -> Result<SparseModel<f64>> {
+    Model::try_from(Parser::<f64>::parse(source)?)?.to_sparse()
+  }
+
+  #[test]
+  fn test_load_or_parse() -> Result<()> {
+    let dir =
+      std::env::temp_dir().join(format!("mps-cache-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("afiro");
+    let cache_path = dir.join("afiro.cache");
+    fs::copy("tests/data/netlib/afiro", &path)?;
+    let parsed = load_or_parse::<Model<f64>>(&path, &cache_path)?;
+    assert!(cache_path.exists());
+    assert_eq!(load_or_parse::<Model<f64>>(&path, &cache_path)?, parsed);
+    // Changing the file invalidates the snapshot
+    let source = fs::read_to_string(&path)?.replace("AFIRO", "CHANGED");
+    fs::write(&path, source)?;
+    assert_eq!(
+      load_or_parse::<Model<f64>>(&path, &cache_path)?.name,
+      "CHANGED"
+    );
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+  }
+}
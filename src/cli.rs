@@ -10,4 +10,11 @@ pub struct Cli {
     help = "The path to the MPS file to parse"
   )]
   pub input_path: String,
+  #[cfg(feature = "cache")]
+  #[arg(
+    long,
+    value_name = "FILE",
+    help = "Parse through a snapshot at FILE, reused while the input is unchanged"
+  )]
+  pub cache: Option<String>,
 }
@@ -0,0 +1,770 @@
+use crate::parse::{L4, L6};
+use crate::types::Section;
+use color_eyre::{Report, Result};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::fmt;
+
+/// Enumeration representing the category of an `Error`.
+///
+/// # Variants
+///
+/// * `Syntax`: A line could not be read, e.g. because a field is missing.
+/// * `BadNumber`: A field that must hold a number does not.
+/// * `BadRowType`: A row type is not `E`, `L`, `G` or `N`, or a row has the
+///   wrong type for where it is used.
+/// * `BadBoundType`: A BOUNDS line has an unknown bound type.
+/// * `UnknownSection`: A header line does not name an MPS section.
+/// * `SectionOutOfOrder`: A section appears after a section that must
+///   follow it, or twice.
+/// * `MissingSection`: A required section (NAME, ROWS, COLUMNS or ENDATA) is
+///   absent.
+/// * `UnbalancedMarker`: INTORG/INTEND markers are nested or unmatched.
+/// * `UnknownRow`: A row name is referenced but never declared.
+/// * `UnknownColumn`: A column name is referenced but never declared.
+/// * `UnknownSet`: A named RHS, RANGES or BOUNDS set does not exist.
+/// * `DuplicateEntry`: The same entry is given more than once.
+/// * `Conflict`: Two declarations disagree, e.g. a row declared twice.
+/// * `MissingValue`: A bound that requires a value has none.
+/// * `NegativeUpperBound`: A negative `UP` or `UI` bound without a lower
+///   bound moved the lower bound to `-inf`.
+/// * `IgnoredEntry`: An entry has no effect on the model, e.g. a range on an
+///   `N` row.
+/// * `Io`: The input could not be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum ErrorKind {
+  Syntax,
+  BadNumber,
+  BadRowType,
+  BadBoundType,
+  UnknownSection,
+  SectionOutOfOrder,
+  MissingSection,
+  UnbalancedMarker,
+  UnknownRow,
+  UnknownColumn,
+  UnknownSet,
+  DuplicateEntry,
+  Conflict,
+  MissingValue,
+  NegativeUpperBound,
+  IgnoredEntry,
+  Io,
+}
+
+/// A structured parse or model error.
+///
+/// `line` and `column` are 1-based and only known for parse errors; errors
+/// found while building a `Model` carry the section and the offending name
+/// instead. `Model` construction returns `color_eyre::Report`s that wrap an
+/// `Error`, which can be recovered with `report.downcast_ref::<Error>()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Error {
+  pub kind: ErrorKind,
+  pub section: Option<Section>,
+  pub line: Option<usize>,
+  pub column: Option<usize>,
+  pub token: Option<String>,
+  pub message: String,
+  pub hint: Option<String>,
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match (self.line, self.column) {
+      (Some(line), Some(column)) => {
+        write!(f, "line {}, column {}: {}", line, column, self.message)
+      }
+      _ => write!(f, "{}", self.message),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl Error {
+  pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+    Error {
+      kind,
+      section: None,
+      line: None,
+      column: None,
+      token: None,
+      message: message.into(),
+      hint: None,
+    }
+  }
+
+  /// Sets the section, unless one is already known.
+  pub fn in_section(mut self, section: Section) -> Self {
+    self.section.get_or_insert(section);
+    self
+  }
+
+  pub fn with_token(mut self, token: impl Into<String>) -> Self {
+    self.token = Some(token.into());
+    self
+  }
+
+  pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+    self.hint = Some(hint.into());
+    self
+  }
+
+  pub fn at(mut self, line: usize, column: usize) -> Self {
+    self.line = Some(line);
+    self.column = Some(column);
+    self
+  }
+
+  /// Renders the error in the style of a compiler diagnostic, quoting the
+  /// offending line of `source` with a caret under the offending token:
+  ///
+  /// ```text
+  /// error: invalid number "1.0x" in COLUMNS section
+  ///  --> line 6, column 25
+  ///   |
+  /// 6 |     X1        COST      1.0x
+  ///   |                         ^^^^
+  ///   = hint: value fields start at columns 25 and 50 in fixed format
+  /// ```
+  ///
+  /// `source` must be the input the error was produced from. Errors without
+  /// a location render as the message and hint alone.
+  pub fn render(&self, source: &str) -> String {
+    let mut rendered = format!("error: {}\n", self.message);
+    let quoted = self.line.zip(self.column).and_then(|(line, column)| {
+      Some((line, column, source.lines().nth(line - 1)?))
+    });
+    let gutter = match quoted {
+      Some((line, column, text)) => {
+        let number = line.to_string();
+        let gutter = " ".repeat(number.len());
+        let text = text.trim_end_matches('\r');
+        // Keep tabs so the caret lines up with the quoted text
+        let indent = text
+          .chars()
+          .take(column - 1)
+          .map(|c| if c == '\t' { '\t' } else { ' ' })
+          .collect::<String>();
+        let width = self.token.as_ref().map_or(1, |t| t.chars().count().max(1));
+        let caret = "^".repeat(width);
+        rendered
+          .push_str(&format!("{gutter}--> line {line}, column {column}\n"));
+        rendered.push_str(&format!("{gutter} |\n{number} | {text}\n"));
+        rendered.push_str(&format!("{gutter} | {indent}{caret}\n"));
+        gutter
+      }
+      None => String::new(),
+    };
+    if let Some(hint) = &self.hint {
+      rendered.push_str(&format!("{gutter} = hint: {hint}\n"));
+    }
+    rendered
+  }
+
+  /// Locates a parse failure at byte `offset` of `input` and classifies it
+  /// by re-reading the offending line in the context of its section.
+  pub(crate) fn from_parse_failure(
+    input: &str,
+    offset: usize,
+    code: nom::error::ErrorKind,
+  ) -> Self {
+    let offset = offset.min(input.len());
+    let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = input[offset..]
+      .find('\n')
+      .map_or(input.len(), |i| offset + i);
+    let line = input[line_start..line_end].trim_end_matches('\r');
+    let line_number = input[..line_start].matches('\n').count() + 1;
+    if input[offset..].trim().is_empty() {
+      return Error::missing_endata().at(line_number, offset - line_start + 1);
+    }
+    let previous = input[..line_start].lines().rev().find_map(header);
+    Error::from_line(line, line_number, previous, code)
+  }
+
+  /// The error for input that ends before the ENDATA line.
+  pub(crate) fn missing_endata() -> Self {
+    Error::new(
+      ErrorKind::MissingSection,
+      "unexpected end of input: missing ENDATA",
+    )
+    .in_section(Section::Endata)
+    .with_hint("the last line of an MPS file must be ENDATA")
+  }
+
+  /// Classifies a failure on `line`, the `line_number`-th line of the input,
+  /// which follows the header of the `previous` section.
+  pub(crate) fn from_line(
+    line: &str,
+    line_number: usize,
+    previous: Option<Section>,
+    code: nom::error::ErrorKind,
+  ) -> Self {
+    let (error, column) = if code == nom::error::ErrorKind::Verify {
+      let error = Error::new(
+        ErrorKind::UnbalancedMarker,
+        "unbalanced INTORG/INTEND marker",
+      )
+      .with_hint(
+        "every 'INTORG' marker must be closed by an 'INTEND' marker before \
+         the next 'INTORG' or the end of COLUMNS",
+      );
+      let token = fields(line)
+        .into_iter()
+        .find(|(_, f)| *f == "'INTORG'" || *f == "'INTEND'");
+      match token {
+        Some((column, token)) => (error.with_token(token), column),
+        None => (error, 1),
+      }
+    } else if !line.starts_with(' ') {
+      let column = fields(line).first().map_or(1, |&(column, _)| column);
+      (header_error(line, previous), column)
+    } else {
+      match previous {
+        Some(section) => data_error(line, section),
+        None => (
+          Error::new(ErrorKind::MissingSection, "missing NAME section")
+            .with_hint("an MPS file must start with a NAME line"),
+          1,
+        ),
+      }
+    };
+    let section = error.section.or(previous);
+    let mut error = error.at(line_number, column);
+    error.section = section;
+    error
+  }
+}
+
+/// Returns the section opened by `line`, if it is a section header.
+fn header(line: &str) -> Option<Section> {
+  if line.starts_with([' ', '\t', '*']) {
+    return None;
+  }
+  Section::try_from(line.split_whitespace().next()?).ok()
+}
+
+/// Splits a line into whitespace-separated fields with their 1-based column.
+fn fields(line: &str) -> Vec<(usize, &str)> {
+  let mut fields = Vec::new();
+  let mut start = None;
+  for (i, c) in line.char_indices().chain([(line.len(), ' ')]) {
+    match (c.is_whitespace(), start) {
+      (true, Some(s)) => {
+        fields.push((s + 1, &line[s..i]));
+        start = None;
+      }
+      (false, None) => start = Some(i),
+      _ => (),
+    }
+  }
+  fields
+}
+
+/// Classifies a header line that the parser could not accept after
+/// `previous`.
+fn header_error(line: &str, previous: Option<Section>) -> Error {
+  let token = line.split_whitespace().next().unwrap_or_default();
+  let section =
+    match Section::try_from(token) {
+      Ok(section) => section,
+      Err(_) => return Error::new(
+        ErrorKind::UnknownSection,
+        format!("unknown section {:?}", token),
+      )
+      .with_token(token)
+      .with_hint(
+        "section headers start in column 1; data lines must start with a space",
+      ),
+    };
+  let position = previous.map_or(0, |p| p.position());
+  let missing = [Section::Name, Section::Rows, Section::Columns]
+    .into_iter()
+    .find(|required| {
+      position < required.position() && section.position() > required.position()
+    });
+  let error = match missing {
+    Some(required) => Error::new(
+      ErrorKind::MissingSection,
+      format!("missing {} section before {}", required.header(), token),
+    )
+    .in_section(required)
+    .with_hint(format!(
+      "every MPS file has a {} section",
+      required.header()
+    )),
+    None
+      if section.position() > position
+        || (section == Section::QcMatrix && previous == Some(section)) =>
+    {
+      Error::new(
+        ErrorKind::Syntax,
+        format!("malformed {} section header", token),
+      )
+      .in_section(section)
+    }
+    None => {
+      let error = Error::new(
+        ErrorKind::SectionOutOfOrder,
+        match previous {
+          Some(previous) => {
+            format!(
+              "section {} out of order after {}",
+              token,
+              previous.header()
+            )
+          }
+          None => format!("section {} out of order", token),
+        },
+      )
+      .in_section(section);
+      match previous {
+        Some(previous) if previous != section => error.with_hint(format!(
+          "{} must come before {}",
+          token,
+          previous.header()
+        )),
+        _ => error.with_hint(format!("{} may only appear once", token)),
+      }
+    }
+  };
+  error.with_token(token)
+}
+
+/// Classifies a data line of `section` that the parser could not read,
+/// returning the error and the column of the offending field.
+fn data_error(line: &str, section: Section) -> (Error, usize) {
+  let fields = fields(line);
+  let number = |i: usize| -> Option<(Error, usize)> {
+    let (column, token) = *fields.get(i)?;
+    match fast_float2::parse::<f64, _>(token) {
+      Ok(_) => None,
+      Err(_) => {
+        let error = Error::new(
+          ErrorKind::BadNumber,
+          format!("invalid number {:?} in {} section", token, section.header()),
+        )
+        .with_token(token);
+        match value_columns(section) {
+          Some(hint) => Some((error.with_hint(hint), column)),
+          None => Some((error, column)),
+        }
+      }
+    }
+  };
+  let integer = |i: usize| -> Option<(Error, usize)> {
+    let (column, token) = *fields.get(i)?;
+    match token.parse::<i32>() {
+      Ok(_) => None,
+      Err(_) => Some((
+        Error::new(
+          ErrorKind::BadNumber,
+          format!(
+            "invalid integer {:?} in {} section",
+            token,
+            section.header()
+          ),
+        )
+        .with_token(token),
+        column,
+      )),
+    }
+  };
+  let (min_fields, found) = match section {
+    Section::Rows | Section::UserCuts => {
+      let found = fields.first().and_then(|&(column, token)| {
+        match token.chars().next().map(crate::types::RowType::try_from) {
+          Some(Ok(_)) => None,
+          _ => Some((
+            Error::new(
+              ErrorKind::BadRowType,
+              format!("invalid row type {:?}", token),
+            )
+            .with_token(token)
+            .with_hint("row types are N, E, L and G"),
+            column,
+          )),
+        }
+      });
+      (2, found)
+    }
+    Section::Columns | Section::Rhs | Section::Ranges => {
+      // A fixed-format RHS or RANGES line may leave the set name blank
+      match number(1) {
+        None => (2, number(3)),
+        Some(_) => (3, number(2).or_else(|| number(4))),
+      }
+    }
+    Section::Bounds => {
+      let found = fields.first().and_then(|&(column, token)| {
+        match crate::types::BoundType::try_from(token) {
+          Ok(_) => None,
+          Err(_) => Some((
+            Error::new(
+              ErrorKind::BadBoundType,
+              format!("invalid bound type {:?}", token),
+            )
+            .with_token(token)
+            .with_hint(
+              "bound types are UP, LO, FX, FR, MI, PL, BV, LI, UI and SC",
+            ),
+            column,
+          )),
+        }
+      });
+      (3, found.or_else(|| number(3)))
+    }
+    Section::QSection
+    | Section::QuadObj
+    | Section::QMatrix
+    | Section::QcMatrix => (3, number(2)),
+    Section::Sos => match fields.first() {
+      Some((_, "S1" | "S2")) => (2, None),
+      _ => (2, number(1)),
+    },
+    Section::Indicators => {
+      let found = match fields.first() {
+        Some(&(column, token)) if token != "IF" => Some((
+          Error::new(
+            ErrorKind::Syntax,
+            format!("indicator line must start with IF: found {:?}", token),
+          )
+          .with_token(token),
+          column,
+        )),
+        _ => match fields.get(3) {
+          Some(&(column, token)) if token != "0" && token != "1" => Some((
+            Error::new(
+              ErrorKind::BadNumber,
+              format!("indicator trigger must be 0 or 1: found {:?}", token),
+            )
+            .with_token(token),
+            column,
+          )),
+          _ => None,
+        },
+      };
+      (4, found)
+    }
+    Section::Branch => (2, integer(fields.len().saturating_sub(1))),
+    _ => (1, None),
+  };
+  found.unwrap_or_else(|| {
+    let (column, token) = fields.first().copied().unwrap_or((1, ""));
+    if fields.len() < min_fields {
+      let message = format!("missing field in {} section", section.header());
+      let end = fields.last().map_or(1, |(c, f)| c + f.len() + 1);
+      let error = Error::new(ErrorKind::Syntax, message);
+      match line_layout(section) {
+        Some(hint) => (error.with_hint(hint), end),
+        None => (error, end),
+      }
+    } else {
+      let message = format!("malformed line in {} section", section.header());
+      (
+        Error::new(ErrorKind::Syntax, message).with_token(token),
+        column,
+      )
+    }
+  })
+}
+
+/// Where the values of a data line of `section` go in fixed format. The
+/// parser slices fields after stripping the leading blank, so the 1-based
+/// column of field `n` is `Ln + 2`.
+fn value_columns(section: Section) -> Option<String> {
+  match section {
+    Section::Columns | Section::Rhs | Section::Ranges => Some(format!(
+      "value fields start at columns {} and {} in fixed format",
+      L4 + 2,
+      L6 + 2
+    )),
+    Section::Bounds
+    | Section::QSection
+    | Section::QuadObj
+    | Section::QMatrix
+    | Section::QcMatrix => Some(format!(
+      "the value field starts at column {} in fixed format",
+      L4 + 2
+    )),
+    _ => None,
+  }
+}
+
+/// The fields a data line of `section` is made of.
+fn line_layout(section: Section) -> Option<&'static str> {
+  match section {
+    Section::Rows | Section::UserCuts => {
+      Some("a row line holds a row type and a row name")
+    }
+    Section::Columns => Some(
+      "a COLUMNS line holds a column name and one or two row name and value \
+       pairs",
+    ),
+    Section::Rhs | Section::Ranges => Some(
+      "an RHS or RANGES line holds a set name and one or two row name and \
+       value pairs",
+    ),
+    Section::Bounds => Some(
+      "a BOUNDS line holds a bound type, a set name, a column name and, for \
+       most types, a value",
+    ),
+    Section::QSection
+    | Section::QuadObj
+    | Section::QMatrix
+    | Section::QcMatrix => {
+      Some("a quadratic term holds two column names and a value")
+    }
+    Section::Indicators => {
+      Some("an indicator line reads IF <row> <column> <0 or 1>")
+    }
+    Section::Branch => {
+      Some("a BRANCH line holds an optional direction, a column and a priority")
+    }
+    _ => None,
+  }
+}
+
+/// Attaches a section to the `Error` carried by a failed `Result`.
+pub(crate) trait InSection<T> {
+  fn in_section(self, section: Section) -> Result<T>;
+}
+
+impl<T> InSection<T> for Result<T> {
+  fn in_section(self, section: Section) -> Result<T> {
+    self.map_err(|report| match report.downcast::<Error>() {
+      Ok(error) => error.in_section(section).into(),
+      Err(report) => report,
+    })
+  }
+}
+
+/// Enumeration representing how serious a `Diagnostic` is.
+///
+/// # Variants
+///
+/// * `Error`: The model cannot be built.
+/// * `Warning`: The model can be built, but probably not as intended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Severity {
+  Error,
+  Warning,
+}
+
+/// A problem found by `Model::validate`.
+///
+/// `names` lists the sets, rows and columns involved, from the outermost
+/// (e.g. the RHS set) to the offending entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Diagnostic {
+  pub severity: Severity,
+  pub kind: ErrorKind,
+  pub section: Option<Section>,
+  pub names: Vec<String>,
+  pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let severity = match self.severity {
+      Severity::Error => "error",
+      Severity::Warning => "warning",
+    };
+    match self.section {
+      Some(section) => {
+        write!(f, "{} in {}: {}", severity, section.header(), self.message)
+      }
+      None => write!(f, "{}: {}", severity, self.message),
+    }
+  }
+}
+
+/// Collects the diagnostics of a `Model` while it is built, so that a single
+/// pass reports every problem instead of stopping at the first one.
+#[derive(Debug, Default)]
+pub(crate) struct Diagnostics {
+  pub(crate) diagnostics: Vec<Diagnostic>,
+  first_error: Option<Report>,
+}
+
+impl Diagnostics {
+  /// Records the error of a failed check, naming the enclosing entries in
+  /// `context`. Returns the value of a successful check.
+  pub(crate) fn check<V>(
+    &mut self,
+    result: Result<V>,
+    context: &[&str],
+  ) -> Option<V> {
+    match result {
+      Ok(value) => Some(value),
+      Err(report) => {
+        self.error(report, context);
+        None
+      }
+    }
+  }
+
+  pub(crate) fn error(&mut self, error: impl Into<Report>, context: &[&str]) {
+    let report = error.into();
+    let (kind, section, token) = match report.downcast_ref::<Error>() {
+      Some(error) => (error.kind, error.section, error.token.clone()),
+      None => (ErrorKind::Syntax, None, None),
+    };
+    self.diagnostics.push(Diagnostic {
+      severity: Severity::Error,
+      kind,
+      section,
+      names: context
+        .iter()
+        .map(|name| name.to_string())
+        .chain(token)
+        .collect(),
+      message: report.to_string(),
+    });
+    self.first_error.get_or_insert(report);
+  }
+
+  pub(crate) fn warn(
+    &mut self,
+    kind: ErrorKind,
+    section: Section,
+    names: &[&str],
+    message: String,
+  ) {
+    self.diagnostics.push(Diagnostic {
+      severity: Severity::Warning,
+      kind,
+      section: Some(section),
+      names: names.iter().map(|name| name.to_string()).collect(),
+      message,
+    });
+  }
+
+  /// Fails with the first recorded error, as a builder that stops at the
+  /// first problem would.
+  pub(crate) fn first_error(self) -> Result<()> {
+    match self.first_error {
+      Some(report) => Err(report),
+      None => Ok(()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::Model;
+  use crate::types::Parser;
+
+  const ROWS: &str = "NAME TEST\nROWS\n N  COST\n L  LIM1\n";
+  const COLUMNS: &str = "COLUMNS\n    X1        COST      1.0\n";
+
+  fn parse_error(input: &str) -> Error {
+    Parser::<f64>::parse(input).expect_err("parse should fail")
+  }
+
+  #[test]
+  fn test_parse_error_location() {
+    let e = parse_error(&format!(
+      "{ROWS}COLUMNS\n    X1        COST      1.0x\nENDATA\n"
+    ));
+    assert_eq!(e.kind, ErrorKind::BadNumber);
+    assert_eq!(e.section, Some(Section::Columns));
+    assert_eq!((e.line, e.column), (Some(6), Some(25)));
+    assert_eq!(e.token.as_deref(), Some("1.0x"));
+    assert_eq!(
+      e.to_string(),
+      "line 6, column 25: invalid number \"1.0x\" in COLUMNS section"
+    );
+  }
+
+  #[test]
+  fn test_render() {
+    let input =
+      format!("{ROWS}COLUMNS\n    X1        COST      1.0x\nENDATA\n");
+    assert_eq!(
+      parse_error(&input).render(&input),
+      [
+        "error: invalid number \"1.0x\" in COLUMNS section",
+        " --> line 6, column 25",
+        "  |",
+        "6 |     X1        COST      1.0x",
+        "  |                         ^^^^",
+        "  = hint: value fields start at columns 25 and 50 in fixed format\n",
+      ]
+      .join("\n")
+    );
+    let input = format!("{ROWS}\tX1 COST\nENDATA\n");
+    assert!(parse_error(&input)
+      .render(&input)
+      .ends_with("5 | \tX1 COST\n  | \t^^\n  = hint: section headers start in column 1; data lines must start with a space\n"));
+    let e = Error::new(
+      ErrorKind::UnknownRow,
+      "referenced row of unspecified type: X",
+    );
+    assert_eq!(
+      e.render(""),
+      "error: referenced row of unspecified type: X\n"
+    );
+  }
+
+  #[test]
+  fn test_parse_error_kinds() {
+    let cases = [
+      (format!("{ROWS} Q  LIM2\n{COLUMNS}ENDATA\n"), ErrorKind::BadRowType, 5, 2),
+      (
+        format!("{ROWS}{COLUMNS}BOUNDS\n XX BND       X1        4\nENDATA\n"),
+        ErrorKind::BadBoundType,
+        8,
+        2,
+      ),
+      (
+        format!("{ROWS}{COLUMNS}BOUNDS\n UP BND       X1        4\nRHS\nENDATA\n"),
+        ErrorKind::SectionOutOfOrder,
+        9,
+        1,
+      ),
+      (
+        format!("{ROWS}COLUMNS\n    MARKER                 'MARKER'                 'INTEND'\nENDATA\n"),
+        ErrorKind::UnbalancedMarker,
+        6,
+        53,
+      ),
+      (format!("{ROWS}{COLUMNS}FOO\nENDATA\n"), ErrorKind::UnknownSection, 7, 1),
+      (format!("NAME TEST\n{COLUMNS}ENDATA\n"), ErrorKind::MissingSection, 2, 1),
+      (format!("{ROWS}{COLUMNS}"), ErrorKind::MissingSection, 7, 1),
+    ];
+    for (input, kind, line, column) in cases {
+      let e = parse_error(&input);
+      assert_eq!(
+        (e.kind, e.line, e.column),
+        (kind, Some(line), Some(column)),
+        "{}",
+        e
+      );
+    }
+  }
+
+  #[test]
+  fn test_model_error() -> Result<()> {
+    let input =
+      format!("{ROWS}{COLUMNS}RHS\n    RHS       LIM2      4\nENDATA\n");
+    let report = Model::try_from(Parser::<f64>::parse(&input)?).unwrap_err();
+    let e = report.downcast_ref::<Error>().expect("structured error");
+    assert_eq!(e.kind, ErrorKind::UnknownRow);
+    assert_eq!(e.section, Some(Section::Rhs));
+    assert_eq!(e.token.as_deref(), Some("LIM2"));
+    assert_eq!(e.line, None);
+    assert_eq!(
+      report.to_string(),
+      "referenced row of unspecified type: LIM2"
+    );
+    Ok(())
+  }
+}
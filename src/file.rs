@@ -0,0 +1,174 @@
+//! Parsing straight from a memory-mapped file.
+//!
+//! `Parser::parse_file` maps the file and parses it in place, so every name
+//! in the result borrows from the mapping instead of from a heap copy of the
+//! file. The returned `ParsedFile` owns the mapping alongside the `Parser`,
+//! and only lends the parser out for as long as the mapping lives.
+//!
+//! Compressed files (see `input`) cannot be parsed in place; they are
+//! decompressed into memory and `ParsedFile` owns that text instead.
+//!
+//! As with any memory map, the file must not be truncated or modified while
+//! it is mapped.
+use crate::error::{Error, ErrorKind};
+use crate::input::{self, Compression};
+use crate::owned::ParserOwned;
+use crate::types::Parser;
+use fast_float2::FastFloat;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// The storage `ParsedFile` borrows from.
+enum Source {
+  Mapped(Mmap),
+  Decompressed(String),
+}
+
+/// A `Parser` together with the file contents its names borrow from.
+pub struct ParsedFile<T: FastFloat> {
+  // Borrows from `_source`, so it is declared, and dropped, first
+  parser: Parser<'static, T>,
+  text: &'static str,
+  _source: Source,
+}
+
+impl<T: FastFloat> ParsedFile<T> {
+  /// The parsed file. Names borrow from `self`.
+  pub fn parser(&self) -> &Parser<'_, T> {
+    &self.parser
+  }
+
+  /// The (decompressed) text of the file, e.g. for `Error::render`.
+  pub fn source(&self) -> &str {
+    self.text
+  }
+
+  /// Copies every name out of the file, releasing the mapping.
+  pub fn into_owned(self) -> ParserOwned<T> {
+    ParserOwned::from(&self.parser)
+  }
+}
+
+impl<T: FastFloat> std::fmt::Debug for ParsedFile<T> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    self.parser.fmt(f)
+  }
+}
+
+fn io_error(e: io::Error) -> Error {
+  Error::new(ErrorKind::Io, e.to_string())
+}
+
+impl<T: FastFloat> Parser<'_, T> {
+  /// Memory-maps the file at `path` and parses it without copying it.
+  ///
+  /// # Errors
+  ///
+  /// Returns an `ErrorKind::Io` error if the file cannot be read or is not
+  /// UTF-8, and the error of `Parser::parse` otherwise.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use mps::Parser;
+  ///
+  /// let parsed = Parser::<f64>::parse_file("tests/data/netlib/afiro")?;
+  /// assert_eq!(parsed.parser().name, "AFIRO");
+  /// # Ok::<(), mps::error::Error>(())
+  /// ```
+  pub fn parse_file(path: impl AsRef<Path>) -> Result<ParsedFile<T>, Error> {
+    let file = File::open(path).map_err(io_error)?;
+    // SAFETY: the mapping is read-only; see the module documentation on
+    // modifying the file while it is mapped
+    let mmap = unsafe { Mmap::map(&file) }.map_err(io_error)?;
+    let source = match Compression::detect(&mmap) {
+      Compression::None => Source::Mapped(mmap),
+      _ => {
+        let mut text = String::new();
+        input::decompress(&mmap[..])
+          .and_then(|mut decoder| decoder.read_to_string(&mut text))
+          .map_err(io_error)?;
+        Source::Decompressed(text)
+      }
+    };
+    let text = match &source {
+      Source::Mapped(mmap) => std::str::from_utf8(mmap).map_err(|_| {
+        Error::new(ErrorKind::Io, "stream did not contain valid UTF-8")
+      })?,
+      Source::Decompressed(text) => text.as_str(),
+    };
+    // SAFETY: the text lives in the mapping or in the string's heap buffer,
+    // neither of which moves when `source` is moved into `ParsedFile`. The
+    // `'static` borrows never leave it: `parser` and `source` shorten them
+    // to the lifetime of `self`, and `parser` is dropped before `_source`.
+    let text: &'static str = unsafe { &*(text as *const str) };
+    let parser = Parser::<T>::parse(text)?;
+    Ok(ParsedFile {
+      parser,
+      text,
+      _source: source,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_file() -> Result<(), Error> {
+    let path = "tests/data/netlib/afiro";
+    let parsed = Parser::<f64>::parse_file(path)?;
+    let source = std::fs::read_to_string(path).unwrap();
+    assert_eq!(parsed.source(), source);
+    assert_eq!(parsed.parser(), &Parser::<f64>::parse(&source)?);
+    assert_eq!(
+      parsed.into_owned(),
+      Parser::<f64>::parse(&source)?.into_owned()
+    );
+    Ok(())
+  }
+
+  #[cfg(feature = "gzip")]
+  #[test]
+  fn test_parse_file_compressed() -> color_eyre::Result<()> {
+    use std::io::Write;
+    let source = std::fs::read_to_string("tests/data/netlib/afiro")?;
+    let mut encoder =
+      flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(source.as_bytes())?;
+    let path =
+      std::env::temp_dir().join(format!("mps-file-{}.gz", std::process::id()));
+    std::fs::write(&path, encoder.finish()?)?;
+    let parsed = Parser::<f64>::parse_file(&path)?;
+    assert_eq!(parsed.source(), source);
+    assert_eq!(parsed.parser(), &Parser::<f64>::parse(&source)?);
+    std::fs::remove_file(&path)?;
+    Ok(())
+  }
+
+  #[test]
+  fn test_parse_file_errors() -> std::io::Result<()> {
+    let e = Parser::<f64>::parse_file("tests/data/netlib/missing").unwrap_err();
+    assert_eq!(e.kind, ErrorKind::Io);
+    let dir =
+      std::env::temp_dir().join(format!("mps-file-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let cases: [(&[u8], ErrorKind); 3] = [
+      (b"", ErrorKind::MissingSection),
+      (b"NAME \xff\nENDATA\n", ErrorKind::Io),
+      (
+        b"NAME\nROWS\n X  R1\nCOLUMNS\nENDATA\n",
+        ErrorKind::BadRowType,
+      ),
+    ];
+    for (i, (contents, kind)) in cases.into_iter().enumerate() {
+      let path = dir.join(i.to_string());
+      std::fs::write(&path, contents)?;
+      assert_eq!(Parser::<f64>::parse_file(&path).unwrap_err().kind, kind);
+    }
+    std::fs::remove_dir_all(&dir)
+  }
+}
@@ -0,0 +1,198 @@
+//! Reading MPS files, with transparent decompression.
+//!
+//! Netlib and MIPLIB files are usually distributed compressed. `open` and
+//! `decompress` recognize gzip, bzip2, xz and zstd data by its magic bytes
+//! and decompress it on the fly; anything else is read as is. Each format
+//! needs its cargo feature (`gzip`, `bzip2`, `xz` or `zstd`, or all of them
+//! via `compression`); compressed input without the feature is an error
+//! rather than garbage.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+use std::path::Path;
+
+/// Enumeration representing the compression of an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+  None,
+  Gzip,
+  Bzip2,
+  Xz,
+  Zstd,
+}
+
+impl Compression {
+  /// Recognizes the compression from the first bytes of the input.
+  pub fn detect(magic: &[u8]) -> Self {
+    match magic {
+      [0x1f, 0x8b, ..] => Compression::Gzip,
+      [b'B', b'Z', b'h', ..] => Compression::Bzip2,
+      [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Compression::Xz,
+      [0x28, 0xb5, 0x2f, 0xfd, ..] => Compression::Zstd,
+      _ => Compression::None,
+    }
+  }
+
+  /// The cargo feature that enables decompression of this format.
+  pub fn feature(&self) -> Option<&'static str> {
+    match self {
+      Compression::None => None,
+      Compression::Gzip => Some("gzip"),
+      Compression::Bzip2 => Some("bzip2"),
+      Compression::Xz => Some("xz"),
+      Compression::Zstd => Some("zstd"),
+    }
+  }
+}
+
+/// Wraps `input` in a decoder for its compression, if any.
+///
+/// Concatenated gzip, bzip2 and xz streams, as written by `cat a.gz b.gz`,
+/// are read in full.
+///
+/// # Errors
+///
+/// Returns an `io::ErrorKind::Unsupported` error if the input is compressed
+/// in a format whose feature is not enabled.
+pub fn decompress<'a, R: Read + 'a>(
+  mut input: R,
+) -> io::Result<Box<dyn BufRead + 'a>> {
+  let mut magic = Vec::with_capacity(6);
+  input.by_ref().take(6).read_to_end(&mut magic)?;
+  let compression = Compression::detect(&magic);
+  let input = Cursor::new(magic).chain(input);
+  match compression {
+    Compression::None => Ok(Box::new(BufReader::new(input))),
+    #[cfg(feature = "gzip")]
+    Compression::Gzip => Ok(Box::new(BufReader::new(
+      flate2::read::MultiGzDecoder::new(input),
+    ))),
+    #[cfg(feature = "bzip2")]
+    Compression::Bzip2 => Ok(Box::new(BufReader::new(
+      bzip2::read::MultiBzDecoder::new(input),
+    ))),
+    #[cfg(feature = "xz")]
+    Compression::Xz => Ok(Box::new(BufReader::new(
+      liblzma::read::XzDecoder::new_multi_decoder(input),
+    ))),
+    #[cfg(feature = "zstd")]
+    Compression::Zstd => Ok(Box::new(BufReader::new(
+      zstd::stream::read::Decoder::new(input)?,
+    ))),
+    #[allow(unreachable_patterns)]
+    compression => Err(io::Error::new(
+      io::ErrorKind::Unsupported,
+      format!(
+        "{:?} input requires the `{}` feature",
+        compression,
+        compression.feature().unwrap_or_default()
+      ),
+    )),
+  }
+}
+
+/// Opens the file at `path` for reading, decompressing it if needed.
+///
+/// The result can be handed to `stream::Reader::new`.
+pub fn open<'a>(path: impl AsRef<Path>) -> io::Result<Box<dyn BufRead + 'a>> {
+  decompress(File::open(path)?)
+}
+
+/// Reads the whole file at `path` into a string, decompressing it if needed,
+/// e.g. to pass it to `Parser::parse`.
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+  let mut contents = String::new();
+  open(path)?.read_to_string(&mut contents)?;
+  Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_detect() {
+    let cases: [(&[u8], Compression); 6] = [
+      (b"NAME          AFIRO", Compression::None),
+      (&[0x1f, 0x8b, 0x08, 0x00], Compression::Gzip),
+      (b"BZh91AY&SY", Compression::Bzip2),
+      (&[0xfd, b'7', b'z', b'X', b'Z', 0x00], Compression::Xz),
+      (&[0x28, 0xb5, 0x2f, 0xfd, 0x04], Compression::Zstd),
+      (b"BZ", Compression::None),
+    ];
+    for (magic, compression) in cases {
+      assert_eq!(Compression::detect(magic), compression);
+    }
+  }
+
+  #[test]
+  fn test_plain() -> io::Result<()> {
+    let mut contents = String::new();
+    decompress("NAME\n".as_bytes())?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "NAME\n");
+    // Inputs shorter than the longest magic number
+    let mut contents = String::new();
+    decompress("N".as_bytes())?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "N");
+    Ok(())
+  }
+
+  #[cfg(any(
+    feature = "gzip",
+    feature = "bzip2",
+    feature = "xz",
+    feature = "zstd"
+  ))]
+  fn assert_decompresses(compressed: Vec<u8>) -> io::Result<()> {
+    let afiro = std::fs::read_to_string("tests/data/netlib/afiro")?;
+    let mut contents = String::new();
+    decompress(compressed.as_slice())?.read_to_string(&mut contents)?;
+    assert_eq!(contents, afiro);
+    Ok(())
+  }
+
+  #[cfg(feature = "gzip")]
+  #[test]
+  fn test_gzip() -> io::Result<()> {
+    use std::io::Write;
+    let mut encoder =
+      flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(&std::fs::read("tests/data/netlib/afiro")?)?;
+    assert_decompresses(encoder.finish()?)
+  }
+
+  #[cfg(feature = "bzip2")]
+  #[test]
+  fn test_bzip2() -> io::Result<()> {
+    use std::io::Write;
+    let mut encoder =
+      bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::fast());
+    encoder.write_all(&std::fs::read("tests/data/netlib/afiro")?)?;
+    assert_decompresses(encoder.finish()?)
+  }
+
+  #[cfg(feature = "xz")]
+  #[test]
+  fn test_xz() -> io::Result<()> {
+    use std::io::Write;
+    let mut encoder = liblzma::write::XzEncoder::new(Vec::new(), 1);
+    encoder.write_all(&std::fs::read("tests/data/netlib/afiro")?)?;
+    assert_decompresses(encoder.finish()?)
+  }
+
+  #[cfg(feature = "zstd")]
+  #[test]
+  fn test_zstd() -> io::Result<()> {
+    let afiro = std::fs::read("tests/data/netlib/afiro")?;
+    assert_decompresses(zstd::encode_all(afiro.as_slice(), 1)?)
+  }
+
+  #[cfg(not(feature = "gzip"))]
+  #[test]
+  fn test_unsupported() {
+    let e = decompress([0x1f, 0x8b, 0x08, 0x00].as_slice())
+      .err()
+      .unwrap();
+    assert_eq!(e.kind(), io::ErrorKind::Unsupported);
+    assert_eq!(e.to_string(), "Gzip input requires the `gzip` feature");
+  }
+}
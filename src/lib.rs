@@ -53,9 +53,34 @@
 //!
 //! - **Configurable Parsing**:
 //!   - Supported feature flags:
+//!     - `cache` - Versioned binary snapshots of a `Model`, `SparseModel` or
+//!       `ParserOwned`, keyed by a checksum of the source; the CLI takes
+//!       `--cache`.
 //!     - `cli` - Command line interface.
+//!     - `mmap` - `Parser::parse_file`, which parses a memory-mapped file in
+//!       place; the CLI uses it too.
+//!     - `rayon` - `Parser::parse_parallel`, which parses the COLUMNS, RHS,
+//!       RANGES and BOUNDS sections of large files on several threads.
+//!     - `serde` - `Serialize` and `Deserialize` for `Model`, `ParserOwned`
+//!       and the `types` enums (`Parser` itself is serialize-only).
+//!     - `gzip`, `bzip2`, `xz`, `zstd` - Transparent decompression of
+//!       compressed input in `input` and the CLI; `compression` enables all
+//!       four.
 //!     - `trace` - Enhanced debugging and statistics via `nom_tracable` and `nom_locate`.
+//! - **CPLEX LP**: `Model::from_lp` reads CPLEX LP files into the same `Model`
+//!   as MPS files, and `Model::to_lp` writes a `Model` as LP.
+//! - **QPLIB**: `Model::from_qplib` reads `.qplib` files from the
+//!   [QPLIB](https://qplib.zib.de/) library of quadratic programs.
+//! - **Quadratic forms**: `Model::quadratic_objective_form` and
+//!   `Model::quadratic_constraint_forms` give each quadratic part as the
+//!   upper triangle of the `Q` of `0.5 x'Qx`. `Model::try_from` rejects a
+//!   QUADOBJ or QSECTION that lists both `(x, y)` and `(y, x)` with
+//!   different values, and a QMATRIX or QCMATRIX that is not symmetric.
+//!   Equal values are read once, with a warning from `Model::validate`.
 //! - **Robustness**: Extensively tested against [Netlib LP test suite](http://www.netlib.org/lp/data/).
+//!   Netlib ships its files in Gay's compressed `emps` encoding, which is
+//!   not read directly: expand them with netlib's `emps` program first, as
+//!   was done for the copies in `tests/data/netlib`.
 //! - **Performance**: Benchmarked using [Criterion.rs](https://github.com/bheisler/criterion.rs).
 //!
 //! ## References
@@ -63,7 +88,21 @@
 //! - [Mathematical Programming System format](https://lpsolve.sourceforge.net/5.5/mps-format.htm)
 //! - [NETLIB linear programming library](http://www.netlib.org/lp/)
 //!
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod error;
+#[cfg(feature = "mmap")]
+pub mod file;
+pub mod input;
+pub mod lp;
 pub mod model;
+pub mod owned;
+#[cfg(all(feature = "rayon", not(feature = "trace")))]
+mod parallel;
 pub mod parse;
+pub mod qplib;
+pub mod stream;
 pub mod types;
+pub mod write;
+pub use crate::owned::ParserOwned;
 pub use crate::types::Parser;
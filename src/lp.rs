@@ -0,0 +1,1670 @@
+//! Reading and writing the CPLEX LP format.
+//!
+//! `Model::from_lp` reads an LP file into the same `Model` an MPS file
+//! produces, so that both formats share the model, validation and export
+//! APIs, and `Model::to_lp` writes a `Model` back as LP text. The file is
+//! translated into the entries of the equivalent MPS file, which are checked
+//! as `Model::try_from` checks them:
+//!
+//! | LP                             | MPS                                  |
+//! |--------------------------------|--------------------------------------|
+//! | objective, `obj` if unnamed    | first `N` row, minus constant in RHS |
+//! | constraint, `cN` if unnamed    | `L`, `G` or `E` row and its RHS      |
+//! | `lower <= expression <= upper` | `G` row with RANGES `upper - lower`  |
+//! | bounds                         | `LO`, `UP`, `FX`, `FR`, `MI` bounds  |
+//! | general                        | columns inside INTORG/INTEND markers |
+//! | binary                         | `BV` bound                           |
+//! | semi-continuous                | `SC` bound                           |
+//! | SOS                            | SOS                                  |
+//! | `[ ... ] / 2` in the objective | QUADOBJ                              |
+//! | `[ ... ]` in a constraint      | QCMATRIX                             |
+//!
+//! The RHS, RANGES and BOUNDS sets are named `RHS`, `RNG` and `BND`. Columns
+//! are listed in the order their names first appear, and a column without
+//! any coefficient gets a zero objective coefficient, as `Model::to_mps`
+//! writes it. Unlike a negative `UP` bound in MPS, a negative upper bound
+//! keeps the lower bound of zero.
+//!
+//! A quadratic objective is stored as QUADOBJ stores it, with one entry
+//! `q` per pair of columns for `0.5 * x'Qx`: `[ a x ^ 2 + b x * y ] / 2`
+//! becomes `(x, x) = a` and `(x, y) = b / 2`. A quadratic constraint is
+//! stored as QCMATRIX stores it, as the full matrix of `x'Qx`: `[ a x ^ 2 +
+//! b x * y ]` becomes `(x, x) = a` and `(x, y) = (y, x) = b / 2`.
+//!
+//! Keywords are case-insensitive and must start a line. Comments start with
+//! a backslash; a `\Problem name:` comment names the model.
+//!
+//! `Model::to_lp` follows the same table the other way round. A constraint
+//! row with a range is written as `lower <= expression <= upper`, whatever
+//! its row type.
+use crate::error::{Diagnostics, Error, ErrorKind};
+use crate::model::builder::Builder;
+use crate::model::{Model, ModelOptions};
+use crate::stream::Event;
+use crate::types::{
+  BoundType, ObjectiveSense, RowType, SOSType, Section, VariableType,
+};
+use crate::write::format_value;
+use color_eyre::{eyre::eyre, Result};
+use fast_float2::FastFloat;
+use hashbrown::{HashMap, HashSet};
+use indexmap::{IndexMap, IndexSet};
+use std::io;
+
+/// A keyword that opens a section of an LP file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keyword {
+  Minimize,
+  Maximize,
+  SubjectTo,
+  Bounds,
+  General,
+  Binary,
+  SemiContinuous,
+  Sos,
+  End,
+}
+
+/// The spellings of every keyword, in lower case.
+const KEYWORDS: [(&str, Keyword); 26] = [
+  ("minimize", Keyword::Minimize),
+  ("minimise", Keyword::Minimize),
+  ("minimum", Keyword::Minimize),
+  ("min", Keyword::Minimize),
+  ("maximize", Keyword::Maximize),
+  ("maximise", Keyword::Maximize),
+  ("maximum", Keyword::Maximize),
+  ("max", Keyword::Maximize),
+  ("subject to", Keyword::SubjectTo),
+  ("such that", Keyword::SubjectTo),
+  ("st", Keyword::SubjectTo),
+  ("s.t.", Keyword::SubjectTo),
+  ("st.", Keyword::SubjectTo),
+  ("bounds", Keyword::Bounds),
+  ("bound", Keyword::Bounds),
+  ("generals", Keyword::General),
+  ("general", Keyword::General),
+  ("gen", Keyword::General),
+  ("binaries", Keyword::Binary),
+  ("binary", Keyword::Binary),
+  ("bin", Keyword::Binary),
+  ("semi-continuous", Keyword::SemiContinuous),
+  ("semis", Keyword::SemiContinuous),
+  ("semi", Keyword::SemiContinuous),
+  ("sos", Keyword::Sos),
+  ("end", Keyword::End),
+];
+
+/// Matches a keyword at the start of `line`, returning it and its length.
+fn keyword(line: &str) -> Option<(Keyword, usize)> {
+  KEYWORDS.iter().find_map(|&(phrase, keyword)| {
+    let mut rest = line;
+    for (i, word) in phrase.split(' ').enumerate() {
+      if i > 0 {
+        let trimmed = rest.trim_start_matches([' ', '\t']);
+        if trimmed.len() == rest.len() {
+          return None;
+        }
+        rest = trimmed;
+      }
+      if !rest.get(..word.len())?.eq_ignore_ascii_case(word) {
+        return None;
+      }
+      rest = &rest[word.len()..];
+    }
+    // A name such as `st1` or a label such as `bounds:` is not a keyword
+    let next = rest.trim_start_matches([' ', '\t']);
+    if next.len() == rest.len() && !rest.is_empty() || next.starts_with(':') {
+      return None;
+    }
+    Some((keyword, line.len() - rest.len()))
+  })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+  Keyword(Keyword),
+  Name,
+  Number,
+  Sign,
+  Relation,
+  Colon,
+  DoubleColon,
+  Open,
+  Close,
+  Slash,
+  Star,
+  Caret,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+  kind: Kind,
+  text: &'a str,
+  line: usize,
+  column: usize,
+}
+
+fn is_name_start(c: char) -> bool {
+  c.is_alphabetic() || "!\"#$%&(),;?@_`'{}|~".contains(c)
+}
+
+fn is_name_char(c: char) -> bool {
+  is_name_start(c) || c.is_ascii_digit() || c == '.' || c == '/'
+}
+
+/// The length of the number at the start of `s`, such as `2`, `.5` or
+/// `1.5e-3`.
+fn number_len(s: &str) -> Option<usize> {
+  let bytes = s.as_bytes();
+  let digits =
+    |i: usize| bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+  let mut end = digits(0);
+  let mut found = end > 0;
+  if bytes.get(end) == Some(&b'.') {
+    let fraction = digits(end + 1);
+    found |= fraction > 0;
+    end += 1 + fraction;
+  }
+  if !found {
+    return None;
+  }
+  if matches!(bytes.get(end), Some(b'e' | b'E')) {
+    let sign = usize::from(matches!(bytes.get(end + 1), Some(b'+' | b'-')));
+    let exponent = digits(end + 1 + sign);
+    if exponent > 0 {
+      end += 1 + sign + exponent;
+    }
+  }
+  Some(end)
+}
+
+/// Reads the token at the start of `s`, returning its kind and length.
+fn token(s: &str) -> Option<(Kind, usize)> {
+  let two = s.get(..2);
+  Some(match s.chars().next()? {
+    '0'..='9' | '.' => (Kind::Number, number_len(s)?),
+    '+' | '-' => (Kind::Sign, 1),
+    '<' | '>' | '=' => match two {
+      Some("<=" | ">=" | "=<" | "=>") => (Kind::Relation, 2),
+      _ => (Kind::Relation, 1),
+    },
+    ':' if two == Some("::") => (Kind::DoubleColon, 2),
+    ':' => (Kind::Colon, 1),
+    '[' => (Kind::Open, 1),
+    ']' => (Kind::Close, 1),
+    '/' => (Kind::Slash, 1),
+    '*' => (Kind::Star, 1),
+    '^' => (Kind::Caret, 1),
+    c if is_name_start(c) => {
+      (Kind::Name, s.find(|c| !is_name_char(c)).unwrap_or(s.len()))
+    }
+    _ => return None,
+  })
+}
+
+/// Splits `input` into tokens, returning them along with the name given by
+/// a `\Problem name:` comment.
+fn tokenize(input: &str) -> Result<(Vec<Token<'_>>, Option<&str>), Error> {
+  let mut tokens = Vec::new();
+  let mut name = None;
+  for (i, line) in input.lines().enumerate() {
+    let line = match line.split_once('\\') {
+      Some((line, comment)) => {
+        let problem = comment.trim().strip_prefix("Problem name:");
+        name = name.or(problem.map(str::trim));
+        line
+      }
+      None => line,
+    };
+    let mut at = line.len() - line.trim_start().len();
+    if let Some((keyword, length)) = keyword(&line[at..]) {
+      tokens.push(Token {
+        kind: Kind::Keyword(keyword),
+        text: &line[at..at + length],
+        line: i + 1,
+        column: at + 1,
+      });
+      at += length;
+    }
+    while let Some(c) = line[at..].chars().next() {
+      if c.is_whitespace() {
+        at += c.len_utf8();
+        continue;
+      }
+      let (kind, length) = token(&line[at..]).ok_or_else(|| {
+        Error::new(ErrorKind::Syntax, format!("unexpected character {:?}", c))
+          .with_token(c)
+          .at(i + 1, at + 1)
+      })?;
+      tokens.push(Token {
+        kind,
+        text: &line[at..at + length],
+        line: i + 1,
+        column: at + 1,
+      });
+      at += length;
+    }
+  }
+  Ok((tokens, name))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Relation {
+  Le,
+  Ge,
+  Eq,
+}
+
+impl Relation {
+  /// The relation with its sides swapped, e.g. `>=` for `<=`.
+  fn reversed(self) -> Self {
+    match self {
+      Relation::Le => Relation::Ge,
+      Relation::Ge => Relation::Le,
+      Relation::Eq => Relation::Eq,
+    }
+  }
+}
+
+/// A sum of linear and quadratic terms and a constant, with the terms of the
+/// same variables added up.
+#[derive(Debug, Default)]
+struct Expression<'a, T> {
+  linear: IndexMap<&'a str, T>,
+  quadratic: IndexMap<(&'a str, &'a str), T>,
+  constant: T,
+}
+
+impl<'a, T: FastFloat> Expression<'a, T> {
+  fn add_linear(&mut self, name: &'a str, value: T) {
+    let term = self.linear.entry(name).or_default();
+    *term = *term + value;
+  }
+
+  /// Adds `value * var1 * var2`, to the term of `var2 * var1` if there is
+  /// one.
+  fn add_quadratic(&mut self, var1: &'a str, var2: &'a str, value: T) {
+    let key = match self.quadratic.contains_key(&(var2, var1)) {
+      true => (var2, var1),
+      false => (var1, var2),
+    };
+    let term = self.quadratic.entry(key).or_default();
+    *term = *term + value;
+  }
+}
+
+/// A constraint `lower <= expression <= upper`.
+#[derive(Debug)]
+struct Constraint<'a, T> {
+  name: String,
+  expression: Expression<'a, T>,
+  lower: T,
+  upper: T,
+}
+
+/// A special ordered set and its members with their weights.
+#[derive(Debug)]
+struct Set<'a, T> {
+  name: String,
+  sos_type: SOSType,
+  members: Vec<(&'a str, T)>,
+}
+
+/// The contents of an LP file.
+struct Lp<'a, T> {
+  tokens: Vec<Token<'a>>,
+  position: usize,
+  /// Where the input ends, for errors at the end of the input
+  end: (usize, usize),
+  name: &'a str,
+  objective_sense: ObjectiveSense,
+  objective_name: &'a str,
+  objective: Expression<'a, T>,
+  constraints: Vec<Constraint<'a, T>>,
+  /// Every variable, in the order its name first appears
+  columns: IndexSet<&'a str>,
+  /// The lower and upper bounds given in the bounds section
+  bounds: IndexMap<&'a str, (Option<T>, Option<T>)>,
+  general: IndexSet<&'a str>,
+  binary: IndexSet<&'a str>,
+  semi_continuous: IndexSet<&'a str>,
+  sets: Vec<Set<'a, T>>,
+}
+
+impl<'a, T: FastFloat> Lp<'a, T> {
+  fn new(input: &'a str) -> Result<Self, Error> {
+    let (tokens, name) = tokenize(input)?;
+    let last = input.lines().last().unwrap_or_default();
+    Ok(Lp {
+      tokens,
+      position: 0,
+      end: (input.lines().count().max(1), last.len() + 1),
+      name: name.unwrap_or_default(),
+      objective_sense: ObjectiveSense::Min,
+      objective_name: "obj",
+      objective: Expression::default(),
+      constraints: Vec::new(),
+      columns: IndexSet::new(),
+      bounds: IndexMap::new(),
+      general: IndexSet::new(),
+      binary: IndexSet::new(),
+      semi_continuous: IndexSet::new(),
+      sets: Vec::new(),
+    })
+  }
+
+  fn kind(&self, ahead: usize) -> Option<Kind> {
+    self.tokens.get(self.position + ahead).map(|t| t.kind)
+  }
+
+  fn next(&mut self) -> Option<Token<'a>> {
+    let token = self.tokens.get(self.position).copied();
+    self.position += 1;
+    token
+  }
+
+  /// Whether the current section has ended.
+  fn at_section(&self) -> bool {
+    matches!(self.kind(0), None | Some(Kind::Keyword(_)))
+  }
+
+  /// An error at the current token.
+  fn error(&self, kind: ErrorKind, message: impl Into<String>) -> Error {
+    let error = Error::new(kind, message);
+    match self.tokens.get(self.position) {
+      Some(token) => error.with_token(token.text).at(token.line, token.column),
+      None => error.at(self.end.0, self.end.1),
+    }
+  }
+
+  fn syntax(&self, expected: &str) -> Error {
+    let found = match self.tokens.get(self.position) {
+      Some(token) => format!("{:?}", token.text),
+      None => "end of input".to_string(),
+    };
+    self.error(
+      ErrorKind::Syntax,
+      format!("expected {}: found {}", expected, found),
+    )
+  }
+
+  /// Reads the whole file.
+  fn file(&mut self) -> Result<(), Error> {
+    self.objective_sense = match self.kind(0) {
+      Some(Kind::Keyword(Keyword::Minimize)) => ObjectiveSense::Min,
+      Some(Kind::Keyword(Keyword::Maximize)) => ObjectiveSense::Max,
+      _ => {
+        return Err(
+          self
+            .error(ErrorKind::MissingSection, "missing objective section")
+            .with_hint("an LP file starts with Minimize or Maximize"),
+        )
+      }
+    };
+    self.next();
+    if let Some(name) = self.label() {
+      self.objective_name = name;
+    }
+    self.objective = self.expression(true)?;
+    loop {
+      if !self.at_section() {
+        return Err(self.syntax("a term or a section keyword"));
+      }
+      let keyword = match self.next() {
+        Some(Token {
+          kind: Kind::Keyword(keyword),
+          ..
+        }) => keyword,
+        _ => return Ok(()),
+      };
+      match keyword {
+        Keyword::Minimize | Keyword::Maximize => {
+          self.position -= 1;
+          return Err(
+            self
+              .error(ErrorKind::SectionOutOfOrder, "second objective section")
+              .with_hint("an LP file has a single objective"),
+          );
+        }
+        Keyword::SubjectTo => {
+          while !self.at_section() {
+            self.constraint()?;
+          }
+        }
+        Keyword::Bounds => {
+          while !self.at_section() {
+            self.bound()?;
+          }
+        }
+        Keyword::General | Keyword::Binary | Keyword::SemiContinuous => {
+          while !self.at_section() {
+            let name = self.variable()?;
+            match keyword {
+              Keyword::General => self.general.insert(name),
+              Keyword::Binary => self.binary.insert(name),
+              _ => self.semi_continuous.insert(name),
+            };
+          }
+        }
+        Keyword::Sos => {
+          while !self.at_section() {
+            self.sos()?;
+          }
+        }
+        // Anything after End is ignored
+        Keyword::End => return Ok(()),
+      }
+    }
+  }
+
+  /// Reads a `name:` label, if there is one.
+  fn label(&mut self) -> Option<&'a str> {
+    if self.kind(0) == Some(Kind::Name) && self.kind(1) == Some(Kind::Colon) {
+      let name = self.next()?.text;
+      self.next();
+      return Some(name);
+    }
+    None
+  }
+
+  fn variable(&mut self) -> Result<&'a str, Error> {
+    match self.kind(0) {
+      Some(Kind::Name) => {
+        let name = self.next().unwrap().text;
+        self.columns.insert(name);
+        Ok(name)
+      }
+      _ => Err(self.syntax("a variable name")),
+    }
+  }
+
+  fn number(&mut self) -> Result<T, Error> {
+    if self.kind(0) != Some(Kind::Number) {
+      return Err(self.syntax("a number"));
+    }
+    match fast_float2::parse::<T, _>(self.tokens[self.position].text) {
+      Ok(value) => {
+        self.next();
+        Ok(value)
+      }
+      Err(_) => Err(self.error(
+        ErrorKind::BadNumber,
+        format!("invalid number {:?}", self.tokens[self.position].text),
+      )),
+    }
+  }
+
+  /// Whether a signed number or infinity comes next, followed by a relation.
+  fn value_first(&self) -> bool {
+    let sign = usize::from(self.kind(0) == Some(Kind::Sign));
+    let value = match self.tokens.get(self.position + sign) {
+      Some(token) => token.kind == Kind::Number || is_infinity(token),
+      None => false,
+    };
+    value && self.kind(sign + 1) == Some(Kind::Relation)
+  }
+
+  /// Reads a signed number, `inf` or `infinity`.
+  fn value(&mut self) -> Result<T, Error> {
+    let negative = match self.kind(0) {
+      Some(Kind::Sign) => self.next().unwrap().text == "-",
+      _ => false,
+    };
+    let value = match self.tokens.get(self.position) {
+      Some(token) if is_infinity(token) => {
+        self.next();
+        T::INFINITY
+      }
+      _ => self.number()?,
+    };
+    Ok(if negative { -value } else { value })
+  }
+
+  fn relation(&mut self) -> Result<Relation, Error> {
+    if self.kind(0) != Some(Kind::Relation) {
+      return Err(self.syntax("<=, >= or ="));
+    }
+    Ok(match self.next().unwrap().text {
+      "<" | "<=" | "=<" => Relation::Le,
+      ">" | ">=" | "=>" => Relation::Ge,
+      _ => Relation::Eq,
+    })
+  }
+
+  /// Reads a sum of terms such as `3 x - y + [ x ^ 2 ] / 2 + 4`. Every term
+  /// but the first starts with a sign; the sum ends before the first term
+  /// that does not.
+  fn expression(
+    &mut self,
+    objective: bool,
+  ) -> Result<Expression<'a, T>, Error> {
+    let mut expression = Expression::default();
+    let mut first = true;
+    loop {
+      let signed = self.kind(0) == Some(Kind::Sign);
+      if !signed && !first {
+        return Ok(expression);
+      }
+      first = false;
+      let negative = signed && self.next().unwrap().text == "-";
+      let sign = |value: T| if negative { -value } else { value };
+      match self.kind(0) {
+        Some(Kind::Number) => {
+          let value = sign(self.number()?);
+          if self.kind(0) == Some(Kind::Name)
+            && self.kind(1) != Some(Kind::Colon)
+          {
+            let name = self.variable()?;
+            expression.add_linear(name, value);
+          } else {
+            expression.constant = expression.constant + value;
+          }
+        }
+        Some(Kind::Name) if self.kind(1) != Some(Kind::Colon) => {
+          let name = self.variable()?;
+          expression.add_linear(name, sign(T::from_u64(1)));
+        }
+        Some(Kind::Open) => {
+          self.quadratic(&mut expression, negative, objective)?
+        }
+        _ if signed => return Err(self.syntax("a term after the sign")),
+        _ => return Ok(expression),
+      }
+    }
+  }
+
+  /// Reads a bracketed sum of quadratic terms, followed by `/ 2` in the
+  /// objective, into `expression`.
+  fn quadratic(
+    &mut self,
+    expression: &mut Expression<'a, T>,
+    negative: bool,
+    objective: bool,
+  ) -> Result<(), Error> {
+    self.next();
+    let mut terms = Vec::new();
+    while self.kind(0) != Some(Kind::Close) {
+      let negative = match self.kind(0) {
+        Some(Kind::Sign) => self.next().unwrap().text == "-",
+        _ if terms.is_empty() => false,
+        _ => return Err(self.syntax("+, - or ]")),
+      };
+      let mut coefficient = match self.kind(0) {
+        Some(Kind::Number) => self.number()?,
+        _ => T::from_u64(1),
+      };
+      if negative {
+        coefficient = -coefficient;
+      }
+      let var1 = self.variable()?;
+      let var2 = match self.kind(0) {
+        Some(Kind::Caret) => {
+          self.next();
+          if self.kind(0) != Some(Kind::Number)
+            || self.tokens[self.position].text != "2"
+          {
+            return Err(self.syntax("the exponent 2"));
+          }
+          self.next();
+          var1
+        }
+        Some(Kind::Star) => {
+          self.next();
+          self.variable()?
+        }
+        _ => return Err(self.syntax("^ 2 or * and a second variable")),
+      };
+      terms.push((var1, var2, coefficient));
+    }
+    self.next();
+    let divisor = match self.kind(0) {
+      Some(Kind::Slash) => {
+        self.next();
+        self.number()?
+      }
+      _ => T::from_u64(1),
+    };
+    if objective && divisor != T::from_u64(2) {
+      return Err(
+        self
+          .syntax("/ 2")
+          .with_hint("quadratic objective terms are written as [ ... ] / 2"),
+      );
+    }
+    for (var1, var2, coefficient) in terms {
+      let coefficient = coefficient / divisor;
+      let coefficient = if negative { -coefficient } else { coefficient };
+      expression.add_quadratic(var1, var2, coefficient);
+    }
+    Ok(())
+  }
+
+  /// Reads a constraint such as `c1: x + y <= 4` or `c2: 1 <= x - y <= 3`.
+  fn constraint(&mut self) -> Result<(), Error> {
+    let label = self.label();
+    let start = self.position;
+    let first = match self.value_first() {
+      true => Some((self.value()?, self.relation()?)),
+      false => None,
+    };
+    let expression = self.expression(false)?;
+    let relation = self.relation()?;
+    let value = self.value()?;
+    let (lower, upper) = match (first, relation) {
+      (None, Relation::Le) => (T::NEG_INFINITY, value),
+      (None, Relation::Ge) => (value, T::INFINITY),
+      (None, Relation::Eq) => (value, value),
+      (Some((lower, Relation::Le)), Relation::Le) => (lower, value),
+      (Some((upper, Relation::Ge)), Relation::Ge) => (value, upper),
+      (Some(_), _) => {
+        self.position = start;
+        return Err(self.error(
+          ErrorKind::Syntax,
+          "a ranged constraint takes two <= or two >= relations",
+        ));
+      }
+    };
+    let constant = -expression.constant;
+    let (lower, upper) = (lower + constant, upper + constant);
+    if lower > upper {
+      self.position = start;
+      return Err(self.error(
+        ErrorKind::Conflict,
+        format!("empty range [{}, {}]", lower, upper),
+      ));
+    }
+    let name = match label {
+      Some(name) => name.to_string(),
+      None => format!("c{}", self.constraints.len() + 1),
+    };
+    self.constraints.push(Constraint {
+      name,
+      expression,
+      lower,
+      upper,
+    });
+    Ok(())
+  }
+
+  /// Reads a bound such as `x <= 4`, `-1 <= x <= 1`, `x = 2` or `x free`.
+  fn bound(&mut self) -> Result<(), Error> {
+    if self.value_first() {
+      let value = self.value()?;
+      let relation = self.relation()?.reversed();
+      let name = self.variable()?;
+      self.set_bound(name, relation, value);
+      if self.kind(0) == Some(Kind::Relation) {
+        let relation = self.relation()?;
+        let value = self.value()?;
+        self.set_bound(name, relation, value);
+      }
+      return Ok(());
+    }
+    let name = self.variable()?;
+    match self.tokens.get(self.position) {
+      Some(token)
+        if token.kind == Kind::Name
+          && token.text.eq_ignore_ascii_case("free") =>
+      {
+        self.next();
+        self.set_bound(name, Relation::Ge, T::NEG_INFINITY);
+        self.set_bound(name, Relation::Le, T::INFINITY);
+      }
+      _ => {
+        let relation = self.relation()?;
+        let value = self.value()?;
+        self.set_bound(name, relation, value);
+      }
+    }
+    Ok(())
+  }
+
+  fn set_bound(&mut self, name: &'a str, relation: Relation, value: T) {
+    let (lower, upper) = self.bounds.entry(name).or_default();
+    if relation != Relation::Le {
+      *lower = Some(value);
+    }
+    if relation != Relation::Ge {
+      *upper = Some(value);
+    }
+  }
+
+  /// Reads a set such as `s1: S1:: x:1 y:2`.
+  fn sos(&mut self) -> Result<(), Error> {
+    let label = match self.kind(2) {
+      Some(Kind::Name) => self.label(),
+      _ => None,
+    };
+    let sos_type = match self.tokens.get(self.position) {
+      Some(token) if token.text.eq_ignore_ascii_case("S1") => SOSType::S1,
+      Some(token) if token.text.eq_ignore_ascii_case("S2") => SOSType::S2,
+      _ => return Err(self.syntax("S1:: or S2::")),
+    };
+    self.next();
+    if self.kind(0) != Some(Kind::DoubleColon) {
+      return Err(self.syntax("::"));
+    }
+    self.next();
+    let mut members = Vec::new();
+    while self.kind(0) == Some(Kind::Name)
+      && self.kind(1) == Some(Kind::Colon)
+      && matches!(self.kind(2), Some(Kind::Number | Kind::Sign))
+    {
+      let name = self.variable()?;
+      self.next();
+      members.push((name, self.value()?));
+    }
+    let name = match label {
+      Some(name) => name.to_string(),
+      None => format!("sos{}", self.sets.len() + 1),
+    };
+    self.sets.push(Set {
+      name,
+      sos_type,
+      members,
+    });
+    Ok(())
+  }
+
+  /// The entries of the equivalent MPS file, in MPS order.
+  fn events(self) -> Vec<Event<T>> {
+    let mut events = Vec::new();
+    let objective_name = self.objective_name.to_string();
+    events.push(Event::Name(self.name.to_string()));
+    events.push(Event::ObjectiveSense(self.objective_sense));
+    events.push(Event::Section(Section::Rows));
+    events.push(Event::Row {
+      row_type: RowType::Nr,
+      row_name: objective_name.clone(),
+    });
+    let mut rhs = Vec::new();
+    let mut ranges = Vec::new();
+    if self.objective.constant != T::default() {
+      rhs.push((objective_name.clone(), -self.objective.constant));
+    }
+    for c in &self.constraints {
+      let (row_type, value, range) = row(c.lower, c.upper);
+      if value != T::default() {
+        rhs.push((c.name.clone(), value));
+      }
+      if let Some(range) = range {
+        ranges.push((c.name.clone(), range));
+      }
+      events.push(Event::Row {
+        row_type,
+        row_name: c.name.clone(),
+      });
+    }
+    events.push(Event::Section(Section::Columns));
+    let mut columns = self
+      .columns
+      .iter()
+      .map(|&name| (name, Vec::new()))
+      .collect::<IndexMap<_, _>>();
+    let rows = std::iter::once((&objective_name, &self.objective))
+      .chain(self.constraints.iter().map(|c| (&c.name, &c.expression)));
+    for (row_name, expression) in rows {
+      for (&column_name, &value) in &expression.linear {
+        columns[column_name].push((row_name.as_str(), value));
+      }
+    }
+    for (column_name, mut entries) in columns {
+      if entries.is_empty() {
+        entries.push((objective_name.as_str(), T::default()));
+      }
+      for (row_name, value) in entries {
+        events.push(Event::Coefficient {
+          column_name: column_name.to_string(),
+          row_name: row_name.to_string(),
+          value,
+          integer: self.general.contains(column_name),
+        });
+      }
+    }
+    if !rhs.is_empty() {
+      events.push(Event::Section(Section::Rhs));
+    }
+    for (row_name, value) in rhs {
+      events.push(Event::Rhs {
+        set_name: "RHS".to_string(),
+        row_name,
+        value,
+      });
+    }
+    if !ranges.is_empty() {
+      events.push(Event::Section(Section::Ranges));
+    }
+    for (row_name, value) in ranges {
+      events.push(Event::Range {
+        set_name: "RNG".to_string(),
+        row_name,
+        value,
+      });
+    }
+    let mut bounds = Vec::new();
+    for &column_name in &self.columns {
+      let (lower, upper) =
+        self.bounds.get(column_name).copied().unwrap_or_default();
+      let binary = self.binary.contains(column_name);
+      let semi_continuous = self.semi_continuous.contains(column_name);
+      for (bound_type, value) in
+        column_bounds(lower, upper, binary, semi_continuous)
+      {
+        bounds.push(Event::Bound {
+          bound_type,
+          bound_name: "BND".to_string(),
+          column_name: column_name.to_string(),
+          value,
+        });
+      }
+    }
+    if !bounds.is_empty() {
+      events.push(Event::Section(Section::Bounds));
+      events.extend(bounds);
+    }
+    if !self.sets.is_empty() {
+      events.push(Event::Section(Section::Sos));
+    }
+    for set in self.sets {
+      events.push(Event::SpecialOrderedSet {
+        sos_type: set.sos_type,
+        set_name: set.name,
+      });
+      for (var_name, weight) in set.members {
+        events.push(Event::SpecialOrderedSetMember {
+          var_name: var_name.to_string(),
+          weight,
+        });
+      }
+    }
+    if !self.objective.quadratic.is_empty() {
+      events.push(Event::Section(Section::QuadObj));
+    }
+    for (&(var1, var2), &value) in &self.objective.quadratic {
+      events.push(Event::QuadraticObjective {
+        var1: var1.to_string(),
+        var2: var2.to_string(),
+        coefficient: if var1 == var2 { value + value } else { value },
+      });
+    }
+    for c in &self.constraints {
+      if c.expression.quadratic.is_empty() {
+        continue;
+      }
+      events.push(Event::Section(Section::QcMatrix));
+      events.push(Event::QuadraticConstraint {
+        row_name: c.name.clone(),
+      });
+      for (&(var1, var2), &value) in &c.expression.quadratic {
+        let mut terms = vec![(var1, var2, value)];
+        if var1 != var2 {
+          let half = value / T::from_u64(2);
+          terms = vec![(var1, var2, half), (var2, var1, half)];
+        }
+        for (var1, var2, coefficient) in terms {
+          events.push(Event::QuadraticConstraintTerm {
+            var1: var1.to_string(),
+            var2: var2.to_string(),
+            coefficient,
+          });
+        }
+      }
+    }
+    events.push(Event::Section(Section::Endata));
+    events
+  }
+}
+
+fn is_infinity(token: &Token) -> bool {
+  token.kind == Kind::Name
+    && (token.text.eq_ignore_ascii_case("inf")
+      || token.text.eq_ignore_ascii_case("infinity"))
+}
+
+/// The row type, right-hand side and range of the constraint
+/// `lower <= expression <= upper`.
+pub(crate) fn row<T: FastFloat>(lower: T, upper: T) -> (RowType, T, Option<T>) {
+  match (lower == T::NEG_INFINITY, upper == T::INFINITY) {
+    _ if lower == upper => (RowType::Eq, lower, None),
+    (true, true) => (RowType::Nr, T::default(), None),
+    (true, false) => (RowType::Leq, upper, None),
+    (false, true) => (RowType::Geq, lower, None),
+    (false, false) => (RowType::Geq, lower, Some(upper + -lower)),
+  }
+}
+
+/// The BOUNDS entries of a column with the given LP bounds, which default to
+/// `0 <= x <= inf`.
+pub(crate) fn column_bounds<T: FastFloat>(
+  lower: Option<T>,
+  upper: Option<T>,
+  binary: bool,
+  semi_continuous: bool,
+) -> Vec<(BoundType, Option<T>)> {
+  let lower = lower.unwrap_or_default();
+  let upper = upper.unwrap_or(T::INFINITY);
+  if binary {
+    return vec![(BoundType::Bv, None)];
+  }
+  if !semi_continuous && lower == upper {
+    return vec![(BoundType::Fx, Some(lower))];
+  }
+  if !semi_continuous && lower == T::NEG_INFINITY && upper == T::INFINITY {
+    return vec![(BoundType::Fr, None)];
+  }
+  let mut bounds = Vec::new();
+  if lower == T::NEG_INFINITY {
+    bounds.push((BoundType::Mi, None));
+  } else if lower != T::default() || upper < T::default() {
+    // An explicit lower bound keeps a negative UP from freeing it
+    bounds.push((BoundType::Lo, Some(lower)));
+  }
+  if semi_continuous {
+    let upper = (upper != T::INFINITY).then_some(upper);
+    bounds.push((BoundType::Sc, upper));
+  } else if upper != T::INFINITY {
+    bounds.push((BoundType::Up, Some(upper)));
+  }
+  bounds
+}
+
+impl<T: FastFloat> Model<T> {
+  /// Reads a model in the CPLEX LP format; see the `lp` module for how it
+  /// maps onto MPS.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error wrapping an `Error` with the line and column for text
+  /// that cannot be read, and the first error `Model::try_from` would find
+  /// in the equivalent MPS file otherwise.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use mps::model::Model;
+  ///
+  /// let model = Model::<f64>::from_lp(
+  ///   "Maximize
+  ///  profit: 3 x + 2 y
+  /// Subject To
+  ///  labor: x + y <= 4
+  /// Bounds
+  ///  y <= 3
+  /// General
+  ///  x
+  /// End",
+  /// )?;
+  /// assert_eq!(model.objective_name.as_deref(), Some("profit"));
+  /// assert!(model.column_types.is_integer("x"));
+  /// # Ok::<(), color_eyre::Report>(())
+  /// ```
+  pub fn from_lp(input: &str) -> Result<Self> {
+    let mut lp = Lp::new(input)?;
+    lp.file()?;
+    let mut diagnostics = Diagnostics::default();
+    let mut builder = Builder::default();
+    for event in lp.events() {
+      builder.add(event, &mut diagnostics);
+    }
+    let model = builder.finish(ModelOptions::default(), &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(model)
+  }
+}
+
+/// The width past which the writer continues an expression on a new line.
+const LINE_WIDTH: usize = 79;
+
+/// Whether the reader would take `name` for a keyword or an infinite value
+/// at the start of a line.
+fn is_reserved(name: &str) -> bool {
+  keyword(name).is_some()
+    || name.eq_ignore_ascii_case("inf")
+    || name.eq_ignore_ascii_case("infinity")
+}
+
+/// Whether the reader reads `name` back as a single name.
+fn is_valid_name(name: &str) -> bool {
+  name.starts_with(is_name_start)
+    && name.chars().all(is_name_char)
+    && !is_reserved(name)
+}
+
+/// The names written for one kind of name: rows, columns or sets.
+///
+/// A valid name is written as is. Any other name has every character a name
+/// may not contain replaced with `_`, is prefixed with `_` if it does not
+/// start like a name or is a keyword, and gets a `_2`, `_3`, ... suffix if
+/// that clashes with another name.
+struct Names<'a> {
+  escaped: HashMap<&'a str, String>,
+  used: HashSet<String>,
+}
+
+impl<'a> Names<'a> {
+  fn new(names: impl Iterator<Item = &'a str> + Clone) -> Self {
+    let mut escaped = Names {
+      escaped: HashMap::new(),
+      used: names
+        .clone()
+        .filter(|name| is_valid_name(name))
+        .map(str::to_string)
+        .collect(),
+    };
+    for name in names {
+      if !is_valid_name(name) && !escaped.escaped.contains_key(name) {
+        let mut base = name
+          .chars()
+          .map(|c| if is_name_char(c) { c } else { '_' })
+          .collect::<String>();
+        if !base.starts_with(is_name_start) || is_reserved(&base) {
+          base.insert(0, '_');
+        }
+        let unique = escaped.unique(&base);
+        escaped.escaped.insert(name, unique);
+      }
+    }
+    escaped
+  }
+
+  /// `base`, or `base` with the first suffix that is not yet used, marked as
+  /// used.
+  fn unique(&mut self, base: &str) -> String {
+    let mut name = base.to_string();
+    let mut suffix = 1;
+    while self.used.contains(&name) {
+      suffix += 1;
+      name = format!("{}_{}", base, suffix);
+    }
+    self.used.insert(name.clone());
+    name
+  }
+
+  fn get<'b>(&'b self, name: &'b str) -> &'b str {
+    self.escaped.get(name).map_or(name, String::as_str)
+  }
+}
+
+/// Splits a formatted value into its sign and magnitude.
+fn signed<T: FastFloat>(value: T) -> (&'static str, String) {
+  let value = format_value(value);
+  match value.strip_prefix('-') {
+    Some(magnitude) => ("-", magnitude.to_string()),
+    None => ("+", value),
+  }
+}
+
+/// A term such as `+ 3 x`, `- x`, `2 x ^ 2` or, without variables, `+ 4`,
+/// leaving out a coefficient of one and, for the first term of a sum, a plus
+/// sign.
+fn term<T: FastFloat>(value: T, variables: &str, first: bool) -> String {
+  let (sign, magnitude) = signed(value);
+  let sign = match (sign, first) {
+    ("+", true) => String::new(),
+    (sign, _) => format!("{} ", sign),
+  };
+  match (magnitude.as_str(), variables) {
+    (_, "") => format!("{}{}", sign, magnitude),
+    ("1", _) => format!("{}{}", sign, variables),
+    _ => format!("{}{} {}", sign, magnitude, variables),
+  }
+}
+
+/// The terms of `x'Qx` for the entries of `Q`, with the entries `(x, y)` and
+/// `(y, x)` added up. With `triangle`, an entry `(x, y)` stands for both, as
+/// in `QuadraticObjectiveMap`.
+fn quadratic_terms<T: FastFloat>(
+  entries: &IndexMap<(String, String), T>,
+  triangle: bool,
+) -> IndexMap<(&str, &str), T> {
+  let mut terms = IndexMap::<(&str, &str), T>::new();
+  for ((var1, var2), &value) in entries {
+    let (var1, var2) = (var1.as_str(), var2.as_str());
+    let value = match triangle && var1 != var2 {
+      true => value + value,
+      false => value,
+    };
+    let key = match terms.contains_key(&(var2, var1)) {
+      true => (var2, var1),
+      false => (var1, var2),
+    };
+    let term = terms.entry(key).or_default();
+    *term = *term + value;
+  }
+  terms
+}
+
+/// Accumulates LP text, wrapping long lines.
+struct LpWriter {
+  output: String,
+}
+
+impl LpWriter {
+  fn line(&mut self, line: &str) {
+    self.output.push_str(line);
+    self.output.push('\n');
+  }
+
+  /// Writes `start` followed by `tokens`, separated by blanks, continuing on
+  /// an indented line before a token that would run past `LINE_WIDTH`.
+  fn wrapped(&mut self, start: &str, tokens: impl IntoIterator<Item = String>) {
+    let mut line = start.to_string();
+    for token in tokens {
+      if line.len() + 1 + token.len() > LINE_WIDTH && !line.trim().is_empty() {
+        self.line(&line);
+        line = "   ".to_string();
+      }
+      if !line.ends_with(' ') {
+        line.push(' ');
+      }
+      line.push_str(&token);
+    }
+    self.line(&line);
+  }
+}
+
+impl<T: FastFloat> Model<T> {
+  /// Serialises the model to CPLEX LP text; see the `lp` module for how MPS
+  /// maps onto LP.
+  ///
+  /// Constraints are written in ROWS order, free rows as `-inf <= ... <=
+  /// inf`, and the bounds, row intervals and objective constant follow the
+  /// current selection, as for `to_sparse`. A column without any coefficient
+  /// is written with a zero objective coefficient and a row without any
+  /// with a zero coefficient on the first column, so that neither is lost.
+  /// Names the reader would not read back, such as `x[1]`, `2y` or `st`, are
+  /// escaped, e.g. to `x_1_`, `_2y` and `_st`. User cuts, cones, indicators,
+  /// lazy constraints, branching priorities, `reference_row`,
+  /// `free_row_values` and `options` are not written.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the bounds or row intervals cannot be resolved, or
+  /// if a row without any coefficient has no column to be written with.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use mps::model::Model;
+  ///
+  /// let model = Model::<f64>::from_lp(
+  ///   "Maximize
+  ///  profit: 3 x + 2 y
+  /// Subject To
+  ///  labor: x + y <= 4
+  /// End",
+  /// )?;
+  /// assert!(model.to_lp()?.contains(" labor: x + y <= 4\n"));
+  /// # Ok::<(), color_eyre::Report>(())
+  /// ```
+  pub fn to_lp(&self) -> Result<String> {
+    let column_bounds = self.column_bounds()?;
+    let row_bounds = self.row_bounds()?;
+    let mut rows = Names::new(self.row_types.0.keys().map(String::as_str));
+    let objective_name = match &self.objective_name {
+      Some(name) => rows.get(name).to_string(),
+      None => rows.unique("obj"),
+    };
+    let columns = Names::new(self.column_types.0.keys().map(String::as_str));
+    let sets =
+      Names::new(self.special_ordered_sets.0.keys().map(String::as_str));
+    let mut linear = IndexMap::<&str, Vec<(&str, T)>>::new();
+    for ((row_name, column_name), &value) in &self.values.0 {
+      if self.row_types.0.contains_key(row_name) {
+        let row = linear.entry(row_name.as_str()).or_default();
+        row.push((column_name.as_str(), value));
+      }
+    }
+    let objective = self.objective_name.as_deref();
+    let mut objective_terms = objective
+      .and_then(|o| linear.get(o))
+      .cloned()
+      .unwrap_or_default();
+    let mut written = HashSet::new();
+    for terms in linear.values() {
+      written.extend(terms.iter().map(|&(column_name, _)| column_name));
+    }
+    for column_name in self.column_types.0.keys() {
+      if !written.contains(column_name.as_str()) {
+        objective_terms.push((column_name.as_str(), T::default()));
+      }
+    }
+    let expression = |linear: &[(&str, T)],
+                      quadratic: IndexMap<(&str, &str), T>,
+                      objective: bool| {
+      let mut tokens = linear
+        .iter()
+        .enumerate()
+        .map(|(i, &(column_name, value))| {
+          term(value, columns.get(column_name), i == 0)
+        })
+        .collect::<Vec<_>>();
+      if !quadratic.is_empty() {
+        tokens.push(if tokens.is_empty() { "[" } else { "+ [" }.to_string());
+        for (i, (&(var1, var2), &value)) in quadratic.iter().enumerate() {
+          let variables = match var1 == var2 {
+            true => format!("{} ^ 2", columns.get(var1)),
+            false => format!("{} * {}", columns.get(var1), columns.get(var2)),
+          };
+          tokens.push(term(value, &variables, i == 0));
+        }
+        tokens.push(if objective { "] / 2" } else { "]" }.to_string());
+      }
+      tokens
+    };
+    let mut w = LpWriter {
+      output: String::new(),
+    };
+    if !self.name.is_empty() {
+      w.line(&format!("\\Problem name: {}", self.name));
+      w.line("");
+    }
+    w.line(match self.objective_sense {
+      Some(ObjectiveSense::Max) => "Maximize",
+      _ => "Minimize",
+    });
+    let mut tokens = expression(
+      &objective_terms,
+      quadratic_terms(&self.quadratic_objective.0, true),
+      true,
+    );
+    let rhs = self.rhs.select(self.options.rhs.as_deref())?;
+    if let Some(&b) = objective.and_then(|o| rhs?.1.get(o)) {
+      if b != T::default() {
+        tokens.push(term(-b, "", tokens.is_empty()));
+      }
+    }
+    w.wrapped(&format!(" {}:", objective_name), tokens);
+    w.line("Subject To");
+    let first_column = self.column_types.0.keys().next();
+    for (row_name, row_type) in &self.row_types.0 {
+      if Some(row_name.as_str()) == objective {
+        continue;
+      }
+      let (lower, upper) = match row_type {
+        RowType::Nr => (T::NEG_INFINITY, T::INFINITY),
+        _ => {
+          let bounds = &row_bounds.0[row_name];
+          (bounds.lower, bounds.upper)
+        }
+      };
+      let quadratic = match self.quadratic_constraints.0.get(row_name) {
+        Some(entries) => quadratic_terms(entries, false),
+        None => IndexMap::new(),
+      };
+      let mut tokens = expression(
+        linear.get(row_name.as_str()).map_or(&[], Vec::as_slice),
+        quadratic,
+        false,
+      );
+      if tokens.is_empty() {
+        let column_name = first_column.ok_or_else(|| {
+          eyre!(format!(
+            "row {:?} has no column to be written with",
+            row_name
+          ))
+        })?;
+        tokens.push(format!("0 {}", columns.get(column_name)));
+      }
+      let start = format!(" {}:", rows.get(row_name));
+      match (lower == T::NEG_INFINITY, upper == T::INFINITY) {
+        _ if lower == upper => {
+          tokens.push(format!("= {}", format_value(lower)))
+        }
+        (false, false) | (true, true) => {
+          tokens.insert(0, format!("{} <=", format_value(lower)));
+          tokens.push(format!("<= {}", format_value(upper)));
+        }
+        (true, false) => tokens.push(format!("<= {}", format_value(upper))),
+        (false, true) => tokens.push(format!(">= {}", format_value(lower))),
+      }
+      w.wrapped(&start, tokens);
+    }
+    let mut bounds = Vec::new();
+    let mut general = Vec::new();
+    let mut binary = Vec::new();
+    let mut semi_continuous = Vec::new();
+    for (column_name, b) in &column_bounds.columns {
+      let name = columns.get(column_name);
+      let (zero, one) = (T::default(), T::from_u64(1));
+      let is_binary = b.kind == VariableType::Binary;
+      if is_binary && b.lower == zero && b.upper == one {
+        binary.push(name);
+        continue;
+      }
+      if matches!(
+        b.kind,
+        VariableType::Integer
+          | VariableType::Binary
+          | VariableType::SemiInteger
+      ) {
+        general.push(name);
+      }
+      if matches!(
+        b.kind,
+        VariableType::SemiContinuous | VariableType::SemiInteger
+      ) {
+        semi_continuous.push(name);
+      }
+      let (lower, upper) = (format_value(b.lower), format_value(b.upper));
+      bounds.push(match (b.lower, b.upper) {
+        (l, u) if l == u => format!(" {} = {}", name, lower),
+        (l, u) if l == T::NEG_INFINITY && u == T::INFINITY => {
+          format!(" {} free", name)
+        }
+        (l, u) if l == zero && u == T::INFINITY => continue,
+        (l, _) if l == zero => format!(" {} <= {}", name, upper),
+        (_, u) if u == T::INFINITY => format!(" {} >= {}", name, lower),
+        _ => format!(" {} <= {} <= {}", lower, name, upper),
+      });
+    }
+    if !bounds.is_empty() {
+      w.line("Bounds");
+      for line in &bounds {
+        w.line(line);
+      }
+    }
+    // One name per line, so that no two names make up a keyword
+    for (header, names) in [
+      ("General", &general),
+      ("Binary", &binary),
+      ("Semi-Continuous", &semi_continuous),
+    ] {
+      if !names.is_empty() {
+        w.line(header);
+        for name in names {
+          w.line(&format!(" {}", name));
+        }
+      }
+    }
+    if !self.special_ordered_sets.0.is_empty() {
+      w.line("SOS");
+      for (set_name, set) in &self.special_ordered_sets.0 {
+        let sos_type = match set.sos_type {
+          SOSType::S1 => "S1::",
+          SOSType::S2 => "S2::",
+        };
+        let start = format!(" {}: {}", sets.get(set_name), sos_type);
+        let members = set.members.iter().map(|(var_name, &weight)| {
+          format!("{}:{}", columns.get(var_name), format_value(weight))
+        });
+        w.wrapped(&start, members);
+      }
+    }
+    w.line("End");
+    Ok(w.output)
+  }
+
+  /// Writes the output of `to_lp` to `writer`.
+  pub fn write_lp<W: io::Write>(&self, writer: &mut W) -> Result<()> {
+    writer.write_all(self.to_lp()?.as_bytes())?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::error::ErrorKind;
+  use crate::Parser;
+
+  const LP: &str = r"\Problem name: LPTEST
+Maximize
+ cost: 2 x1 + 3 x2 - x3 + 4 + [ 4 x1 ^ 2 + 2 x1 * x2 ] / 2
+Subject To
+ c1: x1 + x2 + x1 <= 20
+ -4.5 <= x2 - x3 <= 0
+ qc: x1 + [ x1 ^ 2 + 4 x1 * x2 ] <= 8
+Bounds
+ -1 <= x1 <= 5
+ x2 <= -2
+ x3 free
+ x4 = 3
+ x5 <= 10
+General
+ x2
+Binary
+ x6
+Semi-Continuous
+ x5
+SOS
+ s1: S1:: x1:1 x2:2
+ S2:: x3:1 x4:2.5
+End
+";
+
+  const MPS: &str = "NAME          LPTEST
+OBJSENSE
+    MAX
+ROWS
+ N  cost
+ L  c1
+ G  c2
+ L  qc
+COLUMNS
+    x1        cost         2   c1           2
+    x1        qc           1
+    MARKER                 'MARKER'                 'INTORG'
+    x2        cost         3   c1           1
+    x2        c2           1
+    MARKER                 'MARKER'                 'INTEND'
+    x3        cost        -1   c2          -1
+    x4        cost         0
+    x5        cost         0
+    x6        cost         0
+RHS
+    RHS       cost        -4   c1          20
+    RHS       c2        -4.5   qc           8
+RANGES
+    RNG       c2         4.5
+BOUNDS
+ LO BND       x1          -1
+ UP BND       x1           5
+ LO BND       x2           0
+ UP BND       x2          -2
+ FR BND       x3
+ FX BND       x4           3
+ SC BND       x5          10
+ BV BND       x6
+SOS
+ S1 s1
+    x1 1
+    x2 2
+ S2 sos2
+    x3 1
+    x4 2.5
+QUADOBJ
+    x1        x1           4
+    x1        x2           1
+QCMATRIX      qc
+    x1        x1           1
+    x1        x2           2
+    x2        x1           2
+ENDATA
+";
+
+  #[test]
+  fn test_from_lp() -> Result<()> {
+    let model = Model::<f64>::from_lp(LP)?;
+    assert_eq!(model, Model::try_from(Parser::<f64>::parse(MPS)?)?);
+    let row_bounds = model.row_bounds()?;
+    assert_eq!(
+      row_bounds.get("c2").map(|r| (r.lower, r.upper)),
+      Some((-4.5, 0.0))
+    );
+    assert_eq!(model.to_sparse()?.objective_offset, 4.0);
+    Ok(())
+  }
+
+  #[test]
+  fn test_to_lp() -> Result<()> {
+    let model = Model::<f64>::from_lp(LP)?;
+    let written = model.to_lp()?;
+    assert!(written.starts_with("\\Problem name: LPTEST\n"));
+    assert!(written.contains(" c2: -4.5 <= x2 - x3 <= 0\n"));
+    assert!(written.contains(" qc: x1 + [ x1 ^ 2 + 4 x1 * x2 ] <= 8\n"));
+    assert_eq!(Model::from_lp(&written)?, model);
+    Ok(())
+  }
+
+  #[test]
+  fn test_to_lp_from_mps() -> Result<()> {
+    let model = Model::try_from(Parser::<f64>::parse(
+      "NAME          ESCAPE
+ROWS
+ N  obj
+ L  lim
+ N  spare
+ E  st
+COLUMNS
+ x[1] obj 1 lim 1
+ x[1] spare 1
+ 2y obj -1 st 1
+ x_1_ lim 2.5
+RHS
+ RHS obj 2 lim 4
+ RHS st 1
+RANGES
+ RNG lim 3
+BOUNDS
+ UP BND 2y -1
+ UI BND x_1_ 5
+QMATRIX
+ x[1] x[1] 2
+ x[1] 2y 1
+ 2y x[1] 1
+ENDATA",
+    )?)?;
+    let expected = r"\Problem name: ESCAPE
+
+Minimize
+ obj: x_1__2 - _2y + [ 2 x_1__2 ^ 2 + 2 x_1__2 * _2y ] / 2 - 2
+Subject To
+ lim: 1 <= x_1__2 + 2.5 x_1_ <= 4
+ spare: -inf <= x_1__2 <= inf
+ _st: _2y = 1
+Bounds
+ -inf <= _2y <= -1
+ x_1_ <= 5
+General
+ x_1_
+End
+";
+    let written = model.to_lp()?;
+    assert_eq!(written, expected);
+    let reread = Model::<f64>::from_lp(&written)?;
+    assert_eq!(reread.to_sparse()?.objective_offset, -2.0);
+    assert_eq!(reread.free_rows(), ["spare"]);
+    assert_eq!(reread.to_lp()?, expected);
+    Ok(())
+  }
+
+  #[test]
+  fn test_to_lp_wraps_lines() -> Result<()> {
+    let terms = (1..=40).map(|i| format!("x{}", i)).collect::<Vec<_>>();
+    let model = Model::<f64>::from_lp(&format!(
+      "Minimize\n obj: {}\nEnd\n",
+      terms.join(" + ")
+    ))?;
+    let written = model.to_lp()?;
+    assert!(written.lines().all(|line| line.len() <= LINE_WIDTH));
+    assert!(written.contains("\n   + x"));
+    assert_eq!(Model::from_lp(&written)?, model);
+    Ok(())
+  }
+
+  #[test]
+  fn test_keywords() -> Result<()> {
+    let model = Model::<f64>::from_lp(
+      "minimise \\ a comment
+ x - 2.5e1 y
+s.t.
+ bounds: x + y >= 1
+ -x <= 3 \\ unnamed
+bounds
+ -inf <= y <= +inf
+ x <= infinity
+gen x
+END
+anything after the end
+",
+    )?;
+    assert_eq!(model.objective_sense, Some(ObjectiveSense::Min));
+    assert_eq!(model.objective_name.as_deref(), Some("obj"));
+    assert_eq!(
+      model.row_types.0.keys().collect::<Vec<_>>(),
+      ["obj", "bounds", "c2"]
+    );
+    assert_eq!(model.values.0[&("obj".to_string(), "y".to_string())], -25.0);
+    assert_eq!(model.bounds.0["BND"].len(), 1);
+    assert!(model.column_types.is_integer("x"));
+    Ok(())
+  }
+
+  #[test]
+  fn test_errors() {
+    let cases = [
+      (
+        "Subject To\n c: x <= 1\n",
+        ErrorKind::MissingSection,
+        Some((1, 1)),
+      ),
+      (
+        "Minimize\n x +\nSubject To\n",
+        ErrorKind::Syntax,
+        Some((3, 1)),
+      ),
+      (
+        "Minimize\n x\nSubject To\n c: x <= y\n",
+        ErrorKind::Syntax,
+        Some((4, 10)),
+      ),
+      (
+        "Minimize\n x\nBounds\n x >= 2 y\n",
+        ErrorKind::Syntax,
+        Some((4, 10)),
+      ),
+      ("Minimize\n 2 .x\n", ErrorKind::Syntax, Some((2, 4))),
+      (
+        "Minimize\n x\nst\n r: 1 <= x >= 0\n",
+        ErrorKind::Syntax,
+        Some((4, 5)),
+      ),
+      (
+        "Minimize\n x\nst\n r: 2 <= x <= 1\n",
+        ErrorKind::Conflict,
+        Some((4, 5)),
+      ),
+      (
+        "Minimize\n x\nMaximize\n x\n",
+        ErrorKind::SectionOutOfOrder,
+        Some((3, 1)),
+      ),
+      ("Minimize\n [ x ^ 2 ]\n", ErrorKind::Syntax, None),
+      (
+        "Minimize\n [ x ^ 3 ] / 2\n",
+        ErrorKind::Syntax,
+        Some((2, 8)),
+      ),
+      (
+        "Minimize\n x\nSOS\n s: S3:: x:1\n",
+        ErrorKind::Syntax,
+        Some((4, 5)),
+      ),
+      (
+        "Minimize\n x\nst\n c: x <= 1\n c: x >= 0\n",
+        ErrorKind::Conflict,
+        None,
+      ),
+    ];
+    for (input, kind, at) in cases {
+      let report = Model::<f64>::from_lp(input).unwrap_err();
+      let error = report.downcast_ref::<Error>().unwrap();
+      assert_eq!(error.kind, kind, "{:?}", input);
+      if let Some(at) = at {
+        assert_eq!(error.line.zip(error.column), Some(at), "{:?}", input);
+      }
+    }
+  }
+}
@@ -1,8 +1,8 @@
 mod cli;
 use clap::Parser;
 use cli::Cli;
-use color_eyre::{eyre::eyre, Result};
-use std::fs;
+use color_eyre::Result;
+use std::process;
 cfg_if::cfg_if! {
   if #[cfg(feature = "trace")] {
     use nom_locate::LocatedSpan;
@@ -12,24 +12,57 @@ cfg_if::cfg_if! {
 
 fn main() -> Result<()> {
   let args = Cli::parse();
-  let contents = fs::read_to_string(args.input_path)?;
-  cfg_if::cfg_if! {
-      if #[cfg(feature = "trace")] {
-        let info = TracableInfo::new().forward(true).backward(true);
-        match mps::Parser::<f32>::mps_file(LocatedSpan::new_extra(&contents, info)) {
-          Ok((_, parsed)) => {
-            println!("{:#?}", parsed);
-            Ok(())
-          },
-          Err(e) => Err(eyre!(e.to_string())),
-        }?;
+  #[cfg(feature = "cache")]
+  if let Some(cache_path) = &args.cache {
+    let parsed = mps::cache::load_or_parse::<mps::ParserOwned<f32>>(
+      &args.input_path,
+      cache_path,
+    )?;
+    println!("{:#?}", parsed.as_parser());
+    return Ok(());
+  }
+  print_parsed(&args.input_path)
+}
+
+/// Prints `e` against the source it was found in and exits.
+fn exit_with(e: mps::error::Error, contents: &str) -> ! {
+  eprint!("{}", e.render(contents));
+  process::exit(1);
+}
+
+cfg_if::cfg_if! {
+  if #[cfg(feature = "trace")] {
+    fn print_parsed(path: &str) -> Result<()> {
+      let contents = mps::input::read_to_string(path)?;
+      let info = TracableInfo::new().forward(true).backward(true);
+      if let Ok((_, parsed)) = mps::Parser::<f32>::mps_file(LocatedSpan::new_extra(&contents, info)) {
+        println!("{:#?}", parsed);
         nom_tracable::cumulative_histogram();
-      } else {
-        match mps::Parser::<f32>::mps_file(&contents) {
-          Ok(parsed) => Ok(println!("{:#?}", parsed)),
-          Err(e) => Err(eyre!(e.to_string())),
-        }?;
+        return Ok(());
+      }
+      match mps::Parser::<f32>::parse(&contents) {
+        Ok(parsed) => println!("{:#?}", parsed),
+        Err(e) => exit_with(e, &contents),
+      }
+      Ok(())
+    }
+  } else if #[cfg(feature = "mmap")] {
+    fn print_parsed(path: &str) -> Result<()> {
+      match mps::Parser::<f32>::parse_file(path) {
+        Ok(parsed) => println!("{:#?}", parsed.parser()),
+        Err(e) if e.kind == mps::error::ErrorKind::Io => return Err(e.into()),
+        Err(e) => exit_with(e, &mps::input::read_to_string(path)?),
+      }
+      Ok(())
+    }
+  } else {
+    fn print_parsed(path: &str) -> Result<()> {
+      let contents = mps::input::read_to_string(path)?;
+      match mps::Parser::<f32>::parse(&contents) {
+        Ok(parsed) => println!("{:#?}", parsed),
+        Err(e) => exit_with(e, &contents),
       }
+      Ok(())
+    }
   }
-  Ok(())
 }
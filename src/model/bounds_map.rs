@@ -1,42 +1,87 @@
-use crate::types::{BoundType, Bounds};
-use color_eyre::{eyre::eyre, Result};
+use crate::error::{Diagnostics, Error, ErrorKind};
+use crate::model::set_map;
+use crate::types::{BoundType, Bounds, BoundsLine, Section};
+use color_eyre::Result;
 use fast_float2::FastFloat;
 use hashbrown::HashSet;
 use indexmap::IndexMap;
-#[cfg(feature = "serde")]
-use serde::Serialize;
+
+/// The `(column, bound type) -> value` entries of a single bound set.
+pub type BoundSet<T> = IndexMap<(String, BoundType), Option<T>>;
 
 #[derive(Debug, Default, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
-pub struct BoundsMap<T: FastFloat>(
-  pub IndexMap<String, IndexMap<(String, BoundType), Option<T>>>,
-);
+pub struct BoundsMap<T: FastFloat>(pub IndexMap<String, BoundSet<T>>);
 
 impl<T: FastFloat> TryFrom<(&Bounds<'_, T>, &HashSet<&str>)> for BoundsMap<T> {
   type Error = color_eyre::Report;
 
   fn try_from(t: (&Bounds<'_, T>, &HashSet<&str>)) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let bounds = BoundsMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(bounds)
+  }
+}
+
+impl<T: FastFloat> BoundsMap<T> {
+  /// Builds the map, recording every unknown column and duplicate entry in
+  /// `diagnostics` and skipping the offending entries.
+  pub(crate) fn build(
+    t: (&Bounds<'_, T>, &HashSet<&str>),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
     let mut bounds = BoundsMap(IndexMap::new());
     let (bounds_lines, column_names) = t;
     for b in bounds_lines {
-      match column_names.get(b.column_name.trim()) {
-        Some(_) => bounds.insert(
-          b.bound_name,
-          b.column_name,
-          b.bound_type.clone(),
-          b.value,
-        ),
-        None => Err(eyre!(format!(
-          "specified bound {:?} of type {:?} for unspecified column {:?}",
-          b.bound_name, b.bound_type, b.column_name
-        ))),
-      }?;
+      bounds.add(b, |name| column_names.contains(name), diagnostics);
     }
-    Ok(bounds)
+    bounds
+  }
+
+  /// Adds a single BOUNDS line, as `build` does. `is_column` tells whether a
+  /// column was declared in COLUMNS.
+  pub(crate) fn add(
+    &mut self,
+    b: &BoundsLine<'_, T>,
+    is_column: impl Fn(&str) -> bool,
+    diagnostics: &mut Diagnostics,
+  ) {
+    let bound_name = b.bound_name.trim();
+    if is_column(b.column_name.trim()) {
+      let inserted =
+        self.insert(b.bound_name, b.column_name, b.bound_type.clone(), b.value);
+      diagnostics.check(inserted, &[bound_name]);
+    } else {
+      diagnostics.error(
+        Error::new(
+          ErrorKind::UnknownColumn,
+          format!(
+            "specified bound {:?} of type {:?} for unspecified column {:?}",
+            b.bound_name, b.bound_type, b.column_name
+          ),
+        )
+        .in_section(Section::Bounds)
+        .with_token(b.column_name.trim()),
+        &[bound_name],
+      );
+    }
+  }
+
+  /// Lists the BOUNDS set names in file order.
+  pub fn names(&self) -> Vec<&str> {
+    set_map::names(&self.0)
+  }
+
+  /// Selects the named BOUNDS set, or the first one when `bound_name` is `None`.
+  ///
+  /// Returns `Ok(None)` when the section is empty.
+  pub fn select(
+    &self,
+    bound_name: Option<&str>,
+  ) -> Result<Option<(&str, &BoundSet<T>)>> {
+    set_map::select(&self.0, bound_name, Section::Bounds)
   }
-}
 
-impl<T: FastFloat> BoundsMap<T> {
   fn insert(
     &mut self,
     bound_name: &str,
@@ -53,10 +98,17 @@ impl<T: FastFloat> BoundsMap<T> {
       }
       Some(bounds) => {
         match bounds.insert((column_name.trim().to_string(), bound_type), value) {
-          Some(conflicting_value) => Err(eyre!(format!(
-            "duplicate entry in BOUNDS {:?} for column {:?}: found {:?} and {:?}",
-            bound_name, column_name, value, conflicting_value
-          ))),
+          Some(conflicting_value) => Err(
+            Error::new(
+              ErrorKind::DuplicateEntry,
+              format!(
+                "duplicate entry in BOUNDS {:?} for column {:?}: found {:?} and {:?}",
+                bound_name, column_name, value, conflicting_value
+              ),
+            )
+            .in_section(Section::Bounds)
+            .with_token(column_name.trim()),
+          ),
           None => Ok(()),
         }
       }
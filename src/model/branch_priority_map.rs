@@ -0,0 +1,81 @@
+use crate::error::{Diagnostics, Error, ErrorKind, InSection};
+use crate::model::column_type_map::ColumnTypeMap;
+use crate::types::{
+  BranchDirection, BranchPriorities, BranchPriority, Section,
+};
+use color_eyre::Result;
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The branching priority and preferred direction of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Branching {
+  pub priority: i32,
+  pub direction: BranchDirection,
+}
+
+/// Branching directives keyed by column name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BranchPriorityMap(pub IndexMap<String, Branching>);
+
+impl TryFrom<(&BranchPriorities<'_>, &ColumnTypeMap)> for BranchPriorityMap {
+  type Error = color_eyre::Report;
+
+  fn try_from(t: (&BranchPriorities<'_>, &ColumnTypeMap)) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let branch_priorities = BranchPriorityMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(branch_priorities)
+  }
+}
+
+impl BranchPriorityMap {
+  /// Builds the map, recording every unknown column and duplicate entry in
+  /// `diagnostics` and skipping the offending entries.
+  pub(crate) fn build(
+    t: (&BranchPriorities<'_>, &ColumnTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let mut branch_priorities = BranchPriorityMap(IndexMap::new());
+    let (branch_lines, column_types) = t;
+    for b in branch_lines {
+      branch_priorities.add(b, column_types, diagnostics);
+    }
+    branch_priorities
+  }
+
+  /// Adds a single BRANCH line, as `build` does.
+  pub(crate) fn add(
+    &mut self,
+    b: &BranchPriority<'_>,
+    column_types: &ColumnTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) {
+    let exists = column_types.exists(b.var_name).in_section(Section::Branch);
+    if diagnostics.check(exists, &[]).is_none() {
+      return;
+    }
+    let branching = Branching {
+      priority: b.priority,
+      direction: b.direction,
+    };
+    if let Some(conflicting) = self.0.insert(b.var_name.to_string(), branching)
+    {
+      diagnostics.error(
+        Error::new(
+          ErrorKind::DuplicateEntry,
+          format!(
+            "duplicate entry in BRANCH for column {:?}: found {:?} and {:?}",
+            b.var_name, branching, conflicting
+          ),
+        )
+        .in_section(Section::Branch)
+        .with_token(b.var_name),
+        &[],
+      );
+    }
+  }
+}
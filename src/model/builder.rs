@@ -0,0 +1,342 @@
+use crate::error::{Diagnostics, Error, ErrorKind};
+use crate::model::{Model, ModelOptions, RowTypeMap};
+use crate::stream::Event;
+use crate::types::{
+  BoundsLine, BranchPriority, ConeConstraint, ConeMember, ConeType,
+  IndicatorLine, LazyConstraintLine, QuadraticConstraint,
+  QuadraticObjectiveTerm, QuadraticTerm, RowLine, RowValuePair, SOSLine,
+  SOSMember, SOSType, Section, WideLine,
+};
+use fast_float2::FastFloat;
+
+/// Builds a `Model` from the events of a `stream::Reader`, one entry at a
+/// time, with the same checks as `Model::try_from(Parser)`.
+///
+/// Only the members of the current SOS set, cone, QMATRIX section or QCMATRIX
+/// block are buffered, since those entries are checked as a whole.
+#[derive(Default)]
+pub(crate) struct Builder<T: FastFloat> {
+  model: Model<T>,
+  section: Option<Section>,
+  /// Rows and user cuts, which COLUMNS, RHS and RANGES may reference
+  known_rows: RowTypeMap,
+  objective_name: Option<String>,
+  /// A QSECTION or QUADOBJ section was read, so QMATRIX is ignored
+  quadratic_section: bool,
+  quadratic_matrix: Vec<(String, String, T)>,
+  set: Option<(SOSType, String)>,
+  set_members: Vec<(String, T)>,
+  cone: Option<(String, ConeType)>,
+  cone_members: Vec<String>,
+  quadratic_constraint: Option<String>,
+  quadratic_terms: Vec<(String, String, T)>,
+}
+
+impl<T: FastFloat> Builder<T> {
+  /// Adds a single event, recording every problem in `diagnostics` and
+  /// skipping the offending entries.
+  pub(crate) fn add(&mut self, event: Event<T>, diagnostics: &mut Diagnostics) {
+    let model = &mut self.model;
+    match event {
+      Event::Section(section) => {
+        self.flush(diagnostics);
+        if self.section == Some(Section::Rows) {
+          self.known_rows = self.model.row_types.clone();
+        }
+        if matches!(section, Section::QSection | Section::QuadObj) {
+          self.quadratic_section = true;
+        }
+        self.section = Some(section);
+      }
+      Event::Name(name) => model.name = name,
+      Event::ObjectiveSense(sense) => model.objective_sense = Some(sense),
+      Event::ObjectiveName(name) => self.objective_name = Some(name),
+      Event::ReferenceRow(name) => model.reference_row = Some(name),
+      Event::Row { row_type, row_name } => {
+        let r = RowLine {
+          row_type,
+          row_name: &row_name,
+        };
+        model.row_types.add(&r, Section::Rows, diagnostics);
+      }
+      Event::UserCut { row_type, row_name } => {
+        let new = !model.user_cuts.0.contains_key(&row_name);
+        let r = RowLine {
+          row_type: row_type.clone(),
+          row_name: &row_name,
+        };
+        model.user_cuts.add(&r, Section::UserCuts, diagnostics);
+        if !new {
+          return;
+        }
+        if self.known_rows.0.contains_key(&row_name) {
+          diagnostics.error(
+            Error::new(
+              ErrorKind::Conflict,
+              format!(
+                "user cut {} conflicts with row of the same name",
+                row_name
+              ),
+            )
+            .in_section(Section::UserCuts)
+            .with_token(row_name.as_str()),
+            &[],
+          );
+        } else {
+          self.known_rows.0.insert(row_name, row_type);
+        }
+      }
+      Event::Coefficient {
+        column_name,
+        row_name,
+        value,
+        integer,
+      } => {
+        model.column_types.add(&column_name, integer);
+        let c = wide_line(&column_name, &row_name, value);
+        model.values.add(&c, &self.known_rows, diagnostics);
+      }
+      Event::Rhs {
+        set_name,
+        row_name,
+        value,
+      } => {
+        let r = wide_line(&set_name, &row_name, value);
+        model.rhs.add(&r, &self.known_rows, diagnostics);
+      }
+      Event::Range {
+        set_name,
+        row_name,
+        value,
+      } => {
+        let r = wide_line(&set_name, &row_name, value);
+        model.ranges.add(&r, &self.known_rows, diagnostics);
+      }
+      Event::Bound {
+        bound_type,
+        bound_name,
+        column_name,
+        value,
+      } => {
+        let b = BoundsLine {
+          bound_type,
+          bound_name: &bound_name,
+          column_name: &column_name,
+          value,
+        };
+        let column_types = &model.column_types;
+        model.bounds.add(
+          &b,
+          |name| column_types.0.contains_key(name),
+          diagnostics,
+        );
+      }
+      Event::SpecialOrderedSet { sos_type, set_name } => {
+        self.flush(diagnostics);
+        self.set = Some((sos_type, set_name));
+      }
+      Event::SpecialOrderedSetMember { var_name, weight } => {
+        if self.set.is_some() {
+          self.set_members.push((var_name, weight));
+        }
+      }
+      Event::QuadraticObjective {
+        var1,
+        var2,
+        coefficient,
+      } => {
+        // QMATRIX only counts when there is no QSECTION or QUADOBJ
+        if self.section == Some(Section::QMatrix) {
+          if !self.quadratic_section {
+            self.quadratic_matrix.push((var1, var2, coefficient));
+          }
+          return;
+        }
+        let q = QuadraticObjectiveTerm {
+          var1: &var1,
+          var2: &var2,
+          coefficient,
+        };
+        model
+          .quadratic_objective
+          .add(&q, &model.column_types, diagnostics);
+      }
+      Event::QuadraticConstraint { row_name } => {
+        self.flush(diagnostics);
+        self.quadratic_constraint = Some(row_name);
+      }
+      Event::QuadraticConstraintTerm {
+        var1,
+        var2,
+        coefficient,
+      } => {
+        if self.quadratic_constraint.is_some() {
+          self.quadratic_terms.push((var1, var2, coefficient));
+        }
+      }
+      Event::Cone {
+        cone_name,
+        cone_type,
+      } => {
+        self.flush(diagnostics);
+        self.cone = Some((cone_name, cone_type));
+      }
+      Event::ConeMember { var_name } => {
+        if self.cone.is_some() {
+          self.cone_members.push(var_name);
+        }
+      }
+      Event::Indicator {
+        binary_var,
+        trigger_value,
+        constraint_name,
+      } => {
+        let i = IndicatorLine {
+          binary_var: &binary_var,
+          trigger_value,
+          constraint_name: &constraint_name,
+        };
+        model.indicators.add(
+          &i,
+          &model.row_types,
+          &model.column_types,
+          diagnostics,
+        );
+      }
+      Event::LazyConstraint { priority, row_name } => {
+        let l = LazyConstraintLine {
+          priority,
+          row_name: &row_name,
+        };
+        model
+          .lazy_constraints
+          .add(&l, &model.row_types, diagnostics);
+      }
+      Event::BranchPriority {
+        var_name,
+        priority,
+        direction,
+      } => {
+        let b = BranchPriority {
+          var_name: &var_name,
+          priority,
+          direction,
+        };
+        model
+          .branch_priorities
+          .add(&b, &model.column_types, diagnostics);
+      }
+    }
+  }
+
+  /// Completes the model once every event has been added.
+  pub(crate) fn finish(
+    mut self,
+    options: ModelOptions,
+    diagnostics: &mut Diagnostics,
+  ) -> Model<T> {
+    self.flush(diagnostics);
+    let mut model = self.model;
+    let objective_name = self.objective_name.as_deref();
+    Model::<T>::check_rows(
+      &model.row_types,
+      objective_name,
+      model.reference_row.as_deref(),
+      diagnostics,
+    );
+    model.objective_name = model
+      .row_types
+      .objective(objective_name)
+      .map(str::to_string);
+    diagnostics.check(model.select(options), &[]);
+    model
+  }
+
+  /// Adds the buffered SOS set, cone, QMATRIX section or QCMATRIX block.
+  fn flush(&mut self, diagnostics: &mut Diagnostics) {
+    let model = &mut self.model;
+    let members = std::mem::take(&mut self.set_members);
+    if let Some((sos_type, set_name)) = self.set.take() {
+      let s = SOSLine {
+        sos_type,
+        set_name: &set_name,
+        members: members
+          .iter()
+          .map(|(var_name, weight)| SOSMember {
+            var_name,
+            weight: *weight,
+          })
+          .collect(),
+      };
+      model
+        .special_ordered_sets
+        .add(&s, &model.column_types, diagnostics);
+    }
+    let members = std::mem::take(&mut self.cone_members);
+    if let Some((cone_name, cone_type)) = self.cone.take() {
+      let c = ConeConstraint {
+        cone_name: &cone_name,
+        cone_type,
+        members: members
+          .iter()
+          .map(|var_name| ConeMember {
+            var_name,
+            coefficient: None,
+          })
+          .collect(),
+      };
+      model
+        .cone_constraints
+        .add(&c, &model.column_types, diagnostics);
+    }
+    let terms = std::mem::take(&mut self.quadratic_matrix);
+    if !terms.is_empty() {
+      let terms: Vec<_> = terms
+        .iter()
+        .map(|(var1, var2, coefficient)| QuadraticObjectiveTerm {
+          var1,
+          var2,
+          coefficient: *coefficient,
+        })
+        .collect();
+      model.quadratic_objective.add_full(
+        &terms,
+        &model.column_types,
+        diagnostics,
+      );
+    }
+    let terms = std::mem::take(&mut self.quadratic_terms);
+    if let Some(row_name) = self.quadratic_constraint.take() {
+      let c = QuadraticConstraint {
+        row_name: &row_name,
+        terms: terms
+          .iter()
+          .map(|(var1, var2, coefficient)| QuadraticTerm {
+            var1,
+            var2,
+            coefficient: *coefficient,
+          })
+          .collect(),
+      };
+      model.quadratic_constraints.add(
+        &c,
+        &model.row_types,
+        &model.column_types,
+        diagnostics,
+      );
+    }
+  }
+}
+
+/// A COLUMNS, RHS or RANGES line with a single row/value pair.
+fn wide_line<'a, T>(
+  name: &'a str,
+  row_name: &'a str,
+  value: T,
+) -> WideLine<'a, T> {
+  WideLine {
+    name,
+    first_pair: RowValuePair { row_name, value },
+    second_pair: None,
+  }
+}
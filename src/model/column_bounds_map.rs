@@ -0,0 +1,226 @@
+use crate::error::{Diagnostics, Error, ErrorKind};
+use crate::model::bounds_map::BoundsMap;
+use crate::model::column_type_map::ColumnTypeMap;
+use crate::types::{BoundType, ColumnType, Section, VariableType};
+use color_eyre::Result;
+use fast_float2::FastFloat;
+use hashbrown::HashSet;
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The effective bounds and domain of a single column.
+///
+/// Infinite bounds are represented by `T::NEG_INFINITY` and `T::INFINITY`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ColumnBounds<T: FastFloat> {
+  pub lower: T,
+  pub upper: T,
+  pub kind: VariableType,
+}
+
+impl<T: FastFloat> From<&ColumnType> for ColumnBounds<T> {
+  /// The MPS default bounds `0 <= x_j <= inf` for a column of the given type.
+  fn from(column_type: &ColumnType) -> Self {
+    ColumnBounds {
+      lower: T::default(),
+      upper: T::INFINITY,
+      kind: match column_type {
+        ColumnType::Continuous => VariableType::Continuous,
+        ColumnType::Integer => VariableType::Integer,
+      },
+    }
+  }
+}
+
+/// The effective bounds of every column, in COLUMNS order.
+///
+/// Built by applying the entries of a single bound set, in file order, on top
+/// of the MPS defaults:
+///
+/// | Bound Type | Effect                                                       |
+/// |------------|--------------------------------------------------------------|
+/// | (none)     | `0 <= x_j <= inf`                                            |
+/// | `LO`       | `l_j = value`                                                |
+/// | `UP`       | `u_j = value`, and `l_j = -inf` if `value < 0` and no lower bound was given |
+/// | `FX`       | `l_j = u_j = value`                                          |
+/// | `FR`       | `l_j = -inf`, `u_j = inf`                                    |
+/// | `MI`       | `l_j = -inf`                                                 |
+/// | `PL`       | `u_j = inf`                                                  |
+/// | `BV`       | `l_j = 0`, `u_j = 1`, binary                                 |
+/// | `LI`       | `l_j = value`, integer                                       |
+/// | `UI`       | as `UP`, integer                                             |
+/// | `SC`       | `u_j = value` (or `inf` if omitted), semi-continuous         |
+///
+/// Columns declared between INTORG/INTEND markers start out as integer.
+/// Every negative `UP` or `UI` that frees the lower bound, unless a later
+/// entry sets the lower bound after all, is recorded in `warnings`.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ColumnBoundsMap<T: FastFloat> {
+  pub columns: IndexMap<String, ColumnBounds<T>>,
+  pub warnings: Vec<String>,
+}
+
+impl<T: FastFloat> TryFrom<(&BoundsMap<T>, Option<&str>, &ColumnTypeMap)>
+  for ColumnBoundsMap<T>
+{
+  type Error = color_eyre::Report;
+
+  /// Resolves the named bound set of `BoundsMap`, or the first one when no
+  /// name is given, as most solvers do.
+  fn try_from(
+    t: (&BoundsMap<T>, Option<&str>, &ColumnTypeMap),
+  ) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let resolved = ColumnBoundsMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(resolved)
+  }
+}
+
+impl<T: FastFloat> ColumnBoundsMap<T> {
+  /// Resolves the bounds, recording every unknown column and missing value
+  /// in `diagnostics` and skipping the offending entries. Warnings are
+  /// recorded in `diagnostics` as well as in `warnings`.
+  pub(crate) fn build(
+    t: (&BoundsMap<T>, Option<&str>, &ColumnTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let (bounds, bound_name, column_types) = t;
+    let mut resolved = ColumnBoundsMap {
+      columns: column_types
+        .0
+        .iter()
+        .map(|(name, column_type)| (name.clone(), column_type.into()))
+        .collect(),
+      warnings: Vec::new(),
+    };
+    let (bound_name, entries) =
+      match diagnostics.check(bounds.select(bound_name), &[]) {
+        Some(Some(bound_set)) => bound_set,
+        _ => return resolved,
+      };
+    let mut lower_specified = HashSet::<&str>::new();
+    // Warned about once every entry of the column has been applied
+    let mut freed = IndexMap::<&str, T>::new();
+    for ((column_name, bound_type), value) in entries {
+      let column = resolved.columns.get_mut(column_name).ok_or_else(|| {
+        Error::new(
+          ErrorKind::UnknownColumn,
+          format!(
+            "specified bound {:?} of type {:?} for unspecified column {:?}",
+            bound_name, bound_type, column_name
+          ),
+        )
+        .in_section(Section::Bounds)
+        .with_token(column_name.as_str())
+        .into()
+      });
+      let column = match diagnostics.check(column, &[bound_name]) {
+        Some(column) => column,
+        None => continue,
+      };
+      let required = || {
+        value.ok_or_else(|| {
+          Error::new(
+            ErrorKind::MissingValue,
+            format!(
+              "missing value in BOUNDS {:?} for column {:?} of type {:?}",
+              bound_name, column_name, bound_type
+            ),
+          )
+          .in_section(Section::Bounds)
+          .with_token(column_name.as_str())
+          .into()
+        })
+      };
+      match bound_type {
+        BoundType::Lo | BoundType::Li => {
+          let lower = match diagnostics.check(required(), &[bound_name]) {
+            Some(lower) => lower,
+            None => continue,
+          };
+          column.lower = lower;
+          lower_specified.insert(column_name.as_str());
+          freed.shift_remove(column_name.as_str());
+        }
+        BoundType::Up | BoundType::Ui => {
+          let upper = match diagnostics.check(required(), &[bound_name]) {
+            Some(upper) => upper,
+            None => continue,
+          };
+          column.upper = upper;
+          if upper < T::default()
+            && !lower_specified.contains(column_name.as_str())
+          {
+            column.lower = T::NEG_INFINITY;
+            freed.insert(column_name.as_str(), upper);
+          }
+        }
+        BoundType::Fx => {
+          let fixed = match diagnostics.check(required(), &[bound_name]) {
+            Some(fixed) => fixed,
+            None => continue,
+          };
+          column.lower = fixed;
+          column.upper = fixed;
+          lower_specified.insert(column_name.as_str());
+          freed.shift_remove(column_name.as_str());
+        }
+        BoundType::Fr => {
+          column.lower = T::NEG_INFINITY;
+          column.upper = T::INFINITY;
+          lower_specified.insert(column_name.as_str());
+          freed.shift_remove(column_name.as_str());
+        }
+        BoundType::Mi => {
+          column.lower = T::NEG_INFINITY;
+          lower_specified.insert(column_name.as_str());
+          freed.shift_remove(column_name.as_str());
+        }
+        BoundType::Pl => column.upper = T::INFINITY,
+        BoundType::Bv => {
+          column.lower = T::default();
+          column.upper = T::from_u64(1);
+          lower_specified.insert(column_name.as_str());
+          freed.shift_remove(column_name.as_str());
+        }
+        BoundType::Sc => column.upper = value.unwrap_or(T::INFINITY),
+      }
+      column.kind = match (bound_type, column.kind) {
+        (BoundType::Bv, _) => VariableType::Binary,
+        (BoundType::Li | BoundType::Ui, VariableType::Continuous) => {
+          VariableType::Integer
+        }
+        (BoundType::Li | BoundType::Ui, VariableType::SemiContinuous) => {
+          VariableType::SemiInteger
+        }
+        (BoundType::Sc, VariableType::Continuous) => {
+          VariableType::SemiContinuous
+        }
+        (BoundType::Sc, VariableType::Integer) => VariableType::SemiInteger,
+        (_, kind) => kind,
+      };
+    }
+    for (column_name, upper) in freed {
+      let warning = format!(
+        "negative upper bound {:?} in BOUNDS {:?} for column {:?} without lower bound: setting lower bound to -inf",
+        upper, bound_name, column_name
+      );
+      diagnostics.warn(
+        ErrorKind::NegativeUpperBound,
+        Section::Bounds,
+        &[bound_name, column_name],
+        warning.clone(),
+      );
+      resolved.warnings.push(warning);
+    }
+    resolved
+  }
+
+  pub fn get(&self, column_name: &str) -> Option<&ColumnBounds<T>> {
+    self.columns.get(column_name)
+  }
+}
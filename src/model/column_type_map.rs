@@ -0,0 +1,60 @@
+use crate::error::{Error, ErrorKind};
+use crate::types::{ColumnType, Columns, IntegerColumns};
+use color_eyre::Result;
+use fast_float2::FastFloat;
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ColumnTypeMap(pub IndexMap<String, ColumnType>);
+
+impl<T: FastFloat> TryFrom<(&Columns<'_, T>, &IntegerColumns<'_>)>
+  for ColumnTypeMap
+{
+  type Error = color_eyre::Report;
+
+  fn try_from(t: (&Columns<'_, T>, &IntegerColumns<'_>)) -> Result<Self> {
+    let mut column_types = ColumnTypeMap(IndexMap::new());
+    let (columns_lines, integer_columns) = t;
+    for c in columns_lines {
+      column_types.add(c.name, integer_columns.contains(c.name));
+    }
+    Ok(column_types)
+  }
+}
+
+impl ColumnTypeMap {
+  /// Records a COLUMNS line of `column_name`. A column is integer if any of
+  /// its lines is inside an INTORG/INTEND block.
+  pub(crate) fn add(&mut self, column_name: &str, integer: bool) {
+    let column_type = self
+      .0
+      .entry(column_name.to_string())
+      .or_insert(ColumnType::Continuous);
+    if integer {
+      *column_type = ColumnType::Integer;
+    }
+  }
+
+  pub fn exists(&self, name: &str) -> Result<()> {
+    match self.get(name) {
+      Some(_) => Ok(()),
+      None => Err(
+        Error::new(
+          ErrorKind::UnknownColumn,
+          format!("referenced unspecified column: {}", name),
+        )
+        .with_token(name),
+      ),
+    }?;
+    Ok(())
+  }
+  pub fn get(&self, column_name: &str) -> Option<&ColumnType> {
+    self.0.get(column_name)
+  }
+  pub fn is_integer(&self, column_name: &str) -> bool {
+    matches!(self.get(column_name), Some(ColumnType::Integer))
+  }
+}
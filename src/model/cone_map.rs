@@ -0,0 +1,101 @@
+use crate::error::{Diagnostics, Error, ErrorKind, InSection};
+use crate::model::column_type_map::ColumnTypeMap;
+use crate::types::{ConeConstraint, ConeConstraints, ConeType, Section};
+use color_eyre::Result;
+use fast_float2::FastFloat;
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A second-order cone with its member columns and optional coefficients.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cone<T: FastFloat> {
+  pub cone_type: ConeType,
+  pub members: IndexMap<String, Option<T>>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConeMap<T: FastFloat>(pub IndexMap<String, Cone<T>>);
+
+impl<T: FastFloat> TryFrom<(&ConeConstraints<'_, T>, &ColumnTypeMap)>
+  for ConeMap<T>
+{
+  type Error = color_eyre::Report;
+
+  fn try_from(t: (&ConeConstraints<'_, T>, &ColumnTypeMap)) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let cones = ConeMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(cones)
+  }
+}
+
+impl<T: FastFloat> ConeMap<T> {
+  /// Builds the map, recording every unknown column and duplicate member or
+  /// cone in `diagnostics` and skipping the offending entries.
+  pub(crate) fn build(
+    t: (&ConeConstraints<'_, T>, &ColumnTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let mut cones = ConeMap(IndexMap::new());
+    let (cone_constraints, column_types) = t;
+    for c in cone_constraints {
+      cones.add(c, column_types, diagnostics);
+    }
+    cones
+  }
+
+  /// Adds a single cone, as `build` does.
+  pub(crate) fn add(
+    &mut self,
+    c: &ConeConstraint<'_, T>,
+    column_types: &ColumnTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) {
+    let mut members = IndexMap::new();
+    for m in &c.members {
+      let exists = column_types
+        .exists(m.var_name)
+        .in_section(Section::CSection);
+      if diagnostics.check(exists, &[c.cone_name]).is_none() {
+        continue;
+      }
+      if members
+        .insert(m.var_name.to_string(), m.coefficient)
+        .is_some()
+      {
+        diagnostics.error(
+          Error::new(
+            ErrorKind::DuplicateEntry,
+            format!(
+              "duplicate member in CSECTION {:?} for column {:?}",
+              c.cone_name, m.var_name
+            ),
+          )
+          .in_section(Section::CSection)
+          .with_token(m.var_name),
+          &[c.cone_name],
+        );
+      }
+    }
+    if self.0.contains_key(c.cone_name) {
+      diagnostics.error(
+        Error::new(
+          ErrorKind::DuplicateEntry,
+          format!("duplicate cone {:?}", c.cone_name),
+        )
+        .in_section(Section::CSection)
+        .with_token(c.cone_name),
+        &[],
+      );
+      return;
+    }
+    let cone = Cone {
+      cone_type: c.cone_type.clone(),
+      members,
+    };
+    self.0.insert(c.cone_name.to_string(), cone);
+  }
+}
@@ -0,0 +1,139 @@
+//! Serialization of the maps keyed by tuples: `RowColumnValueMap`,
+//! `BoundsMap`, `QuadraticObjectiveMap` and `QuadraticConstraintMap`.
+//!
+//! Formats such as JSON only key maps by strings, so each tuple-keyed map is
+//! written as a sequence of `(key, value)` entries in map order, and a map of
+//! such maps as a map from name to entry sequence.
+use crate::model::bounds_map::BoundsMap;
+use crate::model::quadratic_map::{
+  QuadraticConstraintMap, QuadraticObjectiveMap,
+};
+use crate::model::row_column_value_map::RowColumnValueMap;
+use fast_float2::FastFloat;
+use indexmap::map::serde_seq;
+use indexmap::IndexMap;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::hash::Hash;
+
+/// The entries of a map, serialized as a sequence.
+struct Entries<K, V>(IndexMap<K, V>);
+
+impl<K: Serialize, V: Serialize> Serialize for Entries<K, V> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serde_seq::serialize(&self.0, serializer)
+  }
+}
+
+impl<'de, K, V> Deserialize<'de> for Entries<K, V>
+where
+  K: Deserialize<'de> + Eq + Hash,
+  V: Deserialize<'de>,
+{
+  fn deserialize<D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    serde_seq::deserialize(deserializer).map(Entries)
+  }
+}
+
+fn serialize_named<K, V, S>(
+  map: &IndexMap<String, IndexMap<K, V>>,
+  serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+  K: Serialize,
+  V: Serialize,
+  S: Serializer,
+{
+  struct Borrowed<'a, K, V>(&'a IndexMap<K, V>);
+
+  impl<K: Serialize, V: Serialize> Serialize for Borrowed<'_, K, V> {
+    fn serialize<S: Serializer>(
+      &self,
+      serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+      serde_seq::serialize(self.0, serializer)
+    }
+  }
+
+  let mut named = serializer.serialize_map(Some(map.len()))?;
+  for (name, entries) in map {
+    named.serialize_entry(name, &Borrowed(entries))?;
+  }
+  named.end()
+}
+
+fn deserialize_named<'de, K, V, D>(
+  deserializer: D,
+) -> Result<IndexMap<String, IndexMap<K, V>>, D::Error>
+where
+  K: Deserialize<'de> + Eq + Hash,
+  V: Deserialize<'de>,
+  D: Deserializer<'de>,
+{
+  let named = IndexMap::<String, Entries<K, V>>::deserialize(deserializer)?;
+  Ok(named.into_iter().map(|(name, e)| (name, e.0)).collect())
+}
+
+impl<T: FastFloat + Serialize> Serialize for RowColumnValueMap<T> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serde_seq::serialize(&self.0, serializer)
+  }
+}
+
+impl<'de, T: FastFloat + Deserialize<'de>> Deserialize<'de>
+  for RowColumnValueMap<T>
+{
+  fn deserialize<D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    serde_seq::deserialize(deserializer).map(RowColumnValueMap)
+  }
+}
+
+impl<T: FastFloat + Serialize> Serialize for BoundsMap<T> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serialize_named(&self.0, serializer)
+  }
+}
+
+impl<'de, T: FastFloat + Deserialize<'de>> Deserialize<'de> for BoundsMap<T> {
+  fn deserialize<D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    deserialize_named(deserializer).map(BoundsMap)
+  }
+}
+
+impl<T: FastFloat + Serialize> Serialize for QuadraticObjectiveMap<T> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serde_seq::serialize(&self.0, serializer)
+  }
+}
+
+impl<'de, T: FastFloat + Deserialize<'de>> Deserialize<'de>
+  for QuadraticObjectiveMap<T>
+{
+  fn deserialize<D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    serde_seq::deserialize(deserializer).map(QuadraticObjectiveMap)
+  }
+}
+
+impl<T: FastFloat + Serialize> Serialize for QuadraticConstraintMap<T> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serialize_named(&self.0, serializer)
+  }
+}
+
+impl<'de, T: FastFloat + Deserialize<'de>> Deserialize<'de>
+  for QuadraticConstraintMap<T>
+{
+  fn deserialize<D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    deserialize_named(deserializer).map(QuadraticConstraintMap)
+  }
+}
@@ -0,0 +1,95 @@
+use crate::error::{Diagnostics, Error, ErrorKind, InSection};
+use crate::model::column_type_map::ColumnTypeMap;
+use crate::model::row_type_map::RowTypeMap;
+use crate::types::{IndicatorLine, Indicators, Section};
+use color_eyre::Result;
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The binary column and trigger value that activate an indicator constraint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Indicator {
+  pub column_name: String,
+  pub trigger_value: u8,
+}
+
+/// Indicator constraints keyed by the name of the row they activate.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IndicatorMap(pub IndexMap<String, Indicator>);
+
+impl TryFrom<(&Indicators<'_>, &RowTypeMap, &ColumnTypeMap)> for IndicatorMap {
+  type Error = color_eyre::Report;
+
+  fn try_from(
+    t: (&Indicators<'_>, &RowTypeMap, &ColumnTypeMap),
+  ) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let indicators = IndicatorMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(indicators)
+  }
+}
+
+impl IndicatorMap {
+  /// Builds the map, recording every unknown row or column and duplicate
+  /// entry in `diagnostics` and skipping the offending entries.
+  pub(crate) fn build(
+    t: (&Indicators<'_>, &RowTypeMap, &ColumnTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let mut indicators = IndicatorMap(IndexMap::new());
+    let (indicator_lines, row_types, column_types) = t;
+    for i in indicator_lines {
+      indicators.add(i, row_types, column_types, diagnostics);
+    }
+    indicators
+  }
+
+  /// Adds a single INDICATORS line, as `build` does.
+  pub(crate) fn add(
+    &mut self,
+    i: &IndicatorLine<'_>,
+    row_types: &RowTypeMap,
+    column_types: &ColumnTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) {
+    let row_exists = row_types
+      .exists(i.constraint_name)
+      .in_section(Section::Indicators);
+    let row_exists = diagnostics.check(row_exists, &[]).is_some();
+    let column_exists = column_types
+      .exists(i.binary_var)
+      .in_section(Section::Indicators);
+    let column_exists = diagnostics
+      .check(column_exists, &[i.constraint_name])
+      .is_some();
+    if !row_exists || !column_exists {
+      return;
+    }
+    let indicator = Indicator {
+      column_name: i.binary_var.to_string(),
+      trigger_value: i.trigger_value,
+    };
+    if let Some(conflicting) =
+      self.0.insert(i.constraint_name.to_string(), indicator)
+    {
+      diagnostics.error(
+        Error::new(
+          ErrorKind::DuplicateEntry,
+          format!(
+            "duplicate entry in INDICATORS for row {:?}: found {:?} and {:?}",
+            i.constraint_name,
+            (i.binary_var, i.trigger_value),
+            (conflicting.column_name, conflicting.trigger_value)
+          ),
+        )
+        .in_section(Section::Indicators)
+        .with_token(i.constraint_name),
+        &[],
+      );
+    }
+  }
+}
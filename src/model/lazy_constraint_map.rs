@@ -0,0 +1,68 @@
+use crate::error::{Diagnostics, Error, ErrorKind, InSection};
+use crate::model::row_type_map::RowTypeMap;
+use crate::types::{LazyConstraintLine, LazyConstraints, Section};
+use color_eyre::Result;
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Lazy constraint rows with their optional priority.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LazyConstraintMap(pub IndexMap<String, Option<i32>>);
+
+impl TryFrom<(&LazyConstraints<'_>, &RowTypeMap)> for LazyConstraintMap {
+  type Error = color_eyre::Report;
+
+  fn try_from(t: (&LazyConstraints<'_>, &RowTypeMap)) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let lazy_constraints = LazyConstraintMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(lazy_constraints)
+  }
+}
+
+impl LazyConstraintMap {
+  /// Builds the map, recording every unknown row and duplicate entry in
+  /// `diagnostics` and skipping the offending entries.
+  pub(crate) fn build(
+    t: (&LazyConstraints<'_>, &RowTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let mut lazy_constraints = LazyConstraintMap(IndexMap::new());
+    let (lazy_lines, row_types) = t;
+    for l in lazy_lines {
+      lazy_constraints.add(l, row_types, diagnostics);
+    }
+    lazy_constraints
+  }
+
+  /// Adds a single LAZYCONS line, as `build` does.
+  pub(crate) fn add(
+    &mut self,
+    l: &LazyConstraintLine<'_>,
+    row_types: &RowTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) {
+    let exists = row_types.exists(l.row_name).in_section(Section::LazyCons);
+    if diagnostics.check(exists, &[]).is_none() {
+      return;
+    }
+    if let Some(conflicting_priority) =
+      self.0.insert(l.row_name.to_string(), l.priority)
+    {
+      diagnostics.error(
+        Error::new(
+          ErrorKind::DuplicateEntry,
+          format!(
+            "duplicate entry in LAZYCONS for row {:?}: found {:?} and {:?}",
+            l.row_name, l.priority, conflicting_priority
+          ),
+        )
+        .in_section(Section::LazyCons)
+        .with_token(l.row_name),
+        &[],
+      );
+    }
+  }
+}
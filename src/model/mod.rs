@@ -1,68 +1,574 @@
 mod bounds_map;
+mod branch_priority_map;
+pub(crate) mod builder;
+mod column_bounds_map;
+mod column_type_map;
+mod cone_map;
+#[cfg(feature = "serde")]
+mod entries;
+mod indicator_map;
+mod lazy_constraint_map;
+mod quadratic_form;
+mod quadratic_map;
 mod ranges_map;
 mod rhs_map;
+mod row_bounds_map;
 mod row_column_value_map;
 mod row_type_map;
+mod set_map;
+mod sos_map;
+mod sparse_matrix;
 
-use crate::model::bounds_map::BoundsMap;
-use crate::model::ranges_map::RangesMap;
-use crate::model::rhs_map::RhsMap;
-use crate::model::row_column_value_map::RowColumnValueMap;
-use crate::model::row_type_map::RowTypeMap;
-use crate::types::Parser;
+use crate::error::{Diagnostic, Diagnostics, Error, ErrorKind, InSection};
+pub use crate::model::bounds_map::{BoundSet, BoundsMap};
+pub use crate::model::branch_priority_map::{BranchPriorityMap, Branching};
+use crate::model::builder::Builder;
+pub use crate::model::column_bounds_map::{ColumnBounds, ColumnBoundsMap};
+pub use crate::model::column_type_map::ColumnTypeMap;
+pub use crate::model::cone_map::{Cone, ConeMap};
+pub use crate::model::indicator_map::{Indicator, IndicatorMap};
+pub use crate::model::lazy_constraint_map::LazyConstraintMap;
+pub use crate::model::quadratic_form::QuadraticForm;
+pub use crate::model::quadratic_map::{
+  QuadraticConstraintMap, QuadraticObjectiveMap,
+};
+pub use crate::model::ranges_map::RangesMap;
+pub use crate::model::rhs_map::RhsMap;
+pub use crate::model::row_bounds_map::{RowBounds, RowBoundsMap};
+pub use crate::model::row_column_value_map::RowColumnValueMap;
+pub use crate::model::row_type_map::RowTypeMap;
+pub use crate::model::sos_map::{SpecialOrderedSet, SpecialOrderedSetMap};
+pub use crate::model::sparse_matrix::{
+  CooMatrix, CscMatrix, CsrMatrix, SparseModel,
+};
+use crate::stream::Reader;
+use crate::types::{ObjectiveSense, Parser, RowType, Section};
 use color_eyre::Result;
 use fast_float2::FastFloat;
-use hashbrown::HashSet;
+use hashbrown::{HashMap, HashSet};
+use indexmap::IndexMap;
 #[cfg(feature = "serde")]
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+
+/// Options controlling how a `Parser` is turned into a `Model`.
+///
+/// Files may carry several named RHS, RANGES and BOUNDS sets. Each option
+/// names the set to use; `None` selects the first set of the section, as most
+/// solvers do.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModelOptions {
+  pub rhs: Option<String>,
+  pub ranges: Option<String>,
+  pub bounds: Option<String>,
+}
 
+/// A validated MPS model.
+///
+/// `objective_name` is the objective row: the OBJNAME row if the file names
+/// one, otherwise the first `N` row. Any other `N` rows are free rows; see
+/// `free_rows` and `drop_free_rows`.
 #[derive(Debug, Default, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Model<T: FastFloat> {
   pub name: String,
+  pub objective_sense: Option<ObjectiveSense>,
+  pub objective_name: Option<String>,
+  pub reference_row: Option<String>,
   pub row_types: RowTypeMap,
+  pub column_types: ColumnTypeMap,
   pub values: RowColumnValueMap<T>,
   pub rhs: RhsMap<T>,
   pub bounds: BoundsMap<T>,
   pub ranges: RangesMap<T>,
+  pub user_cuts: RowTypeMap,
+  pub special_ordered_sets: SpecialOrderedSetMap<T>,
+  pub quadratic_objective: QuadraticObjectiveMap<T>,
+  pub quadratic_constraints: QuadraticConstraintMap<T>,
+  pub indicators: IndicatorMap,
+  pub lazy_constraints: LazyConstraintMap,
+  pub cone_constraints: ConeMap<T>,
+  pub branch_priorities: BranchPriorityMap,
+  pub free_row_values: RowColumnValueMap<T>,
+  pub options: ModelOptions,
 }
 
 impl<T: FastFloat> TryFrom<Parser<'_, T>> for Model<T> {
   type Error = color_eyre::Report;
 
   fn try_from(parsed: Parser<T>) -> Result<Self> {
-    let row_types = RowTypeMap::try_from(&parsed.rows)?;
-    let values = RowColumnValueMap::try_from((&parsed.columns, &row_types))?;
-    let rhs = match parsed.rhs {
-      Some(rhs) => RhsMap::try_from((&rhs, &row_types)),
-      None => Ok(RhsMap::default()),
-    }?;
+    Model::try_from((parsed, ModelOptions::default()))
+  }
+}
+
+impl<T: FastFloat> TryFrom<(Parser<'_, T>, ModelOptions)> for Model<T> {
+  type Error = color_eyre::Report;
+
+  fn try_from(t: (Parser<T>, ModelOptions)) -> Result<Self> {
+    let (parsed, options) = t;
+    let mut diagnostics = Diagnostics::default();
+    let model = Model::build(&parsed, options, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(model)
+  }
+}
+
+impl<R: BufRead, T: FastFloat> TryFrom<Reader<R, T>> for Model<T> {
+  type Error = color_eyre::Report;
+
+  fn try_from(reader: Reader<R, T>) -> Result<Self> {
+    Model::try_from((reader, ModelOptions::default()))
+  }
+}
+
+/// Builds the model while reading, without holding the file in memory.
+impl<R: BufRead, T: FastFloat> TryFrom<(Reader<R, T>, ModelOptions)>
+  for Model<T>
+{
+  type Error = color_eyre::Report;
+
+  fn try_from(t: (Reader<R, T>, ModelOptions)) -> Result<Self> {
+    let (reader, options) = t;
+    let mut diagnostics = Diagnostics::default();
+    let mut builder = Builder::default();
+    for event in reader {
+      builder.add(event?, &mut diagnostics);
+    }
+    let model = builder.finish(options, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(model)
+  }
+}
+
+impl<T: FastFloat> Model<T> {
+  /// Checks the whole `Parser` output and returns every problem found, instead
+  /// of stopping at the first one as `Model::try_from` does.
+  ///
+  /// Errors are the problems `Model::try_from`, `column_bounds` and
+  /// `row_bounds` would fail on, among them a quadratic objective entry
+  /// that differs from its mirror and a QCMATRIX that is not symmetric;
+  /// warnings flag entries that are accepted but probably not intended, such
+  /// as a negative upper bound that frees the lower bound or a QUADOBJ entry
+  /// that repeats its mirror. An empty result means the model is valid.
+  pub fn validate(
+    parsed: &Parser<'_, T>,
+    options: ModelOptions,
+  ) -> Vec<Diagnostic> {
+    let mut diagnostics = Diagnostics::default();
+    let model = Model::build(parsed, options, &mut diagnostics);
+    ColumnBoundsMap::build(
+      (
+        &model.bounds,
+        model.options.bounds.as_deref(),
+        &model.column_types,
+      ),
+      &mut diagnostics,
+    );
+    RowBoundsMap::build(
+      (
+        &model.row_types,
+        (&model.rhs, model.options.rhs.as_deref()),
+        (&model.ranges, model.options.ranges.as_deref()),
+      ),
+      &mut diagnostics,
+    );
+    diagnostics.diagnostics
+  }
+
+  /// Builds the model, recording every problem in `diagnostics` and skipping
+  /// the offending entries.
+  fn build(
+    parsed: &Parser<'_, T>,
+    options: ModelOptions,
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let row_types = RowTypeMap::build(&parsed.rows, Section::Rows, diagnostics);
+    let column_types =
+      ColumnTypeMap::try_from((&parsed.columns, &parsed.integer_columns));
+    let column_types = diagnostics.check(column_types, &[]).unwrap_or_default();
+    let user_cuts = match &parsed.user_cuts {
+      Some(user_cuts) => {
+        RowTypeMap::build(user_cuts, Section::UserCuts, diagnostics)
+      }
+      None => RowTypeMap::default(),
+    };
+    // COLUMNS, RHS and RANGES may reference user cuts as well as rows
+    let mut known_rows = row_types.clone();
+    for (row_name, row_type) in &user_cuts.0 {
+      if known_rows.0.contains_key(row_name) {
+        diagnostics.error(
+          Error::new(
+            ErrorKind::Conflict,
+            format!(
+              "user cut {} conflicts with row of the same name",
+              row_name
+            ),
+          )
+          .in_section(Section::UserCuts)
+          .with_token(row_name.as_str()),
+          &[],
+        );
+      } else {
+        known_rows.0.insert(row_name.clone(), row_type.clone());
+      }
+    }
+    let values =
+      RowColumnValueMap::build((&parsed.columns, &known_rows), diagnostics);
+    let rhs = match &parsed.rhs {
+      Some(rhs) => RhsMap::build((rhs, &known_rows), diagnostics),
+      None => RhsMap::default(),
+    };
     let mut column_names = HashSet::<&str>::new();
     for c in &parsed.columns {
       column_names.insert(c.name);
     }
-    let bounds = match parsed.bounds {
-      Some(bounds) => BoundsMap::try_from((&bounds, &column_names)),
-      None => Ok(BoundsMap::default()),
-    }?;
-    let ranges = match parsed.ranges {
-      Some(ranges) => RangesMap::try_from((&ranges, &row_types)),
-      None => Ok(RangesMap::default()),
-    }?;
-    Ok(Model {
+    let bounds = match &parsed.bounds {
+      Some(bounds) => BoundsMap::build((bounds, &column_names), diagnostics),
+      None => BoundsMap::default(),
+    };
+    let ranges = match &parsed.ranges {
+      Some(ranges) => RangesMap::build((ranges, &known_rows), diagnostics),
+      None => RangesMap::default(),
+    };
+    Self::check_rows(
+      &row_types,
+      parsed.objective_name,
+      parsed.reference_row,
+      diagnostics,
+    );
+    let special_ordered_sets = match &parsed.special_ordered_sets {
+      Some(sets) => {
+        SpecialOrderedSetMap::build((sets, &column_types), diagnostics)
+      }
+      None => SpecialOrderedSetMap::default(),
+    };
+    let quadratic_objective = match &parsed.quadratic_objective {
+      Some(terms) if parsed.full_quadratic_objective => {
+        QuadraticObjectiveMap::build_full((terms, &column_types), diagnostics)
+      }
+      Some(terms) => {
+        QuadraticObjectiveMap::build((terms, &column_types), diagnostics)
+      }
+      None => QuadraticObjectiveMap::default(),
+    };
+    let quadratic_constraints = match &parsed.quadratic_constraints {
+      Some(constraints) => QuadraticConstraintMap::build(
+        (constraints, &row_types, &column_types),
+        diagnostics,
+      ),
+      None => QuadraticConstraintMap::default(),
+    };
+    let indicators = match &parsed.indicators {
+      Some(indicators) => IndicatorMap::build(
+        (indicators, &row_types, &column_types),
+        diagnostics,
+      ),
+      None => IndicatorMap::default(),
+    };
+    let lazy_constraints = match &parsed.lazy_constraints {
+      Some(lazy) => LazyConstraintMap::build((lazy, &row_types), diagnostics),
+      None => LazyConstraintMap::default(),
+    };
+    let cone_constraints = match &parsed.cone_constraints {
+      Some(cones) => ConeMap::build((cones, &column_types), diagnostics),
+      None => ConeMap::default(),
+    };
+    let branch_priorities = match &parsed.branch_priorities {
+      Some(branch) => {
+        BranchPriorityMap::build((branch, &column_types), diagnostics)
+      }
+      None => BranchPriorityMap::default(),
+    };
+    let mut model = Model {
       name: parsed.name.to_string(),
+      objective_sense: parsed.objective_sense,
+      objective_name: row_types
+        .objective(parsed.objective_name)
+        .map(str::to_string),
+      reference_row: parsed.reference_row.map(str::to_string),
       row_types,
+      column_types,
       values,
       rhs,
       bounds,
       ranges,
-    })
+      user_cuts,
+      special_ordered_sets,
+      quadratic_objective,
+      quadratic_constraints,
+      indicators,
+      lazy_constraints,
+      cone_constraints,
+      branch_priorities,
+      free_row_values: RowColumnValueMap::default(),
+      options: ModelOptions::default(),
+    };
+    diagnostics.check(model.select(options), &[]);
+    model
+  }
+
+  /// Checks that the OBJNAME row is an `N` row and that the REFROW row
+  /// exists.
+  fn check_rows(
+    row_types: &RowTypeMap,
+    objective_name: Option<&str>,
+    reference_row: Option<&str>,
+    diagnostics: &mut Diagnostics,
+  ) {
+    if let Some(objective_name) = objective_name {
+      let objective = match row_types.get(objective_name) {
+        Some(RowType::Nr) => Ok(()),
+        Some(row_type) => Err(
+          Error::new(
+            ErrorKind::BadRowType,
+            format!(
+              "objective row {} must be of type Nr: found {:?}",
+              objective_name, row_type
+            ),
+          )
+          .with_token(objective_name)
+          .into(),
+        ),
+        None => row_types.exists(objective_name),
+      };
+      diagnostics.check(objective.in_section(Section::ObjName), &[]);
+    }
+    if let Some(reference_row) = reference_row {
+      let exists = row_types.exists(reference_row).in_section(Section::RefRow);
+      diagnostics.check(exists, &[]);
+    }
+  }
+
+  /// Selects the RHS, RANGES and BOUNDS sets used by `row_bounds` and
+  /// `column_bounds`, e.g. to evaluate another scenario stored in the file.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error, leaving the current selection unchanged, if a named
+  /// set does not exist.
+  pub fn select(&mut self, options: ModelOptions) -> Result<()> {
+    self.rhs.select(options.rhs.as_deref())?;
+    self.ranges.select(options.ranges.as_deref())?;
+    self.bounds.select(options.bounds.as_deref())?;
+    self.options = options;
+    Ok(())
+  }
+
+  /// Lists the free rows: the `N` rows other than the objective row, in ROWS
+  /// order.
+  pub fn free_rows(&self) -> Vec<&str> {
+    self.row_types.free_rows(self.objective_name.as_deref())
+  }
+
+  /// Removes the free rows from `row_types`, along with their RHS and RANGES
+  /// entries and the QCMATRIX, INDICATORS and LAZYCONS entries that name
+  /// them, none of which constrain anything on a free row.
+  ///
+  /// Their coefficients are moved from `values` to `free_row_values`, so they
+  /// remain available for inspection.
+  pub fn drop_free_rows(&mut self) {
+    let free_rows = self
+      .free_rows()
+      .into_iter()
+      .map(str::to_string)
+      .collect::<HashSet<_>>();
+    if free_rows.is_empty() {
+      return;
+    }
+    self
+      .row_types
+      .0
+      .retain(|row_name, _| !free_rows.contains(row_name));
+    let free_row_values = &mut self.free_row_values.0;
+    self.values.0.retain(|(row_name, column_name), value| {
+      if free_rows.contains(row_name) {
+        free_row_values.insert((row_name.clone(), column_name.clone()), *value);
+        false
+      } else {
+        true
+      }
+    });
+    for set in self.rhs.0.values_mut().chain(self.ranges.0.values_mut()) {
+      set.retain(|row_name, _| !free_rows.contains(row_name));
+    }
+    let quadratic_constraints = &mut self.quadratic_constraints.0;
+    quadratic_constraints.retain(|row_name, _| !free_rows.contains(row_name));
+    let indicators = &mut self.indicators.0;
+    indicators.retain(|row_name, _| !free_rows.contains(row_name));
+    let lazy_constraints = &mut self.lazy_constraints.0;
+    lazy_constraints.retain(|row_name, _| !free_rows.contains(row_name));
+  }
+
+  /// Lists the RHS set names in file order.
+  pub fn rhs_names(&self) -> Vec<&str> {
+    self.rhs.names()
+  }
+
+  /// Lists the RANGES set names in file order.
+  pub fn ranges_names(&self) -> Vec<&str> {
+    self.ranges.names()
+  }
+
+  /// Lists the BOUNDS set names in file order.
+  pub fn bounds_names(&self) -> Vec<&str> {
+    self.bounds.names()
+  }
+
+  /// Resolves the effective lower bound, upper bound and variable type of
+  /// every column from the selected bound set and the integer markers.
+  ///
+  /// See `ColumnBoundsMap` for the resolution rules.
+  pub fn column_bounds(&self) -> Result<ColumnBoundsMap<T>> {
+    ColumnBoundsMap::try_from((
+      &self.bounds,
+      self.options.bounds.as_deref(),
+      &self.column_types,
+    ))
+  }
+
+  /// Computes the activity interval `[lower, upper]` of every constraint row
+  /// from the row types, the selected RHS vector and the selected RANGES
+  /// vector.
+  ///
+  /// See `RowBoundsMap` for the resolution rules.
+  pub fn row_bounds(&self) -> Result<RowBoundsMap<T>> {
+    RowBoundsMap::try_from((
+      &self.row_types,
+      (&self.rhs, self.options.rhs.as_deref()),
+      (&self.ranges, self.options.ranges.as_deref()),
+    ))
+  }
+
+  /// The quadratic objective as the upper triangle of the `Q` of
+  /// `0.5 * x'Qx`, over the columns in file order.
+  ///
+  /// See `QuadraticForm` for how each section maps onto `Q`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if an entry names an unknown column, which
+  /// `Model::try_from` rules out.
+  pub fn quadratic_objective_form(&self) -> Result<QuadraticForm<T>> {
+    let mut diagnostics = Diagnostics::default();
+    let form = QuadraticForm::from_triangle(
+      &self.quadratic_objective.0,
+      &self.column_types,
+      &mut diagnostics,
+    );
+    diagnostics.first_error()?;
+    Ok(form)
+  }
+
+  /// The quadratic part of every QCMATRIX row as the upper triangle of the
+  /// `Q` of `0.5 * x'Qx`, in the same form as `quadratic_objective_form`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if an entry names an unknown column, which
+  /// `Model::try_from` rules out.
+  pub fn quadratic_constraint_forms(
+    &self,
+  ) -> Result<IndexMap<String, QuadraticForm<T>>> {
+    let mut diagnostics = Diagnostics::default();
+    let forms = self
+      .quadratic_constraints
+      .0
+      .iter()
+      .map(|(row_name, terms)| {
+        let form = QuadraticForm::from_full(
+          row_name,
+          terms,
+          &self.column_types,
+          &mut diagnostics,
+        );
+        (row_name.clone(), form)
+      })
+      .collect();
+    diagnostics.first_error()?;
+    Ok(forms)
+  }
+
+  /// Assigns integer indices to the constraint rows and columns, in file
+  /// order, and lays the model out as a sparse matrix with objective, bound
+  /// and row-interval vectors.
+  ///
+  /// The objective is the `objective_name` row. An RHS entry on that row is
+  /// taken as the negated objective constant. Coefficients on free rows and
+  /// user cuts are not part of the matrix. Bounds and row intervals follow
+  /// the current selection; see `column_bounds` and `row_bounds`.
+  pub fn to_sparse(&self) -> Result<SparseModel<T>> {
+    let column_bounds = self.column_bounds()?;
+    let row_bounds = self.row_bounds()?;
+    let row_index = row_bounds
+      .0
+      .keys()
+      .enumerate()
+      .map(|(i, row_name)| (row_name.as_str(), i))
+      .collect::<HashMap<_, _>>();
+    let column_index = self
+      .column_types
+      .0
+      .keys()
+      .enumerate()
+      .map(|(j, column_name)| (column_name.as_str(), j))
+      .collect::<HashMap<_, _>>();
+    let objective_name = self.objective_name.as_deref();
+    let mut sparse = SparseModel {
+      objective_sense: self.objective_sense,
+      row_names: row_bounds.0.keys().cloned().collect(),
+      column_names: self.column_types.0.keys().cloned().collect(),
+      matrix: CooMatrix {
+        num_rows: row_index.len(),
+        num_columns: column_index.len(),
+        ..Default::default()
+      },
+      objective: vec![T::default(); column_index.len()],
+      objective_offset: T::default(),
+      column_lower: column_bounds.columns.values().map(|b| b.lower).collect(),
+      column_upper: column_bounds.columns.values().map(|b| b.upper).collect(),
+      column_kinds: column_bounds.columns.values().map(|b| b.kind).collect(),
+      row_lower: row_bounds.0.values().map(|b| b.lower).collect(),
+      row_upper: row_bounds.0.values().map(|b| b.upper).collect(),
+    };
+    for ((row_name, column_name), &value) in &self.values.0 {
+      let Some(&j) = column_index.get(column_name.as_str()) else {
+        return Err(
+          Error::new(
+            ErrorKind::UnknownColumn,
+            format!(
+              "coefficient in row {:?} for unspecified column {:?}",
+              row_name, column_name
+            ),
+          )
+          .in_section(Section::Columns)
+          .with_token(column_name.as_str())
+          .into(),
+        );
+      };
+      if Some(row_name.as_str()) == objective_name {
+        sparse.objective[j] = value;
+      } else if let Some(&i) = row_index.get(row_name.as_str()) {
+        sparse.matrix.row_indices.push(i);
+        sparse.matrix.column_indices.push(j);
+        sparse.matrix.values.push(value);
+      }
+    }
+    if let Some(objective_name) = objective_name {
+      let rhs = self.rhs.select(self.options.rhs.as_deref())?;
+      if let Some(&b) = rhs.and_then(|(_, set)| set.get(objective_name)) {
+        sparse.objective_offset = -b;
+      }
+    }
+    Ok(sparse)
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::error::Severity;
+  use crate::types::{BranchDirection, RangeType, SOSType, VariableType};
   use color_eyre::{eyre::eyre, Result};
 
   #[test]
@@ -136,4 +642,681 @@ mod tests {
     };
     Ok(())
   }
+
+  #[test]
+  fn test_integer_columns() -> Result<()> {
+    let parsed = Parser::<f32>::parse(
+      "NAME          MIPTEST
+ROWS
+ N  obj
+ L  c1
+COLUMNS
+    x         obj                  1.0   c1                    1.0
+    MARKER                 'MARKER'                 'INTORG'
+    y         obj                  2.0   c1                    1.0
+    MARKER                 'MARKER'                 'INTEND'
+ENDATA",
+    )?;
+    let model = Model::try_from(parsed)?;
+    assert!(!model.column_types.is_integer("x"));
+    assert!(model.column_types.is_integer("y"));
+    Ok(())
+  }
+
+  #[test]
+  fn test_column_bounds() -> Result<()> {
+    let parsed = Parser::<f64>::parse(
+      "NAME          BNDTEST
+ROWS
+ N  obj
+COLUMNS
+    a         obj                  1.0
+    b         obj                  1.0
+    c         obj                  1.0
+    d         obj                  1.0
+    e         obj                  1.0
+    f         obj                  1.0
+    g         obj                  1.0
+    h         obj                  1.0
+    MARKER                 'MARKER'                 'INTORG'
+    i         obj                  1.0
+    j         obj                  1.0
+    MARKER                 'MARKER'                 'INTEND'
+    k         obj                  1.0
+    l         obj                  1.0
+BOUNDS
+ UP BND       a                   -5.0
+ LO BND       b                   -2.0
+ UP BND       b                   -1.0
+ FX BND       c                    3.0
+ FR BND       d
+ MI BND       e
+ UP BND       e                    4.0
+ BV BND       f
+ LI BND       g                    2.0
+ SC BND       h                    8.0
+ UP BND       j                   10.0
+ UI BND       k                    7.0
+ UP BND       l                   -1.0
+ LO BND       l                   -5.0
+ENDATA",
+    )?;
+    let model = Model::try_from(parsed)?;
+    let bounds = model.column_bounds()?;
+    let expect = |lower: f64, upper: f64, kind: VariableType| ColumnBounds {
+      lower,
+      upper,
+      kind,
+    };
+    let inf = f64::INFINITY;
+    let cases = [
+      ("a", expect(-inf, -5.0, VariableType::Continuous)),
+      ("b", expect(-2.0, -1.0, VariableType::Continuous)),
+      ("c", expect(3.0, 3.0, VariableType::Continuous)),
+      ("d", expect(-inf, inf, VariableType::Continuous)),
+      ("e", expect(-inf, 4.0, VariableType::Continuous)),
+      ("f", expect(0.0, 1.0, VariableType::Binary)),
+      ("g", expect(2.0, inf, VariableType::Integer)),
+      ("h", expect(0.0, 8.0, VariableType::SemiContinuous)),
+      ("i", expect(0.0, inf, VariableType::Integer)),
+      ("j", expect(0.0, 10.0, VariableType::Integer)),
+      ("k", expect(0.0, 7.0, VariableType::Integer)),
+      ("l", expect(-5.0, -1.0, VariableType::Continuous)),
+    ];
+    for (column_name, expected) in cases {
+      assert_eq!(bounds.get(column_name), Some(&expected), "{column_name}");
+    }
+    // A later LO gives l a lower bound after all
+    assert_eq!(bounds.warnings.len(), 1);
+    assert!(bounds.warnings[0].contains("column \"a\""));
+    Ok(())
+  }
+
+  #[test]
+  fn test_row_bounds() -> Result<()> {
+    let parsed = Parser::<f64>::parse(
+      "NAME          RNGTEST
+ROWS
+ N  obj
+ L  le
+ G  ge
+ E  ep
+ E  em
+ E  ez
+ L  le_plain
+ G  ge_plain
+ E  eq_plain
+ L  no_rhs
+COLUMNS
+    x         obj                  1.0   le                    1.0
+    x         ge                   1.0   ep                    1.0
+    x         em                   1.0   ez                    1.0
+    x         le_plain             1.0   ge_plain              1.0
+    x         eq_plain             1.0   no_rhs                1.0
+RHS
+    RHS       obj                 -7.0
+    RHS       le                  10.0   ge                   10.0
+    RHS       ep                  10.0   em                   10.0
+    RHS       ez                  10.0   le_plain              4.0
+    RHS       ge_plain             5.0   eq_plain              6.0
+RANGES
+    RNG       le                  -3.0   ge                   -3.0
+    RNG       ep                   3.0   em                   -3.0
+    RNG       ez                   0.0
+ENDATA",
+    )?;
+    let model = Model::try_from(parsed)?;
+    let bounds = model.row_bounds()?;
+    let inf = f64::INFINITY;
+    let expect =
+      |lower: f64, upper: f64, range_type: Option<RangeType>| RowBounds {
+        lower,
+        upper,
+        range_type,
+      };
+    let cases = [
+      ("le", expect(7.0, 10.0, Some(RangeType::_Le))),
+      ("ge", expect(10.0, 13.0, Some(RangeType::_Ge))),
+      ("ep", expect(10.0, 13.0, Some(RangeType::_Ep))),
+      ("em", expect(7.0, 10.0, Some(RangeType::_Em))),
+      ("ez", expect(10.0, 10.0, Some(RangeType::_Ez))),
+      ("le_plain", expect(-inf, 4.0, None)),
+      ("ge_plain", expect(5.0, inf, None)),
+      ("eq_plain", expect(6.0, 6.0, None)),
+      ("no_rhs", expect(-inf, 0.0, None)),
+    ];
+    assert_eq!(bounds.0.len(), cases.len());
+    for (row_name, expected) in cases {
+      assert_eq!(bounds.get(row_name), Some(&expected), "{row_name}");
+    }
+    assert_eq!(bounds.get("obj"), None);
+    Ok(())
+  }
+
+  #[test]
+  fn test_select_sets() -> Result<()> {
+    let input = "NAME          SETTEST
+ROWS
+ N  obj
+ L  c1
+COLUMNS
+    x         obj                  1.0   c1                    1.0
+RHS
+    RHS1      c1                   1.0
+    RHS2      c1                   2.0
+RANGES
+    RNG1      c1                   1.0
+    RNG2      c1                   2.0
+BOUNDS
+ UP BND1      x                    1.0
+ UP BND2      x                    2.0
+ENDATA";
+    let model = Model::try_from(Parser::<f64>::parse(input)?)?;
+    assert_eq!(model.rhs_names(), ["RHS1", "RHS2"]);
+    assert_eq!(model.ranges_names(), ["RNG1", "RNG2"]);
+    assert_eq!(model.bounds_names(), ["BND1", "BND2"]);
+    let c1 = model.row_bounds()?.get("c1").cloned();
+    assert_eq!(c1.map(|b| (b.lower, b.upper)), Some((0.0, 1.0)));
+    let x = model.column_bounds()?.get("x").copied();
+    assert_eq!(x.map(|b| b.upper), Some(1.0));
+
+    let options = ModelOptions {
+      rhs: Some("RHS2".to_string()),
+      ranges: Some("RNG2".to_string()),
+      bounds: Some("BND2".to_string()),
+    };
+    let mut model = Model::try_from((Parser::<f64>::parse(input)?, options))?;
+    let c1 = model.row_bounds()?.get("c1").cloned();
+    assert_eq!(c1.map(|b| (b.lower, b.upper)), Some((0.0, 2.0)));
+    let x = model.column_bounds()?.get("x").copied();
+    assert_eq!(x.map(|b| b.upper), Some(2.0));
+
+    let error = eyre!("unknown RHS set \"RHS3\": found [\"RHS1\", \"RHS2\"]");
+    match model.select(ModelOptions {
+      rhs: Some("RHS3".to_string()),
+      ..Default::default()
+    }) {
+      Ok(_) => panic!(),
+      Err(e) => assert_eq!(e.to_string(), error.to_string()),
+    };
+    assert_eq!(model.options.rhs.as_deref(), Some("RHS2"));
+    Ok(())
+  }
+
+  #[test]
+  fn test_objective_selection() -> Result<()> {
+    let rows = "ROWS
+ N  profit
+ L  c1
+ N  cost
+ N  weight
+COLUMNS
+    x         profit               1.0   c1                    1.0
+    x         cost                 2.0   weight                3.0
+RHS
+    RHS       c1                   4.0   weight                5.0
+ENDATA";
+    let model = Model::try_from(Parser::<f64>::parse(&format!(
+      "NAME          OBJTEST\n{}",
+      rows
+    ))?)?;
+    assert_eq!(model.objective_name.as_deref(), Some("profit"));
+    assert_eq!(model.free_rows(), ["cost", "weight"]);
+
+    let mut model = Model::try_from(Parser::<f64>::parse(&format!(
+      "NAME          OBJTEST\nOBJSENSE\n    MAX\nOBJNAME\n    cost\n{}",
+      rows
+    ))?)?;
+    assert_eq!(model.objective_name.as_deref(), Some("cost"));
+    assert_eq!(model.free_rows(), ["profit", "weight"]);
+
+    model.drop_free_rows();
+    assert!(model.free_rows().is_empty());
+    assert_eq!(model.row_types.0.keys().collect::<Vec<_>>(), ["c1", "cost"]);
+    assert_eq!(model.values.0.len(), 2);
+    let key = |row: &str| (row.to_string(), "x".to_string());
+    assert_eq!(model.free_row_values.0.get(&key("profit")), Some(&1.0));
+    assert_eq!(model.free_row_values.0.get(&key("weight")), Some(&3.0));
+    assert_eq!(model.rhs.0["RHS"].get("weight"), None);
+    assert_eq!(model.row_bounds()?.0.len(), 1);
+    Ok(())
+  }
+
+  #[test]
+  fn test_drop_free_row_references() -> Result<()> {
+    let mut model = Model::try_from(Parser::<f64>::parse(
+      "NAME          FREEREFS
+ROWS
+ N  obj
+ L  c1
+ N  free
+COLUMNS
+    x         obj                  1.0   c1                    1.0
+    y         free                 1.0
+QCMATRIX      free
+    x         x                    1.0
+QCMATRIX      c1
+    y         y                    1.0
+INDICATORS
+ IF free y 1
+ IF c1 y 0
+LAZYCONS
+    free
+ENDATA",
+    )?)?;
+    model.drop_free_rows();
+    let quadratic = model.quadratic_constraints.0.keys().collect::<Vec<_>>();
+    assert_eq!(quadratic, ["c1"]);
+    assert_eq!(model.indicators.0.keys().collect::<Vec<_>>(), ["c1"]);
+    assert!(model.lazy_constraints.0.is_empty());
+    let key = ("free".to_string(), "y".to_string());
+    assert_eq!(model.free_row_values.0.get(&key), Some(&1.0));
+    Ok(())
+  }
+
+  #[test]
+  fn test_sparse_export() -> Result<()> {
+    let parsed = Parser::<f64>::parse(
+      "NAME          SPARSE
+OBJSENSE
+    MAX
+ROWS
+ N  obj
+ L  c1
+ N  free
+ G  c2
+COLUMNS
+    x         c2                   3.0   obj                   1.0
+    x         c1                   2.0
+    y         c1                   4.0   free                  9.0
+    z         obj                 -1.0   c2                    5.0
+RHS
+    RHS       obj                  7.0   c1                    8.0
+BOUNDS
+ UP BND       y                    6.0
+ BV BND       z
+ENDATA",
+    )?;
+    let sparse = Model::try_from(parsed)?.to_sparse()?;
+    assert_eq!(sparse.objective_sense, Some(ObjectiveSense::Max));
+    assert_eq!(sparse.row_names, ["c1", "c2"]);
+    assert_eq!(sparse.column_names, ["x", "y", "z"]);
+    assert_eq!(sparse.objective, [1.0, 0.0, -1.0]);
+    assert_eq!(sparse.objective_offset, -7.0);
+    assert_eq!(sparse.column_lower, [0.0, 0.0, 0.0]);
+    assert_eq!(sparse.column_upper, [f64::INFINITY, 6.0, 1.0]);
+    assert_eq!(sparse.column_kinds[2], VariableType::Binary);
+    assert_eq!(sparse.row_lower, [f64::NEG_INFINITY, 0.0]);
+    assert_eq!(sparse.row_upper, [8.0, f64::INFINITY]);
+
+    let coo = sparse.coo();
+    assert_eq!((coo.num_rows, coo.num_columns), (2, 3));
+    assert_eq!(coo.row_indices, [1, 0, 0, 1]);
+    assert_eq!(coo.column_indices, [0, 0, 1, 2]);
+    assert_eq!(coo.values, [3.0, 2.0, 4.0, 5.0]);
+    let csc = sparse.csc();
+    assert_eq!(csc.column_starts, [0, 2, 3, 4]);
+    assert_eq!(csc.row_indices, [0, 1, 0, 1]);
+    assert_eq!(csc.values, [2.0, 3.0, 4.0, 5.0]);
+    let csr = sparse.csr();
+    assert_eq!(csr.row_starts, [0, 2, 4]);
+    assert_eq!(csr.column_indices, [0, 1, 0, 2]);
+    assert_eq!(csr.values, [2.0, 4.0, 3.0, 5.0]);
+
+    let parsed =
+      Parser::<f64>::parse(include_str!("../../tests/data/netlib/afiro"))?;
+    let sparse = Model::try_from(parsed)?.to_sparse()?;
+    let csc = sparse.csc();
+    assert_eq!((csc.num_rows, csc.num_columns), (27, 32));
+    assert_eq!(csc.column_starts.last(), Some(&csc.values.len()));
+    assert_eq!(sparse.csr().values.len(), csc.values.len());
+
+    // A hand-built model can name a column it does not declare
+    let mut model = Model::<f64>::default();
+    model.row_types.0.insert("c1".to_string(), RowType::Leq);
+    let key = ("c1".to_string(), "x".to_string());
+    model.values.0.insert(key, 1.0);
+    let e = model.to_sparse().unwrap_err();
+    assert_eq!(
+      e.to_string(),
+      "coefficient in row \"c1\" for unspecified column \"x\""
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_quadratic_forms() -> Result<()> {
+    let input = "NAME          QUADRATIC
+ROWS
+ N  obj
+ L  c1
+COLUMNS
+    x         obj                  1.0   c1                    1.0
+    y         c1                   1.0
+    z         obj                  1.0
+RHS
+    RHS       c1                   8.0
+QUADOBJ
+    y         x                    1.0
+    x         x                    2.0
+    z         z                    4.0
+QCMATRIX      c1
+    x         x                    1.0
+    x         y                    2.0
+    y         x                    2.0
+    z         z                    0.5
+ENDATA";
+    let model = Model::try_from(Parser::<f64>::parse(input)?)?;
+    let objective = model.quadratic_objective_form()?;
+    assert_eq!(objective.num_columns, 3);
+    assert_eq!(objective.row_indices, [0, 0, 2]);
+    assert_eq!(objective.column_indices, [0, 1, 2]);
+    assert_eq!(objective.values, [2.0, 1.0, 4.0]);
+    assert_eq!(objective.evaluate(&[1.0, 2.0, 3.0]), 21.0);
+    let csc = objective.to_csc();
+    assert_eq!(csc.column_starts, [0, 1, 2, 3]);
+    assert_eq!(csc.row_indices, [0, 0, 2]);
+    // QCMATRIX lists the x'Mx of Q = 2M
+    let constraints = model.quadratic_constraint_forms()?;
+    assert_eq!(constraints["c1"].values, [2.0, 4.0, 1.0]);
+    assert_eq!(constraints["c1"].evaluate(&[1.0, 2.0, 3.0]), 13.5);
+
+    // QMATRIX lists the full Q, which is folded into the same form
+    let qmatrix = input.replace(
+      "QUADOBJ\n    y         x                    1.0\n",
+      "QMATRIX\n    x         y                    1.0\n    y         x                    1.0\n",
+    );
+    let qmatrix = Model::try_from(Parser::<f64>::parse(&qmatrix)?)?;
+    assert_eq!(qmatrix.quadratic_objective_form()?, objective);
+
+    let lp = Model::<f64>::from_lp(
+      "Minimize
+ obj: x + y + z + [ 2 x ^ 2 + 2 x * y + 4 z ^ 2 ] / 2
+Subject To
+ c1: x + y + [ x ^ 2 + 4 x * y + 0.5 z ^ 2 ] <= 8
+End",
+    )?;
+    assert_eq!(lp.quadratic_objective_form()?, objective);
+    assert_eq!(lp.quadratic_constraint_forms()?, constraints);
+    Ok(())
+  }
+
+  #[test]
+  fn test_asymmetric_quadratic_forms() -> Result<()> {
+    let input = "NAME          ASYMMETRIC
+ROWS
+ N  obj
+ L  c1
+COLUMNS
+    x         c1                   1.0
+    y         c1                   1.0
+    z         c1                   1.0
+QCMATRIX      c1
+    x         y                    2.0
+    y         x                    3.0
+    x         z                    1.0
+ENDATA";
+    let parsed = Parser::<f64>::parse(input)?;
+    let e = Model::try_from(parsed.clone()).unwrap_err();
+    assert_eq!(
+      e.to_string(),
+      "QCMATRIX \"c1\" is not symmetric: entries for (\"x\", \"y\") and (\"y\", \"x\") differ: found 2.0 and 3.0"
+    );
+    assert!(Model::<f64>::try_from(Reader::new(input.as_bytes())).is_err());
+    let found = Model::validate(&parsed, ModelOptions::default())
+      .iter()
+      .map(|d| (d.kind, d.section, d.names.join(" ")))
+      .collect::<Vec<_>>();
+    let expected = ["c1 x y", "c1 x z"].map(|names| {
+      (
+        ErrorKind::Conflict,
+        Some(Section::QcMatrix),
+        names.to_string(),
+      )
+    });
+    assert_eq!(found, expected);
+
+    // QMATRIX lists the full matrix, so an entry needs an equal mirror
+    let qmatrix = input
+      .replace("QCMATRIX      c1", "QMATRIX")
+      .replace("    y         x                    3.0\n", "");
+    let parsed = Parser::<f64>::parse(&qmatrix)?;
+    assert_eq!(parsed.quadratic_objective.as_ref().map(Vec::len), Some(2));
+    let found = Model::validate(&parsed, ModelOptions::default())
+      .iter()
+      .map(|d| (d.kind, d.section, d.names.join(" ")))
+      .collect::<Vec<_>>();
+    let expected = ["x y", "x z"].map(|names| {
+      (
+        ErrorKind::Conflict,
+        Some(Section::QMatrix),
+        names.to_string(),
+      )
+    });
+    assert_eq!(found, expected);
+    assert!(Model::<f64>::try_from(Reader::new(qmatrix.as_bytes())).is_err());
+
+    // QUADOBJ gives both Q[x][y] and Q[y][x] with one entry, so a mirror
+    // with another value conflicts
+    let quadobj = input
+      .replace("QCMATRIX      c1", "QUADOBJ")
+      .replace("    x         z                    1.0\n", "");
+    let parsed = Parser::<f64>::parse(&quadobj)?;
+    let e = Model::try_from(parsed).unwrap_err();
+    assert!(e.to_string().contains("conflicts with its mirror"));
+    assert!(Model::<f64>::try_from(Reader::new(quadobj.as_bytes())).is_err());
+
+    // while an equal mirror is read once, with a warning
+    let quadobj = quadobj.replace("3.0", "2.0");
+    let parsed = Parser::<f64>::parse(&quadobj)?;
+    let found = Model::validate(&parsed, ModelOptions::default())
+      .iter()
+      .map(|d| (d.severity, d.kind, d.names.join(" ")))
+      .collect::<Vec<_>>();
+    let expected = [(
+      Severity::Warning,
+      ErrorKind::DuplicateEntry,
+      "y x".to_string(),
+    )];
+    assert_eq!(found, expected);
+    let model = Model::try_from(parsed)?;
+    assert_eq!(model.quadratic_objective.0.len(), 1);
+    assert_eq!(model.quadratic_objective_form()?.values, [2.0]);
+    let streamed = Model::<f64>::try_from(Reader::new(quadobj.as_bytes()))?;
+    assert_eq!(streamed, model);
+    Ok(())
+  }
+
+  #[test]
+  fn test_extensions() -> Result<()> {
+    let parsed = Parser::<f64>::parse(
+      "NAME          EXTTEST
+OBJSENSE
+    MAX
+OBJNAME
+    cost
+ROWS
+ N  cost
+ L  c1
+ E  c2
+USERCUTS
+ L  cut1
+COLUMNS
+    x1        cost                 1.0   c1                    1.0
+    x1        cut1                 1.0
+    x2        cost                 2.0   c2                    1.0
+RHS
+    rhs1      c1                  20.0   cut1                 10.0
+BOUNDS
+ UP bnd1      x2                   1.0
+SOS
+ S1 set1
+    x1 1.0
+    x2 2.0
+QUADOBJ
+    x1        x1                   2.0
+    x1        x2                   1.0
+QCMATRIX      c1
+    x1        x1                   1.0
+CSECTION
+    x1
+    x2
+INDICATORS
+ IF c1 x2 1
+LAZYCONS
+    c2
+BRANCH
+ UP x2 10
+ENDATA",
+    )?;
+    let model = Model::try_from(parsed)?;
+    assert_eq!(model.objective_sense, Some(ObjectiveSense::Max));
+    assert_eq!(model.objective_name.as_deref(), Some("cost"));
+    assert_eq!(model.user_cuts.get("cut1"), Some(&RowType::Leq));
+    let sos = &model.special_ordered_sets.0["set1"];
+    assert_eq!(sos.sos_type, SOSType::S1);
+    assert_eq!(sos.members.get("x2"), Some(&2.0));
+    let key = ("x1".to_string(), "x2".to_string());
+    assert_eq!(model.quadratic_objective.0.get(&key), Some(&1.0));
+    assert_eq!(model.quadratic_constraints.0["c1"].len(), 1);
+    assert_eq!(model.cone_constraints.0["CONE"].members.len(), 2);
+    let indicator = Indicator {
+      column_name: "x2".to_string(),
+      trigger_value: 1,
+    };
+    assert_eq!(model.indicators.0.get("c1"), Some(&indicator));
+    assert_eq!(model.lazy_constraints.0.get("c2"), Some(&None));
+    let branching = Branching {
+      priority: 10,
+      direction: BranchDirection::Up,
+    };
+    assert_eq!(model.branch_priorities.0.get("x2"), Some(&branching));
+    Ok(())
+  }
+
+  #[test]
+  fn test_unspecified_extension_references() -> Result<()> {
+    let header = "NAME          EXTTEST
+ROWS
+ N  cost
+ L  c1
+COLUMNS
+    x1        cost                 1.0   c1                    1.0
+";
+    let cases = [
+      (
+        "OBJNAME\n    c1\nROWS",
+        "objective row c1 must be of type Nr: found Leq",
+      ),
+      (
+        "SOS\n S1 set1\n    x1 1.0\n    x9 2.0\n",
+        "referenced unspecified column: x9",
+      ),
+      (
+        "QUADOBJ\n    x1        x9                   2.0\n",
+        "referenced unspecified column: x9",
+      ),
+      (
+        "INDICATORS\n IF c9 x1 1\n",
+        "referenced row of unspecified type: c9",
+      ),
+      ("BRANCH\n UP x9 10\n", "referenced unspecified column: x9"),
+    ];
+    for (section, message) in cases {
+      let input = match section.ends_with("ROWS") {
+        true => header.replacen("ROWS", section, 1),
+        false => format!("{header}{section}"),
+      };
+      let input = format!("{input}ENDATA");
+      let parsed = Parser::<f64>::parse(&input)?;
+      match Model::try_from(parsed) {
+        Ok(_) => panic!("{section}"),
+        Err(e) => assert_eq!(e.to_string(), eyre!(message).to_string()),
+      };
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_validate() -> Result<()> {
+    let input = "NAME TEST
+ROWS
+ N  COST
+ L  LIM1
+ L  LIM1
+COLUMNS
+    X1        COST      1.0        LIM9      2.0
+    X2        LIM1      1.0
+RHS
+    RHS       LIM1      4.0        LIM1      5.0
+RANGES
+    RNG       COST      1.0
+BOUNDS
+ UP BND       X1        -1.0
+ UP BND       X2        3.0
+ UP BND       X2        4.0
+ LO BND       X3        0.0
+ENDATA";
+    let parsed = Parser::<f64>::parse(input)?;
+    let diagnostics = Model::validate(&parsed, ModelOptions::default());
+    let found = diagnostics
+      .iter()
+      .map(|d| (d.severity, d.kind, d.section, d.names.join(" ")))
+      .collect::<Vec<_>>();
+    let expected = [
+      (Severity::Error, ErrorKind::Conflict, Section::Rows, "LIM1"),
+      (
+        Severity::Error,
+        ErrorKind::UnknownRow,
+        Section::Columns,
+        "X1 LIM9",
+      ),
+      (
+        Severity::Error,
+        ErrorKind::DuplicateEntry,
+        Section::Rhs,
+        "RHS LIM1",
+      ),
+      (
+        Severity::Error,
+        ErrorKind::DuplicateEntry,
+        Section::Bounds,
+        "BND X2",
+      ),
+      (
+        Severity::Error,
+        ErrorKind::UnknownColumn,
+        Section::Bounds,
+        "BND X3",
+      ),
+      (
+        Severity::Warning,
+        ErrorKind::NegativeUpperBound,
+        Section::Bounds,
+        "BND X1",
+      ),
+      (
+        Severity::Warning,
+        ErrorKind::IgnoredEntry,
+        Section::Ranges,
+        "RNG COST",
+      ),
+    ]
+    .map(|(severity, kind, section, names)| {
+      (severity, kind, Some(section), names.to_string())
+    });
+    assert_eq!(found, expected);
+    // The first error is the one `Model::try_from` fails on
+    let e = Model::try_from(parsed).unwrap_err();
+    assert_eq!(e.to_string(), diagnostics[0].message);
+    assert_eq!(
+      diagnostics[5].to_string(),
+      "warning in BOUNDS: negative upper bound -1.0 in BOUNDS \"BND\" for column \"X1\" without lower bound: setting lower bound to -inf"
+    );
+
+    let parsed =
+      Parser::<f64>::parse(include_str!("../../tests/data/netlib/afiro"))?;
+    assert!(Model::validate(&parsed, ModelOptions::default()).is_empty());
+    Ok(())
+  }
 }
@@ -0,0 +1,162 @@
+use crate::error::{Diagnostics, InSection};
+use crate::model::column_type_map::ColumnTypeMap;
+use crate::model::sparse_matrix::{CooMatrix, CscMatrix};
+use crate::types::Section;
+use fast_float2::FastFloat;
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A quadratic form `0.5 * x'Qx` with a symmetric `Q`, stored as the upper
+/// triangle of `Q`.
+///
+/// Entry `k` is `Q[row_indices[k]][column_indices[k]] = values[k]` with
+/// `row_indices[k] <= column_indices[k]`, and an off-diagonal entry stands
+/// for its mirror below the diagonal as well. Entries are sorted by column
+/// and then by row, as in a compressed sparse column matrix. Index `j` is
+/// the `j`-th column of the model in file order, as in `SparseModel`.
+///
+/// Each source lists `Q` in its own way, which building the form undoes:
+///
+/// - QUADOBJ and QSECTION list one triangle: `(x, y) = q` gives
+///   `Q[x][y] = q`.
+/// - QMATRIX lists the full matrix, `(x, y) = (y, x) = q`, and is folded into
+///   the QUADOBJ form when the `Model` is built.
+/// - QCMATRIX lists the full matrix `M` of `x'Mx`: `(x, y) = (y, x) = m`
+///   gives `Q[x][y] = 2m`.
+/// - An LP objective `[ a x ^ 2 + b x * y ] / 2` gives `Q[x][x] = a` and
+///   `Q[x][y] = b / 2`.
+/// - An LP constraint `[ a x ^ 2 + b x * y ]` gives `Q[x][x] = 2a` and
+///   `Q[x][y] = b`.
+/// - QPLIB lists one triangle: `i j q` gives `Q[i][j] = q`.
+///
+/// `Model` keeps LP and QPLIB terms in the QUADOBJ and QCMATRIX forms, so the
+/// last three go through the first three.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuadraticForm<T: FastFloat> {
+  pub num_columns: usize,
+  pub row_indices: Vec<usize>,
+  pub column_indices: Vec<usize>,
+  pub values: Vec<T>,
+}
+
+impl<T: FastFloat> QuadraticForm<T> {
+  /// Computes `0.5 * x'Qx`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `x` has fewer than `num_columns` entries.
+  pub fn evaluate(&self, x: &[T]) -> T {
+    let mut diagonal = T::default();
+    let mut off_diagonal = T::default();
+    for k in 0..self.values.len() {
+      let (i, j) = (self.row_indices[k], self.column_indices[k]);
+      if i == j {
+        diagonal = diagonal + self.values[k] * x[i] * x[j];
+      } else {
+        off_diagonal = off_diagonal + self.values[k] * x[i] * x[j];
+      }
+    }
+    diagonal / T::from_u64(2) + off_diagonal
+  }
+
+  /// The upper triangle of `Q` in compressed sparse column format.
+  pub fn to_csc(&self) -> CscMatrix<T> {
+    CooMatrix {
+      num_rows: self.num_columns,
+      num_columns: self.num_columns,
+      row_indices: self.row_indices.clone(),
+      column_indices: self.column_indices.clone(),
+      values: self.values.clone(),
+    }
+    .to_csc()
+  }
+
+  /// Builds the form of a QUADOBJ-style map, where `(x, y)` gives both
+  /// `Q[x][y]` and `Q[y][x]`. The map lists each pair of columns once, as
+  /// `Model::try_from` ensures.
+  pub(crate) fn from_triangle(
+    terms: &IndexMap<(String, String), T>,
+    column_types: &ColumnTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let mut entries = IndexMap::new();
+    let section = Section::QuadObj;
+    for ((var1, var2), &q) in terms {
+      let Some((i, j)) =
+        Self::indices(var1, var2, column_types, section, &[], diagnostics)
+      else {
+        continue;
+      };
+      entries.insert((i.min(j), i.max(j)), q);
+    }
+    Self::from_entries(entries, column_types.0.len())
+  }
+
+  /// Builds the form of the QCMATRIX of `row_name`, the full matrix `M` of
+  /// `x'Mx`, as `Q = M + M'`. That is `2M` for the symmetric `M` that
+  /// `Model::try_from` ensures.
+  pub(crate) fn from_full(
+    row_name: &str,
+    terms: &IndexMap<(String, String), T>,
+    column_types: &ColumnTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let mut entries = IndexMap::new();
+    let section = Section::QcMatrix;
+    for ((var1, var2), &m) in terms {
+      let Some((i, j)) = Self::indices(
+        var1,
+        var2,
+        column_types,
+        section,
+        &[row_name],
+        diagnostics,
+      ) else {
+        continue;
+      };
+      // An entry off the diagonal is added to its mirror
+      let q = entries
+        .entry((i.min(j), i.max(j)))
+        .or_insert_with(T::default);
+      *q = *q + if i == j { m + m } else { m };
+    }
+    Self::from_entries(entries, column_types.0.len())
+  }
+
+  /// Looks up the column indices of an entry, recording unknown columns in
+  /// `diagnostics`.
+  fn indices(
+    var1: &str,
+    var2: &str,
+    column_types: &ColumnTypeMap,
+    section: Section,
+    context: &[&str],
+    diagnostics: &mut Diagnostics,
+  ) -> Option<(usize, usize)> {
+    let exists = column_types
+      .exists(var1)
+      .and_then(|_| column_types.exists(var2))
+      .in_section(section);
+    diagnostics.check(exists, context)?;
+    let index = |name| column_types.0.get_index_of(name);
+    Some((index(var1)?, index(var2)?))
+  }
+
+  /// Sorts the `(row, column)` entries of the upper triangle by column and
+  /// then by row.
+  fn from_entries(
+    entries: IndexMap<(usize, usize), T>,
+    num_columns: usize,
+  ) -> Self {
+    let mut entries = entries.into_iter().collect::<Vec<_>>();
+    entries.sort_unstable_by_key(|&((i, j), _)| (j, i));
+    QuadraticForm {
+      num_columns,
+      row_indices: entries.iter().map(|&((i, _), _)| i).collect(),
+      column_indices: entries.iter().map(|&((_, j), _)| j).collect(),
+      values: entries.iter().map(|&(_, q)| q).collect(),
+    }
+  }
+}
@@ -0,0 +1,333 @@
+use crate::error::{Diagnostics, Error, ErrorKind, InSection};
+use crate::model::column_type_map::ColumnTypeMap;
+use crate::model::row_type_map::RowTypeMap;
+use crate::types::{
+  QuadraticConstraint, QuadraticConstraints, QuadraticObjective,
+  QuadraticObjectiveTerm, Section,
+};
+use color_eyre::Result;
+use fast_float2::FastFloat;
+use indexmap::IndexMap;
+
+/// Quadratic objective coefficients keyed by `(column, column)`.
+///
+/// The objective is `0.5 * x'Qx` for a symmetric `Q`, stored as QSECTION and
+/// QUADOBJ list it: a single entry `(x, y)` gives both `Q[x][y]` and
+/// `Q[y][x]`, so a pair of columns is listed once. A QMATRIX section, which
+/// lists the full matrix, is folded into this form by `build_full`.
+/// `Model::quadratic_objective_form` gives `Q` itself.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct QuadraticObjectiveMap<T: FastFloat>(
+  pub IndexMap<(String, String), T>,
+);
+
+impl<T: FastFloat> TryFrom<(&QuadraticObjective<'_, T>, &ColumnTypeMap)>
+  for QuadraticObjectiveMap<T>
+{
+  type Error = color_eyre::Report;
+
+  fn try_from(t: (&QuadraticObjective<'_, T>, &ColumnTypeMap)) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let quadratic_objective = QuadraticObjectiveMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(quadratic_objective)
+  }
+}
+
+impl<T: FastFloat> QuadraticObjectiveMap<T> {
+  /// Builds the map, recording every unknown column, duplicate entry and
+  /// entry that differs from its mirror in `diagnostics` and skipping the
+  /// offending entries. An entry equal to its mirror is skipped with a
+  /// warning.
+  pub(crate) fn build(
+    t: (&QuadraticObjective<'_, T>, &ColumnTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let mut quadratic_objective = QuadraticObjectiveMap(IndexMap::new());
+    let (terms, column_types) = t;
+    for q in terms {
+      quadratic_objective.add(q, column_types, diagnostics);
+    }
+    quadratic_objective
+  }
+
+  /// Builds the map from the terms of a QMATRIX section, keeping one entry
+  /// of each pair of mirrors. Every entry without an equal mirror is recorded
+  /// in `diagnostics`, along with unknown columns and duplicate entries, and
+  /// skipped.
+  pub(crate) fn build_full(
+    t: (&QuadraticObjective<'_, T>, &ColumnTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let mut quadratic_objective = QuadraticObjectiveMap(IndexMap::new());
+    let (terms, column_types) = t;
+    quadratic_objective.add_full(terms, column_types, diagnostics);
+    quadratic_objective
+  }
+
+  /// Adds a single term, as `build` does.
+  pub(crate) fn add(
+    &mut self,
+    q: &QuadraticObjectiveTerm<'_, T>,
+    column_types: &ColumnTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) {
+    let section = Section::QuadObj;
+    let exists = column_types
+      .exists(q.var1)
+      .and_then(|_| column_types.exists(q.var2))
+      .in_section(section);
+    if diagnostics.check(exists, &[]).is_none() {
+      return;
+    }
+    let mirror = (q.var2.to_string(), q.var1.to_string());
+    if q.var1 != q.var2 {
+      if let Some(&mirror_value) = self.0.get(&mirror) {
+        // A full matrix listed in QUADOBJ reads as one triangle
+        if mirror_value == q.coefficient {
+          diagnostics.warn(
+            ErrorKind::DuplicateEntry,
+            section,
+            &[q.var1, q.var2],
+            format!(
+              "quadratic objective entry for {:?} repeats its mirror {:?} with the same value {:?}: reading the pair once",
+              (q.var1, q.var2),
+              (q.var2, q.var1),
+              q.coefficient
+            ),
+          );
+          return;
+        }
+        diagnostics.error(
+          Error::new(
+            ErrorKind::Conflict,
+            format!(
+              "quadratic objective entry for {:?} conflicts with its mirror {:?}: found {:?} and {:?}",
+              (q.var1, q.var2),
+              (q.var2, q.var1),
+              q.coefficient,
+              mirror_value
+            ),
+          )
+          .in_section(section)
+          .with_token(q.var2)
+          .with_hint("list each pair of columns once, as QUADOBJ does"),
+          &[q.var1],
+        );
+        return;
+      }
+    }
+    let key = (q.var1.to_string(), q.var2.to_string());
+    if let Some(conflicting_value) = self.0.insert(key, q.coefficient) {
+      diagnostics.error(
+        Error::new(
+          ErrorKind::DuplicateEntry,
+          format!(
+            "duplicate quadratic objective entry for {:?}: found {:?} and {:?}",
+            (q.var1, q.var2),
+            q.coefficient,
+            conflicting_value
+          ),
+        )
+        .in_section(section)
+        .with_token(q.var2),
+        &[q.var1],
+      );
+    }
+  }
+
+  /// Adds the terms of a whole QMATRIX section, as `build_full` does.
+  pub(crate) fn add_full(
+    &mut self,
+    terms: &[QuadraticObjectiveTerm<'_, T>],
+    column_types: &ColumnTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) {
+    let mut full = IndexMap::new();
+    for q in terms {
+      let exists = column_types
+        .exists(q.var1)
+        .and_then(|_| column_types.exists(q.var2))
+        .in_section(Section::QMatrix);
+      if diagnostics.check(exists, &[]).is_none() {
+        continue;
+      }
+      let key = (q.var1.to_string(), q.var2.to_string());
+      if let Some(conflicting_value) = full.insert(key, q.coefficient) {
+        diagnostics.error(
+          Error::new(
+            ErrorKind::DuplicateEntry,
+            format!(
+              "duplicate QMATRIX entry for {:?}: found {:?} and {:?}",
+              (q.var1, q.var2),
+              q.coefficient,
+              conflicting_value
+            ),
+          )
+          .in_section(Section::QMatrix)
+          .with_token(q.var2),
+          &[q.var1],
+        );
+      }
+    }
+    remove_asymmetric(&mut full, |(var1, var2), found| {
+      diagnostics.error(
+        Error::new(
+          ErrorKind::Conflict,
+          format!(
+            "QMATRIX is not symmetric: entries for {:?} and {:?} differ: {}",
+            (var1, var2),
+            (var2, var1),
+            found
+          ),
+        )
+        .in_section(Section::QMatrix)
+        .with_token(var2.as_str())
+        .with_hint(
+          "QMATRIX lists the full matrix, with both (x, y) and (y, x)",
+        ),
+        &[var1],
+      );
+    });
+    for ((var1, var2), value) in full {
+      let mirror = (var2.clone(), var1.clone());
+      if var1 == var2 || !self.0.contains_key(&mirror) {
+        self.0.insert((var1, var2), value);
+      }
+    }
+  }
+}
+
+/// Quadratic constraint coefficients from QCMATRIX sections, keyed by row
+/// name and then by `(column, column)`.
+///
+/// Each row lists the full symmetric matrix `M` of `x'Mx`, without the `0.5`
+/// of the objective, so every entry `(x, y)` has an equal mirror `(y, x)`.
+/// `Model::quadratic_constraint_forms` gives the `Q = 2M` of `0.5 * x'Qx`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct QuadraticConstraintMap<T: FastFloat>(
+  pub IndexMap<String, IndexMap<(String, String), T>>,
+);
+
+impl<T: FastFloat>
+  TryFrom<(&QuadraticConstraints<'_, T>, &RowTypeMap, &ColumnTypeMap)>
+  for QuadraticConstraintMap<T>
+{
+  type Error = color_eyre::Report;
+
+  fn try_from(
+    t: (&QuadraticConstraints<'_, T>, &RowTypeMap, &ColumnTypeMap),
+  ) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let quadratic_constraints =
+      QuadraticConstraintMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(quadratic_constraints)
+  }
+}
+
+impl<T: FastFloat> QuadraticConstraintMap<T> {
+  /// Builds the map, recording every unknown row or column, duplicate entry
+  /// and entry without an equal mirror in `diagnostics` and skipping the
+  /// offending entries.
+  pub(crate) fn build(
+    t: (&QuadraticConstraints<'_, T>, &RowTypeMap, &ColumnTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let mut quadratic_constraints = QuadraticConstraintMap(IndexMap::new());
+    let (constraints, row_types, column_types) = t;
+    for c in constraints {
+      quadratic_constraints.add(c, row_types, column_types, diagnostics);
+    }
+    quadratic_constraints
+  }
+
+  /// Adds a single QCMATRIX block, as `build` does.
+  pub(crate) fn add(
+    &mut self,
+    c: &QuadraticConstraint<'_, T>,
+    row_types: &RowTypeMap,
+    column_types: &ColumnTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) {
+    let exists = row_types.exists(c.row_name).in_section(Section::QcMatrix);
+    if diagnostics.check(exists, &[]).is_none() {
+      return;
+    }
+    let terms = self.0.entry(c.row_name.to_string()).or_default();
+    for q in &c.terms {
+      let exists = column_types
+        .exists(q.var1)
+        .and_then(|_| column_types.exists(q.var2))
+        .in_section(Section::QcMatrix);
+      if diagnostics.check(exists, &[c.row_name]).is_none() {
+        continue;
+      }
+      let key = (q.var1.to_string(), q.var2.to_string());
+      if let Some(conflicting_value) = terms.insert(key, q.coefficient) {
+        diagnostics.error(
+          Error::new(
+            ErrorKind::DuplicateEntry,
+            format!(
+              "duplicate entry in QCMATRIX {:?} for {:?}: found {:?} and {:?}",
+              c.row_name,
+              (q.var1, q.var2),
+              q.coefficient,
+              conflicting_value
+            ),
+          )
+          .in_section(Section::QcMatrix)
+          .with_token(q.var2),
+          &[c.row_name, q.var1],
+        );
+      }
+    }
+    remove_asymmetric(terms, |(var1, var2), found| {
+      diagnostics.error(
+        Error::new(
+          ErrorKind::Conflict,
+          format!(
+            "QCMATRIX {:?} is not symmetric: entries for {:?} and {:?} differ: {}",
+            c.row_name,
+            (var1, var2),
+            (var2, var1),
+            found
+          ),
+        )
+        .in_section(Section::QcMatrix)
+        .with_token(var2.as_str())
+        .with_hint("QCMATRIX lists the full matrix, with both (x, y) and (y, x)"),
+        &[c.row_name, var1],
+      );
+    });
+  }
+}
+
+/// Removes every entry of a full matrix that has no equal mirror, along with
+/// its mirror, passing each pair to `report` once, at its first entry.
+fn remove_asymmetric<T: FastFloat>(
+  terms: &mut IndexMap<(String, String), T>,
+  mut report: impl FnMut(&(String, String), String),
+) {
+  let mut asymmetric = Vec::new();
+  for (key, &value) in terms.iter() {
+    if key.0 == key.1 {
+      continue;
+    }
+    let mirror = (key.1.clone(), key.0.clone());
+    let found = match terms.get(&mirror) {
+      Some(&mirror_value) if mirror_value == value => continue,
+      Some(_) if asymmetric.contains(&mirror) => continue,
+      Some(mirror_value) => {
+        format!("found {:?} and {:?}", value, mirror_value)
+      }
+      None => format!("found {:?} and no entry", value),
+    };
+    report(key, found);
+    asymmetric.push(key.clone());
+  }
+  for (var1, var2) in asymmetric {
+    terms.shift_remove(&(var2.clone(), var1.clone()));
+    terms.shift_remove(&(var1, var2));
+  }
+}
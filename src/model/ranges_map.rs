@@ -1,34 +1,74 @@
+use crate::error::{Diagnostics, Error, ErrorKind, InSection};
 use crate::model::row_type_map::RowTypeMap;
-use crate::types::Ranges;
-use color_eyre::{eyre::eyre, Result};
+use crate::model::set_map;
+use crate::types::{Ranges, Section, WideLine};
+use color_eyre::Result;
 use fast_float2::FastFloat;
 use indexmap::IndexMap;
 #[cfg(feature = "serde")]
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RangesMap<T: FastFloat>(pub IndexMap<String, IndexMap<String, T>>);
 
 impl<T: FastFloat> TryFrom<(&Ranges<'_, T>, &RowTypeMap)> for RangesMap<T> {
   type Error = color_eyre::Report;
 
   fn try_from(t: (&Ranges<'_, T>, &RowTypeMap)) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let ranges = RangesMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(ranges)
+  }
+}
+
+impl<T: FastFloat> RangesMap<T> {
+  /// Builds the map, recording every unknown row and duplicate entry in
+  /// `diagnostics` and skipping the offending entries.
+  pub(crate) fn build(
+    t: (&Ranges<'_, T>, &RowTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
     let mut ranges = RangesMap(IndexMap::new());
     let (ranges_lines, row_types) = t;
     for r in ranges_lines {
-      row_types.exists(r.first_pair.row_name)?;
-      ranges.insert(r.name, r.first_pair.row_name, r.first_pair.value)?;
-      if let Some(second_pair) = r.second_pair.as_ref() {
-        row_types.exists(second_pair.row_name)?;
-        ranges.insert(r.name, second_pair.row_name, second_pair.value)?;
+      ranges.add(r, row_types, diagnostics);
+    }
+    ranges
+  }
+
+  /// Adds a single RANGES line, as `build` does.
+  pub(crate) fn add(
+    &mut self,
+    r: &WideLine<'_, T>,
+    row_types: &RowTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) {
+    for pair in std::iter::once(&r.first_pair).chain(&r.second_pair) {
+      let exists = row_types.exists(pair.row_name).in_section(Section::Ranges);
+      if diagnostics.check(exists, &[r.name]).is_some() {
+        let inserted = self.insert(r.name, pair.row_name, pair.value);
+        diagnostics.check(inserted, &[r.name]);
       }
     }
-    Ok(ranges)
   }
-}
 
-impl<T: FastFloat> RangesMap<T> {
+  /// Lists the RANGES set names in file order.
+  pub fn names(&self) -> Vec<&str> {
+    set_map::names(&self.0)
+  }
+
+  /// Selects the named RANGES set, or the first one when `ranges_name` is `None`.
+  ///
+  /// Returns `Ok(None)` when the section is empty.
+  pub fn select(
+    &self,
+    ranges_name: Option<&str>,
+  ) -> Result<Option<(&str, &IndexMap<String, T>)>> {
+    set_map::select(&self.0, ranges_name, Section::Ranges)
+  }
+
   fn insert(
     &mut self,
     ranges_name: &str,
@@ -43,10 +83,17 @@ impl<T: FastFloat> RangesMap<T> {
         Ok(())
       }
       Some(ranges) => match ranges.insert(row_name.to_string(), value) {
-        Some(conflicting_value) => Err(eyre!(format!(
-          "duplicate entry in RANGES {:?} at row {:?}: found {:?} and {:?}",
-          ranges_name, row_name, value, conflicting_value
-        ))),
+        Some(conflicting_value) => Err(
+          Error::new(
+            ErrorKind::DuplicateEntry,
+            format!(
+              "duplicate entry in RANGES {:?} at row {:?}: found {:?} and {:?}",
+              ranges_name, row_name, value, conflicting_value
+            ),
+          )
+          .in_section(Section::Ranges)
+          .with_token(row_name),
+        ),
         None => Ok(()),
       },
     }?;
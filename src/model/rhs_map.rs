@@ -1,34 +1,74 @@
+use crate::error::{Diagnostics, Error, ErrorKind, InSection};
 use crate::model::row_type_map::RowTypeMap;
-use crate::types::Rhs;
-use color_eyre::{eyre::eyre, Result};
+use crate::model::set_map;
+use crate::types::{Rhs, Section, WideLine};
+use color_eyre::Result;
 use fast_float2::FastFloat;
 use indexmap::IndexMap;
 #[cfg(feature = "serde")]
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RhsMap<T: FastFloat>(pub IndexMap<String, IndexMap<String, T>>);
 
 impl<T: FastFloat> TryFrom<(&Rhs<'_, T>, &RowTypeMap)> for RhsMap<T> {
   type Error = color_eyre::Report;
 
   fn try_from(t: (&Rhs<'_, T>, &RowTypeMap)) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let rhs = RhsMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(rhs)
+  }
+}
+
+impl<T: FastFloat> RhsMap<T> {
+  /// Builds the map, recording every unknown row and duplicate entry in
+  /// `diagnostics` and skipping the offending entries.
+  pub(crate) fn build(
+    t: (&Rhs<'_, T>, &RowTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
     let mut rhs = RhsMap(IndexMap::new());
     let (rhs_lines, row_types) = t;
     for r in rhs_lines {
-      row_types.exists(r.first_pair.row_name)?;
-      rhs.insert(r.name, r.first_pair.row_name, r.first_pair.value)?;
-      if let Some(second_pair) = r.second_pair.as_ref() {
-        row_types.exists(second_pair.row_name)?;
-        rhs.insert(r.name, second_pair.row_name, second_pair.value)?;
+      rhs.add(r, row_types, diagnostics);
+    }
+    rhs
+  }
+
+  /// Adds a single RHS line, as `build` does.
+  pub(crate) fn add(
+    &mut self,
+    r: &WideLine<'_, T>,
+    row_types: &RowTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) {
+    for pair in std::iter::once(&r.first_pair).chain(&r.second_pair) {
+      let exists = row_types.exists(pair.row_name).in_section(Section::Rhs);
+      if diagnostics.check(exists, &[r.name]).is_some() {
+        let inserted = self.insert(r.name, pair.row_name, pair.value);
+        diagnostics.check(inserted, &[r.name]);
       }
     }
-    Ok(rhs)
   }
-}
 
-impl<T: FastFloat> RhsMap<T> {
+  /// Lists the RHS set names in file order.
+  pub fn names(&self) -> Vec<&str> {
+    set_map::names(&self.0)
+  }
+
+  /// Selects the named RHS set, or the first one when `rhs_name` is `None`.
+  ///
+  /// Returns `Ok(None)` when the section is empty.
+  pub fn select(
+    &self,
+    rhs_name: Option<&str>,
+  ) -> Result<Option<(&str, &IndexMap<String, T>)>> {
+    set_map::select(&self.0, rhs_name, Section::Rhs)
+  }
+
   fn insert(&mut self, rhs_name: &str, row_name: &str, value: T) -> Result<()> {
     match self.0.get_mut(rhs_name) {
       None => {
@@ -38,10 +78,17 @@ impl<T: FastFloat> RhsMap<T> {
         Ok(())
       }
       Some(rhs) => match rhs.insert(row_name.to_string(), value) {
-        Some(conflicting_value) => Err(eyre!(format!(
-          "duplicate entry in RHS {:?} at row {:?}: found {:?} and {:?}",
-          rhs_name, row_name, value, conflicting_value
-        ))),
+        Some(conflicting_value) => Err(
+          Error::new(
+            ErrorKind::DuplicateEntry,
+            format!(
+              "duplicate entry in RHS {:?} at row {:?}: found {:?} and {:?}",
+              rhs_name, row_name, value, conflicting_value
+            ),
+          )
+          .in_section(Section::Rhs)
+          .with_token(row_name),
+        ),
         None => Ok(()),
       },
     }?;
@@ -0,0 +1,127 @@
+use crate::error::{Diagnostics, ErrorKind};
+use crate::model::ranges_map::RangesMap;
+use crate::model::rhs_map::RhsMap;
+use crate::model::row_type_map::RowTypeMap;
+use crate::types::{RangeType, RowType, Section};
+use color_eyre::Result;
+use fast_float2::FastFloat;
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The activity interval `[lower, upper]` of a single constraint row.
+///
+/// Infinite limits are represented by `T::NEG_INFINITY` and `T::INFINITY`.
+/// `range_type` is set when the row has an entry in the RANGES vector.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RowBounds<T: FastFloat> {
+  pub lower: T,
+  pub upper: T,
+  pub range_type: Option<RangeType>,
+}
+
+/// The activity interval of every constraint row, in ROWS order.
+///
+/// Built from the row types, the selected RHS vector and the selected RANGES
+/// vector, each defaulting to the first one in the file.
+/// A row without an RHS entry has `b_i = 0`. Without a range, `E` rows are
+/// `[b_i, b_i]`, `L` rows are `[-inf, b_i]` and `G` rows are `[b_i, inf]`.
+/// With a range `R_i` the limits follow the table documented on `RangeType`.
+/// `N` rows are not constraints and are omitted.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RowBoundsMap<T: FastFloat>(pub IndexMap<String, RowBounds<T>>);
+
+type RowBoundsSource<'a, T> = (
+  &'a RowTypeMap,
+  (&'a RhsMap<T>, Option<&'a str>),
+  (&'a RangesMap<T>, Option<&'a str>),
+);
+
+impl<T: FastFloat> TryFrom<RowBoundsSource<'_, T>> for RowBoundsMap<T> {
+  type Error = color_eyre::Report;
+
+  fn try_from(t: RowBoundsSource<'_, T>) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let row_bounds = RowBoundsMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(row_bounds)
+  }
+}
+
+impl<T: FastFloat> RowBoundsMap<T> {
+  /// Computes the row bounds, recording unknown RHS or RANGES sets in
+  /// `diagnostics`, along with a warning for every ignored range on an `N`
+  /// row.
+  pub(crate) fn build(
+    t: RowBoundsSource<'_, T>,
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let (row_types, (rhs, rhs_name), (ranges, ranges_name)) = t;
+    let rhs = diagnostics.check(rhs.select(rhs_name), &[]).flatten();
+    let ranges = diagnostics.check(ranges.select(ranges_name), &[]).flatten();
+    let mut row_bounds = IndexMap::new();
+    for (row_name, row_type) in &row_types.0 {
+      if *row_type == RowType::Nr {
+        if let Some((ranges_name, r)) =
+          ranges.and_then(|(name, values)| Some((name, values.get(row_name)?)))
+        {
+          diagnostics.warn(
+            ErrorKind::IgnoredEntry,
+            Section::Ranges,
+            &[ranges_name, row_name],
+            format!(
+              "range {:?} in RANGES {:?} for row {:?} of type Nr is ignored",
+              r, ranges_name, row_name
+            ),
+          );
+        }
+        continue;
+      }
+      let b = rhs
+        .and_then(|(_, values)| values.get(row_name))
+        .copied()
+        .unwrap_or_default();
+      let bounds = match ranges.and_then(|(_, values)| values.get(row_name)) {
+        Some(&r) => {
+          let range_type = match diagnostics
+            .check(RangeType::try_from((row_type, r)), &[row_name])
+          {
+            Some(range_type) => range_type,
+            None => continue,
+          };
+          let magnitude = if r < T::default() { -r } else { r };
+          let (lower, upper) = match range_type {
+            RangeType::_Le | RangeType::_Em => (b + -magnitude, b),
+            RangeType::_Ge | RangeType::_Ep => (b, b + magnitude),
+            RangeType::_Ez => (b, b),
+          };
+          RowBounds {
+            lower,
+            upper,
+            range_type: Some(range_type),
+          }
+        }
+        None => {
+          let (lower, upper) = match row_type {
+            RowType::Leq => (T::NEG_INFINITY, b),
+            RowType::Geq => (b, T::INFINITY),
+            _ => (b, b),
+          };
+          RowBounds {
+            lower,
+            upper,
+            range_type: None,
+          }
+        }
+      };
+      row_bounds.insert(row_name.clone(), bounds);
+    }
+    RowBoundsMap(row_bounds)
+  }
+
+  pub fn get(&self, row_name: &str) -> Option<&RowBounds<T>> {
+    self.0.get(row_name)
+  }
+}
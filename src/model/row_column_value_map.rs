@@ -1,13 +1,11 @@
+use crate::error::{Diagnostics, Error, ErrorKind, InSection};
 use crate::model::row_type_map::RowTypeMap;
-use crate::types::Columns;
-use color_eyre::{eyre::eyre, Result};
+use crate::types::{Columns, Section, WideLine};
+use color_eyre::Result;
 use fast_float2::FastFloat;
 use indexmap::IndexMap;
-#[cfg(feature = "serde")]
-use serde::Serialize;
 
 #[derive(Debug, Default, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct RowColumnValueMap<T: FastFloat>(pub IndexMap<(String, String), T>);
 
 impl<T: FastFloat> TryFrom<(&Columns<'_, T>, &RowTypeMap)>
@@ -16,29 +14,44 @@ impl<T: FastFloat> TryFrom<(&Columns<'_, T>, &RowTypeMap)>
   type Error = color_eyre::Report;
 
   fn try_from(t: (&Columns<'_, T>, &RowTypeMap)) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let row_column_values = RowColumnValueMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(row_column_values)
+  }
+}
+
+impl<T: FastFloat> RowColumnValueMap<T> {
+  /// Builds the map, recording every unknown row and conflicting entry in
+  /// `diagnostics` and skipping the offending entries.
+  pub(crate) fn build(
+    t: (&Columns<'_, T>, &RowTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
     let mut row_column_values = RowColumnValueMap(IndexMap::new());
     let (columns_lines, row_types) = t;
     for c in columns_lines {
-      row_types.exists(c.first_pair.row_name)?;
-      row_column_values.insert(
-        c.first_pair.row_name,
-        c.name,
-        c.first_pair.value,
-      )?;
-      if let Some(second_pair) = c.second_pair.as_ref() {
-        row_types.exists(second_pair.row_name)?;
-        row_column_values.insert(
-          second_pair.row_name,
-          c.name,
-          second_pair.value,
-        )?;
+      row_column_values.add(c, row_types, diagnostics);
+    }
+    row_column_values
+  }
+
+  /// Adds a single COLUMNS line, as `build` does.
+  pub(crate) fn add(
+    &mut self,
+    c: &WideLine<'_, T>,
+    row_types: &RowTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) {
+    for pair in std::iter::once(&c.first_pair).chain(&c.second_pair) {
+      let exists = row_types.exists(pair.row_name).in_section(Section::Columns);
+      if diagnostics.check(exists, &[c.name]).is_some() {
+        let inserted = self.insert(pair.row_name, c.name, pair.value);
+        diagnostics.check(inserted, &[]);
       }
     }
-    Ok(row_column_values)
   }
-}
 
-impl<T: FastFloat> RowColumnValueMap<T> {
   fn insert(
     &mut self,
     row_name: &str,
@@ -47,10 +60,17 @@ impl<T: FastFloat> RowColumnValueMap<T> {
   ) -> Result<()> {
     match self.0.insert((row_name.to_string(), column_name.to_string()), value)
       {
-        Some(conflicting_value) => Err(eyre!(format!(
-          "conflicting (row, column, value) information for {:?}: found {:?} and {:?}",
-          (row_name, column_name), value, conflicting_value
-        ))),
+        Some(conflicting_value) => Err(
+          Error::new(
+            ErrorKind::DuplicateEntry,
+            format!(
+              "conflicting (row, column, value) information for {:?}: found {:?} and {:?}",
+              (row_name, column_name), value, conflicting_value
+            ),
+          )
+          .in_section(Section::Columns)
+          .with_token(column_name),
+        ),
         None => Ok(()),
       }?;
     Ok(())
@@ -1,53 +1,118 @@
-use crate::types::{RowType, Rows};
-use color_eyre::{eyre::eyre, Result};
+use crate::error::{Diagnostics, Error, ErrorKind};
+use crate::types::{RowLine, RowType, Rows, Section};
+use color_eyre::Result;
 use indexmap::IndexMap;
 #[cfg(feature = "serde")]
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RowTypeMap(pub IndexMap<String, RowType>);
 
 impl TryFrom<&Rows<'_>> for RowTypeMap {
   type Error = color_eyre::Report;
 
   fn try_from(rows: &Rows<'_>) -> Result<Self> {
-    let mut row_types = IndexMap::new();
-    for r in rows {
-      match row_types.insert(r.row_name.to_string(), r.row_type.clone()) {
-        Some(row_type) => Err(eyre!(format!(
-          "conflicting row type information for {}: found {:?} and {:?}",
-          r.row_name,
-          r.row_type.clone(),
-          row_type
-        ))),
-        None => Ok(()),
-      }?;
-    }
-    Ok(RowTypeMap(row_types))
+    let mut diagnostics = Diagnostics::default();
+    let row_types = RowTypeMap::build(rows, Section::Rows, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(row_types)
   }
 }
 
 impl RowTypeMap {
+  /// Builds the map from the lines of `section` (ROWS or USERCUTS),
+  /// recording every conflicting declaration in `diagnostics`. The first
+  /// declaration of a row wins.
+  pub(crate) fn build(
+    rows: &Rows<'_>,
+    section: Section,
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let mut row_types = RowTypeMap(IndexMap::new());
+    for r in rows {
+      row_types.add(r, section, diagnostics);
+    }
+    row_types
+  }
+
+  /// Adds a single line of `section`, as `build` does.
+  pub(crate) fn add(
+    &mut self,
+    r: &RowLine<'_>,
+    section: Section,
+    diagnostics: &mut Diagnostics,
+  ) {
+    match self.0.get(r.row_name) {
+      Some(row_type) => diagnostics.error(
+        Error::new(
+          ErrorKind::Conflict,
+          format!(
+            "conflicting row type information for {}: found {:?} and {:?}",
+            r.row_name, r.row_type, row_type
+          ),
+        )
+        .in_section(section)
+        .with_token(r.row_name),
+        &[],
+      ),
+      None => {
+        self.0.insert(r.row_name.to_string(), r.row_type.clone());
+      }
+    }
+  }
+
   pub fn exists(&self, name: &str) -> Result<()> {
     match self.get(name) {
       Some(_) => Ok(()),
-      None => Err(eyre!(format!(
-        "referenced row of unspecified type: {}",
-        name
-      ))),
+      None => Err(
+        Error::new(
+          ErrorKind::UnknownRow,
+          format!("referenced row of unspecified type: {}", name),
+        )
+        .with_token(name),
+      ),
     }?;
     Ok(())
   }
   pub fn get(&self, row_name: &str) -> Option<&RowType> {
     self.0.get(row_name)
   }
+
+  /// Chooses the objective row: `objective_name` if given, otherwise the
+  /// first `N` row.
+  pub fn objective<'a>(
+    &'a self,
+    objective_name: Option<&'a str>,
+  ) -> Option<&'a str> {
+    objective_name.or_else(|| {
+      self
+        .0
+        .iter()
+        .find(|(_, row_type)| **row_type == RowType::Nr)
+        .map(|(row_name, _)| row_name.as_str())
+    })
+  }
+
+  /// Lists the `N` rows other than the objective row, in ROWS order.
+  pub fn free_rows(&self, objective_name: Option<&str>) -> Vec<&str> {
+    let objective_name = self.objective(objective_name);
+    self
+      .0
+      .iter()
+      .filter(|(row_name, row_type)| {
+        **row_type == RowType::Nr && Some(row_name.as_str()) != objective_name
+      })
+      .map(|(row_name, _)| row_name.as_str())
+      .collect()
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
   use crate::types::Parser;
+  use color_eyre::eyre::eyre;
 
   #[test]
   fn test_conflicting_rows_line() -> Result<()> {
@@ -0,0 +1,41 @@
+use crate::error::{Error, ErrorKind};
+use crate::types::Section;
+use color_eyre::Result;
+use indexmap::IndexMap;
+
+/// Lists the names of the sets of a RHS, RANGES or BOUNDS section in file
+/// order.
+pub(crate) fn names<S>(sets: &IndexMap<String, S>) -> Vec<&str> {
+  sets.keys().map(String::as_str).collect()
+}
+
+/// Selects the named set of `section`, or the first one when `name` is
+/// `None`.
+///
+/// Returns `Ok(None)` when the section is empty.
+pub(crate) fn select<'a, S>(
+  sets: &'a IndexMap<String, S>,
+  name: Option<&str>,
+  section: Section,
+) -> Result<Option<(&'a str, &'a S)>> {
+  match name {
+    None => Ok(sets.first().map(|(name, set)| (name.as_str(), set))),
+    Some(name) => match sets.get_key_value(name) {
+      Some((name, set)) => Ok(Some((name.as_str(), set))),
+      None => Err(
+        Error::new(
+          ErrorKind::UnknownSet,
+          format!(
+            "unknown {} set {:?}: found {:?}",
+            section.header(),
+            name,
+            names(sets)
+          ),
+        )
+        .in_section(section)
+        .with_token(name)
+        .into(),
+      ),
+    },
+  }
+}
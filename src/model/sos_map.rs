@@ -0,0 +1,100 @@
+use crate::error::{Diagnostics, Error, ErrorKind, InSection};
+use crate::model::column_type_map::ColumnTypeMap;
+use crate::types::{SOSLine, SOSType, Section, SpecialOrderedSets};
+use color_eyre::Result;
+use fast_float2::FastFloat;
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A special ordered set with its member columns and their weights.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpecialOrderedSet<T: FastFloat> {
+  pub sos_type: SOSType,
+  pub members: IndexMap<String, T>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpecialOrderedSetMap<T: FastFloat>(
+  pub IndexMap<String, SpecialOrderedSet<T>>,
+);
+
+impl<T: FastFloat> TryFrom<(&SpecialOrderedSets<'_, T>, &ColumnTypeMap)>
+  for SpecialOrderedSetMap<T>
+{
+  type Error = color_eyre::Report;
+
+  fn try_from(t: (&SpecialOrderedSets<'_, T>, &ColumnTypeMap)) -> Result<Self> {
+    let mut diagnostics = Diagnostics::default();
+    let sets = SpecialOrderedSetMap::build(t, &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(sets)
+  }
+}
+
+impl<T: FastFloat> SpecialOrderedSetMap<T> {
+  /// Builds the map, recording every unknown column and duplicate member or
+  /// set in `diagnostics` and skipping the offending entries.
+  pub(crate) fn build(
+    t: (&SpecialOrderedSets<'_, T>, &ColumnTypeMap),
+    diagnostics: &mut Diagnostics,
+  ) -> Self {
+    let mut sets = SpecialOrderedSetMap(IndexMap::new());
+    let (sos_lines, column_types) = t;
+    for s in sos_lines {
+      sets.add(s, column_types, diagnostics);
+    }
+    sets
+  }
+
+  /// Adds a single SOS set and its members, as `build` does.
+  pub(crate) fn add(
+    &mut self,
+    s: &SOSLine<'_, T>,
+    column_types: &ColumnTypeMap,
+    diagnostics: &mut Diagnostics,
+  ) {
+    let mut members = IndexMap::new();
+    for m in &s.members {
+      let exists = column_types.exists(m.var_name).in_section(Section::Sos);
+      if diagnostics.check(exists, &[s.set_name]).is_none() {
+        continue;
+      }
+      if let Some(conflicting_weight) =
+        members.insert(m.var_name.to_string(), m.weight)
+      {
+        diagnostics.error(
+          Error::new(
+            ErrorKind::DuplicateEntry,
+            format!(
+              "duplicate member in SOS {:?} for column {:?}: found {:?} and {:?}",
+              s.set_name, m.var_name, m.weight, conflicting_weight
+            ),
+          )
+          .in_section(Section::Sos)
+          .with_token(m.var_name),
+          &[s.set_name],
+        );
+      }
+    }
+    if self.0.contains_key(s.set_name) {
+      diagnostics.error(
+        Error::new(
+          ErrorKind::DuplicateEntry,
+          format!("duplicate SOS set {:?}", s.set_name),
+        )
+        .in_section(Section::Sos)
+        .with_token(s.set_name),
+        &[],
+      );
+      return;
+    }
+    let set = SpecialOrderedSet {
+      sos_type: s.sos_type.clone(),
+      members,
+    };
+    self.0.insert(s.set_name.to_string(), set);
+  }
+}
@@ -0,0 +1,142 @@
+use crate::types::{ObjectiveSense, VariableType};
+use fast_float2::FastFloat;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A sparse matrix in coordinate (triplet) format.
+///
+/// Entry `k` is `a[row_indices[k]][column_indices[k]] = values[k]`, in the
+/// order the coefficients appear in the COLUMNS section.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CooMatrix<T: FastFloat> {
+  pub num_rows: usize,
+  pub num_columns: usize,
+  pub row_indices: Vec<usize>,
+  pub column_indices: Vec<usize>,
+  pub values: Vec<T>,
+}
+
+/// A sparse matrix in compressed sparse column format.
+///
+/// The entries of column `j` are at positions
+/// `column_starts[j]..column_starts[j + 1]`, sorted by row index.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CscMatrix<T: FastFloat> {
+  pub num_rows: usize,
+  pub num_columns: usize,
+  pub column_starts: Vec<usize>,
+  pub row_indices: Vec<usize>,
+  pub values: Vec<T>,
+}
+
+/// A sparse matrix in compressed sparse row format.
+///
+/// The entries of row `i` are at positions `row_starts[i]..row_starts[i + 1]`,
+/// sorted by column index.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CsrMatrix<T: FastFloat> {
+  pub num_rows: usize,
+  pub num_columns: usize,
+  pub row_starts: Vec<usize>,
+  pub column_indices: Vec<usize>,
+  pub values: Vec<T>,
+}
+
+impl<T: FastFloat> CooMatrix<T> {
+  /// Converts to compressed sparse column format.
+  pub fn to_csc(&self) -> CscMatrix<T> {
+    let (column_starts, order) =
+      compress(self.num_columns, &self.column_indices, &self.row_indices);
+    CscMatrix {
+      num_rows: self.num_rows,
+      num_columns: self.num_columns,
+      column_starts,
+      row_indices: order.iter().map(|&k| self.row_indices[k]).collect(),
+      values: order.iter().map(|&k| self.values[k]).collect(),
+    }
+  }
+
+  /// Converts to compressed sparse row format.
+  pub fn to_csr(&self) -> CsrMatrix<T> {
+    let (row_starts, order) =
+      compress(self.num_rows, &self.row_indices, &self.column_indices);
+    CsrMatrix {
+      num_rows: self.num_rows,
+      num_columns: self.num_columns,
+      row_starts,
+      column_indices: order.iter().map(|&k| self.column_indices[k]).collect(),
+      values: order.iter().map(|&k| self.values[k]).collect(),
+    }
+  }
+}
+
+/// Orders the entries by `(major, minor)` index and returns the start offset
+/// of every major index along with the permutation of entries.
+fn compress(
+  num_major: usize,
+  major: &[usize],
+  minor: &[usize],
+) -> (Vec<usize>, Vec<usize>) {
+  let mut order = (0..major.len()).collect::<Vec<_>>();
+  order.sort_unstable_by_key(|&k| (major[k], minor[k]));
+  let mut starts = vec![0; num_major + 1];
+  for &m in major {
+    starts[m + 1] += 1;
+  }
+  for m in 0..num_major {
+    starts[m + 1] += starts[m];
+  }
+  (starts, order)
+}
+
+/// A `Model` laid out as index-based vectors and a sparse constraint matrix,
+/// ready to hand to a solver kernel.
+///
+/// Row index `i` refers to the `i`-th constraint row (`row_names[i]`) and
+/// column index `j` to the `j`-th column (`column_names[j]`), both in file
+/// order. The model reads
+///
+/// ```text
+/// optimize    objective' x + objective_offset
+/// subject to  row_lower <= A x <= row_upper
+///             column_lower <= x <= column_upper
+/// ```
+///
+/// where `objective_sense` is the OBJSENSE of the model, minimizing when the
+/// file has none. Infinite limits are represented by `T::NEG_INFINITY` and
+/// `T::INFINITY`.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SparseModel<T: FastFloat> {
+  pub objective_sense: Option<ObjectiveSense>,
+  pub row_names: Vec<String>,
+  pub column_names: Vec<String>,
+  pub matrix: CooMatrix<T>,
+  pub objective: Vec<T>,
+  pub objective_offset: T,
+  pub column_lower: Vec<T>,
+  pub column_upper: Vec<T>,
+  pub column_kinds: Vec<VariableType>,
+  pub row_lower: Vec<T>,
+  pub row_upper: Vec<T>,
+}
+
+impl<T: FastFloat> SparseModel<T> {
+  /// The constraint matrix in coordinate format.
+  pub fn coo(&self) -> &CooMatrix<T> {
+    &self.matrix
+  }
+
+  /// The constraint matrix in compressed sparse column format.
+  pub fn csc(&self) -> CscMatrix<T> {
+    self.matrix.to_csc()
+  }
+
+  /// The constraint matrix in compressed sparse row format.
+  pub fn csr(&self) -> CsrMatrix<T> {
+    self.matrix.to_csr()
+  }
+}
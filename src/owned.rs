@@ -0,0 +1,615 @@
+//! Owned counterparts of the parsed MPS types.
+//!
+//! `Parser<'a, T>` borrows every name from the input buffer. `ParserOwned<T>`
+//! holds the same data with `String` names, so it can outlive the buffer, be
+//! sent across threads or be assembled in code. Convert with
+//! `Parser::into_owned` and back with `ParserOwned::as_parser`, which borrows
+//! the names again so that `Model::try_from` and the writer can be reused.
+//!
+//! ```
+//! use mps::{Parser, ParserOwned};
+//!
+//! let owned: ParserOwned<f64> = {
+//!   let input = String::from("NAME example\nROWS\n N  COST\nCOLUMNS\nENDATA\n");
+//!   Parser::parse(&input)?.into_owned()
+//! };
+//! assert_eq!(owned.name, "example");
+//! assert_eq!(owned.as_parser().rows[0].row_name, "COST");
+//! # Ok::<(), color_eyre::Report>(())
+//! ```
+use crate::types::{
+  BoundType, BoundsLine, BranchDirection, BranchPriority, ConeConstraint,
+  ConeMember, ConeType, IndicatorLine, LazyConstraintLine, ObjectiveSense,
+  Parser, QuadraticConstraint, QuadraticObjectiveTerm, QuadraticTerm, RowLine,
+  RowType, RowValuePair, SOSLine, SOSMember, SOSType, WideLine,
+};
+use fast_float2::FastFloat;
+use indexmap::IndexSet;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An owned `Parser`, independent of the input buffer.
+///
+/// Fields mirror `Parser` one to one; see there for their meaning.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParserOwned<T: FastFloat> {
+  pub name: String,
+  pub objective_sense: Option<ObjectiveSense>,
+  pub objective_name: Option<String>,
+  pub reference_row: Option<String>,
+  pub rows: Vec<RowLineOwned>,
+  pub columns: Vec<WideLineOwned<T>>,
+  pub integer_columns: IndexSet<String>,
+  pub rhs: Option<Vec<WideLineOwned<T>>>,
+  pub ranges: Option<Vec<WideLineOwned<T>>>,
+  pub bounds: Option<Vec<BoundsLineOwned<T>>>,
+  pub user_cuts: Option<Vec<RowLineOwned>>,
+  pub special_ordered_sets: Option<Vec<SOSLineOwned<T>>>,
+  pub quadratic_objective: Option<Vec<QuadraticObjectiveTermOwned<T>>>,
+  pub full_quadratic_objective: bool,
+  pub quadratic_constraints: Option<Vec<QuadraticConstraintOwned<T>>>,
+  pub indicators: Option<Vec<IndicatorLineOwned>>,
+  pub lazy_constraints: Option<Vec<LazyConstraintLineOwned>>,
+  pub cone_constraints: Option<Vec<ConeConstraintOwned<T>>>,
+  pub branch_priorities: Option<Vec<BranchPriorityOwned>>,
+}
+
+/// Converts every element of an optional section.
+fn convert<'a, A: 'a, B: From<&'a A>>(
+  section: &'a Option<Vec<A>>,
+) -> Option<Vec<B>> {
+  section
+    .as_ref()
+    .map(|lines| lines.iter().map(B::from).collect())
+}
+
+impl<T: FastFloat> Parser<'_, T> {
+  /// Copies every name out of the input buffer.
+  pub fn into_owned(self) -> ParserOwned<T> {
+    ParserOwned::from(&self)
+  }
+}
+
+impl<T: FastFloat> From<&Parser<'_, T>> for ParserOwned<T> {
+  fn from(p: &Parser<'_, T>) -> Self {
+    ParserOwned {
+      name: p.name.to_string(),
+      objective_sense: p.objective_sense,
+      objective_name: p.objective_name.map(str::to_string),
+      reference_row: p.reference_row.map(str::to_string),
+      rows: p.rows.iter().map(RowLineOwned::from).collect(),
+      columns: p.columns.iter().map(WideLineOwned::from).collect(),
+      integer_columns: p
+        .integer_columns
+        .iter()
+        .map(|name| name.to_string())
+        .collect(),
+      rhs: convert(&p.rhs),
+      ranges: convert(&p.ranges),
+      bounds: convert(&p.bounds),
+      user_cuts: convert(&p.user_cuts),
+      special_ordered_sets: convert(&p.special_ordered_sets),
+      quadratic_objective: convert(&p.quadratic_objective),
+      full_quadratic_objective: p.full_quadratic_objective,
+      quadratic_constraints: convert(&p.quadratic_constraints),
+      indicators: convert(&p.indicators),
+      lazy_constraints: convert(&p.lazy_constraints),
+      cone_constraints: convert(&p.cone_constraints),
+      branch_priorities: convert(&p.branch_priorities),
+    }
+  }
+}
+
+impl<T: FastFloat> From<Parser<'_, T>> for ParserOwned<T> {
+  fn from(p: Parser<'_, T>) -> Self {
+    ParserOwned::from(&p)
+  }
+}
+
+impl<T: FastFloat> ParserOwned<T> {
+  /// Borrows the names again as a `Parser`.
+  pub fn as_parser(&self) -> Parser<'_, T> {
+    Parser::from(self)
+  }
+}
+
+impl<'a, T: FastFloat> From<&'a ParserOwned<T>> for Parser<'a, T> {
+  fn from(p: &'a ParserOwned<T>) -> Self {
+    Parser {
+      name: &p.name,
+      objective_sense: p.objective_sense,
+      objective_name: p.objective_name.as_deref(),
+      reference_row: p.reference_row.as_deref(),
+      rows: p.rows.iter().map(RowLine::from).collect(),
+      columns: p.columns.iter().map(WideLine::from).collect(),
+      integer_columns: p.integer_columns.iter().map(String::as_str).collect(),
+      rhs: convert(&p.rhs),
+      ranges: convert(&p.ranges),
+      bounds: convert(&p.bounds),
+      user_cuts: convert(&p.user_cuts),
+      special_ordered_sets: convert(&p.special_ordered_sets),
+      quadratic_objective: convert(&p.quadratic_objective),
+      full_quadratic_objective: p.full_quadratic_objective,
+      quadratic_constraints: convert(&p.quadratic_constraints),
+      indicators: convert(&p.indicators),
+      lazy_constraints: convert(&p.lazy_constraints),
+      cone_constraints: convert(&p.cone_constraints),
+      branch_priorities: convert(&p.branch_priorities),
+    }
+  }
+}
+
+/// An owned `RowLine`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RowLineOwned {
+  pub row_type: RowType,
+  pub row_name: String,
+}
+
+impl From<&RowLine<'_>> for RowLineOwned {
+  fn from(r: &RowLine<'_>) -> Self {
+    RowLineOwned {
+      row_type: r.row_type.clone(),
+      row_name: r.row_name.to_string(),
+    }
+  }
+}
+
+impl<'a> From<&'a RowLineOwned> for RowLine<'a> {
+  fn from(r: &'a RowLineOwned) -> Self {
+    RowLine {
+      row_type: r.row_type.clone(),
+      row_name: &r.row_name,
+    }
+  }
+}
+
+/// An owned `RowValuePair`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RowValuePairOwned<T> {
+  pub row_name: String,
+  pub value: T,
+}
+
+impl<T: Copy> From<&RowValuePair<'_, T>> for RowValuePairOwned<T> {
+  fn from(p: &RowValuePair<'_, T>) -> Self {
+    RowValuePairOwned {
+      row_name: p.row_name.to_string(),
+      value: p.value,
+    }
+  }
+}
+
+impl<'a, T: Copy> From<&'a RowValuePairOwned<T>> for RowValuePair<'a, T> {
+  fn from(p: &'a RowValuePairOwned<T>) -> Self {
+    RowValuePair {
+      row_name: &p.row_name,
+      value: p.value,
+    }
+  }
+}
+
+/// An owned `WideLine`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WideLineOwned<T> {
+  pub name: String,
+  pub first_pair: RowValuePairOwned<T>,
+  pub second_pair: Option<RowValuePairOwned<T>>,
+}
+
+impl<T: Copy> From<&WideLine<'_, T>> for WideLineOwned<T> {
+  fn from(w: &WideLine<'_, T>) -> Self {
+    WideLineOwned {
+      name: w.name.to_string(),
+      first_pair: (&w.first_pair).into(),
+      second_pair: w.second_pair.as_ref().map(RowValuePairOwned::from),
+    }
+  }
+}
+
+impl<'a, T: Copy> From<&'a WideLineOwned<T>> for WideLine<'a, T> {
+  fn from(w: &'a WideLineOwned<T>) -> Self {
+    WideLine {
+      name: &w.name,
+      first_pair: (&w.first_pair).into(),
+      second_pair: w.second_pair.as_ref().map(RowValuePair::from),
+    }
+  }
+}
+
+/// An owned `BoundsLine`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoundsLineOwned<T> {
+  pub bound_type: BoundType,
+  pub bound_name: String,
+  pub column_name: String,
+  pub value: Option<T>,
+}
+
+impl<T: Copy> From<&BoundsLine<'_, T>> for BoundsLineOwned<T> {
+  fn from(b: &BoundsLine<'_, T>) -> Self {
+    BoundsLineOwned {
+      bound_type: b.bound_type.clone(),
+      bound_name: b.bound_name.to_string(),
+      column_name: b.column_name.to_string(),
+      value: b.value,
+    }
+  }
+}
+
+impl<'a, T: Copy> From<&'a BoundsLineOwned<T>> for BoundsLine<'a, T> {
+  fn from(b: &'a BoundsLineOwned<T>) -> Self {
+    BoundsLine {
+      bound_type: b.bound_type.clone(),
+      bound_name: &b.bound_name,
+      column_name: &b.column_name,
+      value: b.value,
+    }
+  }
+}
+
+/// An owned `IndicatorLine`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IndicatorLineOwned {
+  pub binary_var: String,
+  pub trigger_value: u8,
+  pub constraint_name: String,
+}
+
+impl From<&IndicatorLine<'_>> for IndicatorLineOwned {
+  fn from(i: &IndicatorLine<'_>) -> Self {
+    IndicatorLineOwned {
+      binary_var: i.binary_var.to_string(),
+      trigger_value: i.trigger_value,
+      constraint_name: i.constraint_name.to_string(),
+    }
+  }
+}
+
+impl<'a> From<&'a IndicatorLineOwned> for IndicatorLine<'a> {
+  fn from(i: &'a IndicatorLineOwned) -> Self {
+    IndicatorLine {
+      binary_var: &i.binary_var,
+      trigger_value: i.trigger_value,
+      constraint_name: &i.constraint_name,
+    }
+  }
+}
+
+/// An owned `LazyConstraintLine`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LazyConstraintLineOwned {
+  pub priority: Option<i32>,
+  pub row_name: String,
+}
+
+impl From<&LazyConstraintLine<'_>> for LazyConstraintLineOwned {
+  fn from(l: &LazyConstraintLine<'_>) -> Self {
+    LazyConstraintLineOwned {
+      priority: l.priority,
+      row_name: l.row_name.to_string(),
+    }
+  }
+}
+
+impl<'a> From<&'a LazyConstraintLineOwned> for LazyConstraintLine<'a> {
+  fn from(l: &'a LazyConstraintLineOwned) -> Self {
+    LazyConstraintLine {
+      priority: l.priority,
+      row_name: &l.row_name,
+    }
+  }
+}
+
+/// An owned `QuadraticObjectiveTerm`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuadraticObjectiveTermOwned<T: FastFloat> {
+  pub var1: String,
+  pub var2: String,
+  pub coefficient: T,
+}
+
+impl<T: FastFloat> From<&QuadraticObjectiveTerm<'_, T>>
+  for QuadraticObjectiveTermOwned<T>
+{
+  fn from(q: &QuadraticObjectiveTerm<'_, T>) -> Self {
+    QuadraticObjectiveTermOwned {
+      var1: q.var1.to_string(),
+      var2: q.var2.to_string(),
+      coefficient: q.coefficient,
+    }
+  }
+}
+
+impl<'a, T: FastFloat> From<&'a QuadraticObjectiveTermOwned<T>>
+  for QuadraticObjectiveTerm<'a, T>
+{
+  fn from(q: &'a QuadraticObjectiveTermOwned<T>) -> Self {
+    QuadraticObjectiveTerm {
+      var1: &q.var1,
+      var2: &q.var2,
+      coefficient: q.coefficient,
+    }
+  }
+}
+
+/// An owned `SOSLine`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SOSLineOwned<T: FastFloat> {
+  pub sos_type: SOSType,
+  pub set_name: String,
+  pub members: Vec<SOSMemberOwned<T>>,
+}
+
+/// An owned `SOSMember`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SOSMemberOwned<T: FastFloat> {
+  pub var_name: String,
+  pub weight: T,
+}
+
+impl<T: FastFloat> From<&SOSLine<'_, T>> for SOSLineOwned<T> {
+  fn from(s: &SOSLine<'_, T>) -> Self {
+    SOSLineOwned {
+      sos_type: s.sos_type.clone(),
+      set_name: s.set_name.to_string(),
+      members: s
+        .members
+        .iter()
+        .map(|m| SOSMemberOwned {
+          var_name: m.var_name.to_string(),
+          weight: m.weight,
+        })
+        .collect(),
+    }
+  }
+}
+
+impl<'a, T: FastFloat> From<&'a SOSLineOwned<T>> for SOSLine<'a, T> {
+  fn from(s: &'a SOSLineOwned<T>) -> Self {
+    SOSLine {
+      sos_type: s.sos_type.clone(),
+      set_name: &s.set_name,
+      members: s
+        .members
+        .iter()
+        .map(|m| SOSMember {
+          var_name: &m.var_name,
+          weight: m.weight,
+        })
+        .collect(),
+    }
+  }
+}
+
+/// An owned `QuadraticConstraint`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuadraticConstraintOwned<T: FastFloat> {
+  pub row_name: String,
+  pub terms: Vec<QuadraticTermOwned<T>>,
+}
+
+/// An owned `QuadraticTerm`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuadraticTermOwned<T: FastFloat> {
+  pub var1: String,
+  pub var2: String,
+  pub coefficient: T,
+}
+
+impl<T: FastFloat> From<&QuadraticConstraint<'_, T>>
+  for QuadraticConstraintOwned<T>
+{
+  fn from(c: &QuadraticConstraint<'_, T>) -> Self {
+    QuadraticConstraintOwned {
+      row_name: c.row_name.to_string(),
+      terms: c
+        .terms
+        .iter()
+        .map(|t| QuadraticTermOwned {
+          var1: t.var1.to_string(),
+          var2: t.var2.to_string(),
+          coefficient: t.coefficient,
+        })
+        .collect(),
+    }
+  }
+}
+
+impl<'a, T: FastFloat> From<&'a QuadraticConstraintOwned<T>>
+  for QuadraticConstraint<'a, T>
+{
+  fn from(c: &'a QuadraticConstraintOwned<T>) -> Self {
+    QuadraticConstraint {
+      row_name: &c.row_name,
+      terms: c
+        .terms
+        .iter()
+        .map(|t| QuadraticTerm {
+          var1: &t.var1,
+          var2: &t.var2,
+          coefficient: t.coefficient,
+        })
+        .collect(),
+    }
+  }
+}
+
+/// An owned `ConeConstraint`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConeConstraintOwned<T: FastFloat> {
+  pub cone_name: String,
+  pub cone_type: ConeType,
+  pub members: Vec<ConeMemberOwned<T>>,
+}
+
+/// An owned `ConeMember`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConeMemberOwned<T: FastFloat> {
+  pub var_name: String,
+  pub coefficient: Option<T>,
+}
+
+impl<T: FastFloat> From<&ConeConstraint<'_, T>> for ConeConstraintOwned<T> {
+  fn from(c: &ConeConstraint<'_, T>) -> Self {
+    ConeConstraintOwned {
+      cone_name: c.cone_name.to_string(),
+      cone_type: c.cone_type.clone(),
+      members: c
+        .members
+        .iter()
+        .map(|m| ConeMemberOwned {
+          var_name: m.var_name.to_string(),
+          coefficient: m.coefficient,
+        })
+        .collect(),
+    }
+  }
+}
+
+impl<'a, T: FastFloat> From<&'a ConeConstraintOwned<T>>
+  for ConeConstraint<'a, T>
+{
+  fn from(c: &'a ConeConstraintOwned<T>) -> Self {
+    ConeConstraint {
+      cone_name: &c.cone_name,
+      cone_type: c.cone_type.clone(),
+      members: c
+        .members
+        .iter()
+        .map(|m| ConeMember {
+          var_name: &m.var_name,
+          coefficient: m.coefficient,
+        })
+        .collect(),
+    }
+  }
+}
+
+/// An owned `BranchPriority`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BranchPriorityOwned {
+  pub var_name: String,
+  pub priority: i32,
+  pub direction: BranchDirection,
+}
+
+impl From<&BranchPriority<'_>> for BranchPriorityOwned {
+  fn from(b: &BranchPriority<'_>) -> Self {
+    BranchPriorityOwned {
+      var_name: b.var_name.to_string(),
+      priority: b.priority,
+      direction: b.direction,
+    }
+  }
+}
+
+impl<'a> From<&'a BranchPriorityOwned> for BranchPriority<'a> {
+  fn from(b: &'a BranchPriorityOwned) -> Self {
+    BranchPriority {
+      var_name: &b.var_name,
+      priority: b.priority,
+      direction: b.direction,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::Model;
+  use crate::types::Format;
+  use color_eyre::Result;
+
+  const EXTENSIONS: &str = "NAME          EXTTEST
+OBJSENSE
+    MAX
+OBJNAME
+    cost
+REFROW
+    c1
+ROWS
+ N  obj
+ N  cost
+ L  c1
+ E  c2
+USERCUTS
+ L  cut1
+COLUMNS
+    x1        cost                 1.0   c1                    1.0
+    x1        cut1                 1.0
+    MARKER                 'MARKER'                 'INTORG'
+    x2        cost                 2.0   c2                    1.0
+    MARKER                 'MARKER'                 'INTEND'
+    x3        obj                  1.0
+RHS
+    rhs1      c1                  20.0   cut1                 10.0
+RANGES
+    rng1      c2                  -4.5
+BOUNDS
+ UP bnd1      x2                   1.0
+ BV bnd1      x3
+ FR bnd2      x1
+SOS
+ S1 set1
+    x1 1.0
+    x2 2.0
+QUADOBJ
+    x1        x1                   2.0
+    x1        x2                   1.0
+QCMATRIX      c1
+    x1        x1                   1.0
+CSECTION
+ RQUAD
+    x1
+    x2
+INDICATORS
+ IF c1 x2 1
+LAZYCONS
+    c2
+    3 c1
+BRANCH
+ UP x2 10
+    x1 5
+ENDATA";
+
+  #[test]
+  fn test_round_trip() -> Result<()> {
+    let parsed = Parser::<f64>::parse(EXTENSIONS)?;
+    let owned = parsed.clone().into_owned();
+    assert_eq!(owned.as_parser(), parsed);
+    assert_eq!(
+      owned.as_parser().to_mps(Format::Fixed)?,
+      parsed.to_mps(Format::Fixed)?
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_outlives_input() -> Result<()> {
+    let owned = {
+      let input = EXTENSIONS.to_string();
+      Parser::<f64>::parse(&input)?.into_owned()
+    };
+    let model = std::thread::spawn(move || Model::try_from(owned.as_parser()))
+      .join()
+      .unwrap()?;
+    assert_eq!(model, Model::try_from(Parser::<f64>::parse(EXTENSIONS)?)?);
+    Ok(())
+  }
+}
@@ -0,0 +1,348 @@
+//! Parsing the bulk sections of a file on several threads.
+//!
+//! COLUMNS, RHS, RANGES and BOUNDS hold nearly every line of a large file,
+//! and each of their lines parses on its own. `Parallel` finds where such a
+//! section ends, cuts it into chunks at line boundaries, parses the chunks on
+//! the rayon thread pool and joins the results in file order. Whatever spans
+//! lines, such as the INTORG/INTEND blocks of COLUMNS or where the section
+//! stops, is settled afterwards in file order, so that the result, and any
+//! error, is exactly that of the sequential parser.
+use crate::parse::{line_ending_flexible, Sections};
+use crate::types::*;
+use fast_float2::FastFloat;
+use nom::{
+  bytes::complete::tag,
+  character::complete::space0,
+  error::{Error, ErrorKind},
+  IResult,
+};
+use rayon::prelude::*;
+use std::cmp;
+
+/// The default smallest chunk; smaller sections stay on one thread.
+pub(crate) const MIN_CHUNK: usize = 64 * 1024;
+
+/// Where a chunk stopped parsing, if it did.
+type Stop<'a> = nom::Err<Error<&'a str>>;
+
+/// Parses the bulk sections in chunks of at least `min_chunk` bytes.
+pub(crate) struct Parallel {
+  pub min_chunk: usize,
+}
+
+/// A COLUMNS line that matters once the chunks are joined.
+enum Column<'a, T: FastFloat> {
+  /// A MARKER line and the input at its start
+  Marker(Option<MarkerType>, &'a str),
+  Line(WideLine<'a, T>),
+}
+
+/// Parses a section header such as `COLUMNS`, with optional trailing spaces.
+fn header<'a>(s: &'a str, name: &'static str) -> IResult<&'a str, ()> {
+  let (s, _) = tag(name)(s)?;
+  let (s, _) = space0(s)?;
+  let (s, _) = line_ending_flexible(s)?;
+  Ok((s, ()))
+}
+
+/// The length of the section body at the start of `s`.
+///
+/// Only a line starting with a blank, a tab, a `*` or a line break can belong
+/// to the section, so the body ends at the first other line. It may end
+/// earlier, which the line parsers find out.
+fn body_len(s: &str) -> usize {
+  let bytes = s.as_bytes();
+  let mut start = 0;
+  while start < bytes.len() {
+    if !matches!(bytes[start], b' ' | b'\t' | b'*' | b'\r' | b'\n') {
+      return start;
+    }
+    match bytes[start..].iter().position(|&b| b == b'\n') {
+      Some(end) => start += end + 1,
+      None => return bytes.len(),
+    }
+  }
+  bytes.len()
+}
+
+/// Cuts `body` after the first line break past every `size` bytes.
+fn chunks(body: &str, size: usize) -> Vec<&str> {
+  let mut chunks = Vec::new();
+  let mut rest = body;
+  while !rest.is_empty() {
+    let end = rest.as_bytes()[cmp::min(size, rest.len())..]
+      .iter()
+      .position(|&b| b == b'\n')
+      .map_or(rest.len(), |end| size + end + 1);
+    let (chunk, tail) = rest.split_at(end);
+    chunks.push(chunk);
+    rest = tail;
+  }
+  chunks
+}
+
+/// Parses the lines of `chunk` until one of them stops the section.
+fn scan<'a, I>(
+  chunk: &'a str,
+  line: impl Fn(&'a str) -> IResult<&'a str, Option<I>>,
+) -> (Vec<I>, Option<Stop<'a>>) {
+  let mut items = Vec::new();
+  let mut s = chunk;
+  while !s.is_empty() {
+    match line(s) {
+      // As in `many0`, a line parser must make progress
+      Ok((rest, _)) if rest.len() == s.len() => {
+        return (
+          items,
+          Some(nom::Err::Error(Error::new(s, ErrorKind::Many0))),
+        )
+      }
+      Ok((rest, item)) => {
+        items.extend(item);
+        s = rest;
+      }
+      // Like `many0`, stop the section at the start of the line
+      Err(nom::Err::Error(e)) => {
+        return (items, Some(nom::Err::Error(Error::new(s, e.code))))
+      }
+      Err(e) => return (items, Some(e)),
+    }
+  }
+  (items, None)
+}
+
+/// The suffix of `s` starting where `part`, a suffix of a chunk of `s`,
+/// starts.
+fn rebase<'a>(s: &'a str, part: &str) -> &'a str {
+  &s[part.as_ptr() as usize - s.as_ptr() as usize..]
+}
+
+impl Parallel {
+  /// Parses the body of the section at the start of `s` line by line,
+  /// returning the items of every line before the section stops and where,
+  /// and how, it stops.
+  fn run<'a, I: Send>(
+    &self,
+    s: &'a str,
+    line: impl Fn(&'a str) -> IResult<&'a str, Option<I>> + Sync,
+  ) -> (Vec<I>, IResult<&'a str, ()>) {
+    let body = &s[..body_len(s)];
+    // About four chunks per thread, to even out their work
+    let size = cmp::max(
+      self.min_chunk.max(1),
+      body.len() / (4 * rayon::current_num_threads()),
+    );
+    let scanned = chunks(body, size)
+      .into_par_iter()
+      .map(|chunk| scan(chunk, &line))
+      .collect::<Vec<_>>();
+    let mut items = Vec::new();
+    for (chunk, stop) in scanned {
+      items.extend(chunk);
+      match stop {
+        None => (),
+        Some(nom::Err::Error(e)) => {
+          return (items, Ok((rebase(s, e.input), ())))
+        }
+        Some(nom::Err::Failure(e)) => {
+          let e = Error::new(rebase(s, e.input), e.code);
+          return (items, Err(nom::Err::Failure(e)));
+        }
+        Some(nom::Err::Incomplete(needed)) => {
+          return (items, Err(nom::Err::Incomplete(needed)))
+        }
+      }
+    }
+    (items, Ok((&s[body.len()..], ())))
+  }
+}
+
+impl<'a, T: FastFloat + Send> Sections<'a, T> for Parallel {
+  fn columns(
+    &self,
+    s: &'a str,
+    layout: Layout,
+  ) -> IResult<&'a str, (Vec<WideLine<'a, T>>, IntegerColumns<'a>)> {
+    let (s, _) = header(s, "COLUMNS")?;
+    let (items, stop) =
+      self.run(s, |s| match Parser::<T>::try_marker_line(s) {
+        Ok((rest, marker)) => Ok((rest, Some(Column::Marker(marker, s)))),
+        Err(nom::Err::Error(_)) => Parser::<T>::columns_line_in(s, layout)
+          .map(|(rest, line)| (rest, line.map(Column::Line))),
+        Err(e) => Err(e),
+      });
+
+    // Replay the integer blocks as `Parser::columns` does
+    let verify = |s| nom::Err::Failure(Error::new(s, ErrorKind::Verify));
+    let mut lines = Vec::new();
+    let mut integer_columns = IntegerColumns::new();
+    let mut integer_block = false;
+    for item in items {
+      match item {
+        Column::Marker(marker, at) => match (marker, integer_block) {
+          (Some(MarkerType::IntOrg), false) => integer_block = true,
+          (Some(MarkerType::IntEnd), true) => integer_block = false,
+          (Some(_), _) => return Err(verify(rebase(s, at))),
+          (None, _) => (),
+        },
+        Column::Line(line) => {
+          if integer_block {
+            integer_columns.insert(line.name);
+          }
+          lines.push(line);
+        }
+      }
+    }
+    let (s, _) = stop?;
+    if integer_block {
+      return Err(verify(s));
+    }
+    Ok((s, (lines, integer_columns)))
+  }
+
+  fn rhs(
+    &self,
+    s: &'a str,
+    layout: Layout,
+  ) -> IResult<&'a str, Vec<WideLine<'a, T>>> {
+    let (s, _) = header(s, "RHS")?;
+    let (lines, stop) = self.run(s, |s| Parser::<T>::rhs_line_in(s, layout));
+    let (s, _) = stop?;
+    Ok((s, lines))
+  }
+
+  fn ranges(
+    &self,
+    s: &'a str,
+    layout: Layout,
+  ) -> IResult<&'a str, Vec<WideLine<'a, T>>> {
+    let (s, _) = header(s, "RANGES")?;
+    let (lines, stop) = self.run(s, |s| Parser::<T>::ranges_line_in(s, layout));
+    let (s, _) = stop?;
+    Ok((s, lines))
+  }
+
+  fn bounds(
+    &self,
+    s: &'a str,
+    layout: Layout,
+  ) -> IResult<&'a str, Vec<BoundsLine<'a, T>>> {
+    let (s, _) = header(s, "BOUNDS")?;
+    let (lines, stop) = self.run(s, |s| Parser::<T>::bounds_line_in(s, layout));
+    let (s, _) = stop?;
+    Ok((s, lines))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Checks that chunked parsing agrees with `Parser::parse`, with chunks
+  /// from a line or two up to whole sections.
+  fn assert_same(input: &str) {
+    let expected = Parser::<f64>::parse(input);
+    for min_chunk in [64, MIN_CHUNK] {
+      let parsed = Parser::<f64>::parse_with(
+        input,
+        Layout::PerLine,
+        &Parallel { min_chunk },
+      );
+      assert_eq!(parsed, expected, "min_chunk {}", min_chunk);
+    }
+  }
+
+  #[test]
+  fn test_chunks() {
+    let body = " A\n B\r\n\n* C\n D";
+    assert_eq!(chunks(body, 1), [" A\n", " B\r\n", "\n* C\n", " D"]);
+    assert_eq!(chunks(body, 5), [" A\n B\r\n", "\n* C\n D"]);
+    assert_eq!(chunks(body, 100), [body]);
+    assert!(chunks("", 1).is_empty());
+    assert_eq!(body_len(body), body.len());
+    assert_eq!(body_len(" A\n\tB\nRHS\n C\n"), 6);
+  }
+
+  #[test]
+  fn test_netlib() -> std::io::Result<()> {
+    for entry in std::fs::read_dir("tests/data/netlib")? {
+      assert_same(&std::fs::read_to_string(entry?.path())?);
+    }
+    Ok(())
+  }
+
+  const MARKERS: &str = "NAME          MARKERS
+ROWS
+ N  COST
+ L  LIM1
+COLUMNS
+    MARKER                 'MARKER'                 'INTORG'
+    X1        COST         1.0   LIM1         1.0
+    X2        COST         2.0   LIM1         1.0
+    MARKER                 'MARKER'                 'INTEND'
+* continuous
+    Y1        COST         3.0   LIM1         1.0
+
+    MARKER                 'MARKER'                 'INTORG'
+    Z1        COST         4.0
+    MARKER                 'MARKER'                 'INTEND'
+RHS
+    RHS       LIM1         4.0
+RANGES
+    RNG       LIM1         2.5
+BOUNDS
+ UP BND       X1           4.0
+ FR BND       Y1
+ENDATA
+";
+
+  #[test]
+  fn test_markers() {
+    assert_same(MARKERS);
+    assert_same(&MARKERS.replace('\n', "\r\n"));
+    let parsed = Parser::<f64>::parse_with(
+      MARKERS,
+      Layout::PerLine,
+      &Parallel { min_chunk: 1 },
+    );
+    assert_eq!(
+      parsed
+        .unwrap()
+        .integer_columns
+        .into_iter()
+        .collect::<Vec<_>>(),
+      ["X1", "X2", "Z1"]
+    );
+  }
+
+  #[test]
+  fn test_errors() {
+    let intend =
+      "    MARKER                 'MARKER'                 'INTEND'\n";
+    let intorg =
+      "    MARKER                 'MARKER'                 'INTORG'\n";
+    let broken = [
+      // Unbalanced markers
+      MARKERS.replacen(intend, "", 1),
+      MARKERS.replacen(intorg, "", 1),
+      MARKERS.replacen(intend, intorg, 1),
+      MARKERS.replace(intend, "").replacen(intorg, "", 1),
+      // Lines that stop a section early
+      MARKERS.replace("    Y1        COST ", "Y1 COST "),
+      MARKERS
+        .replace("    Y1        COST         3.0", "    Y1        COST     x"),
+      MARKERS.replace("    RHS       LIM1         4.0", "    RHS       LIM1  "),
+      MARKERS.replace(" UP BND", " XX BND"),
+      MARKERS.replace(" FR BND       Y1", "\tFR BND       Y1"),
+      MARKERS.replace("ENDATA\n", ""),
+      MARKERS.replace("\nENDATA\n", ""),
+      MARKERS.replace("\n", "\r"),
+    ];
+    for input in &broken {
+      assert_same(input);
+    }
+    assert!(broken
+      .iter()
+      .any(|input| Parser::<f64>::parse(input).is_err()));
+  }
+}
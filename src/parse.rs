@@ -1,3 +1,4 @@
+use crate::error::Error;
 use crate::types::*;
 use color_eyre::{eyre::eyre, eyre::OptionExt, Result};
 use fast_float2::FastFloat;
@@ -19,25 +20,163 @@ cfg_if::cfg_if! {
   }
 }
 
-static L1: usize = 0;
-static R1: usize = 2;
-static L2: usize = 3;
-static R2: usize = 11;
-static L3: usize = 13;
-static R3: usize = 21;
-static L4: usize = 23;
-static R4: usize = 35;
-static L5: usize = 38;
-static R5: usize = 46;
-static L6: usize = 48;
-static R6: usize = 60;
+pub(crate) static L1: usize = 0;
+pub(crate) static R1: usize = 2;
+pub(crate) static L2: usize = 3;
+pub(crate) static R2: usize = 11;
+pub(crate) static L3: usize = 13;
+pub(crate) static R3: usize = 21;
+pub(crate) static L4: usize = 23;
+pub(crate) static R4: usize = 35;
+pub(crate) static L5: usize = 38;
+pub(crate) static R5: usize = 46;
+pub(crate) static L6: usize = 48;
+pub(crate) static R6: usize = 60;
+
+/// Parses the bulk sections (COLUMNS, RHS, RANGES and BOUNDS), which hold
+/// nearly every line of a large file.
+pub(crate) trait Sections<'a, T: FastFloat> {
+  fn columns(
+    &self,
+    s: Span<'a>,
+    layout: Layout,
+  ) -> IResult<Span<'a>, (Vec<WideLine<'a, T>>, IntegerColumns<'a>)>;
+  fn rhs(
+    &self,
+    s: Span<'a>,
+    layout: Layout,
+  ) -> IResult<Span<'a>, Vec<WideLine<'a, T>>>;
+  fn ranges(
+    &self,
+    s: Span<'a>,
+    layout: Layout,
+  ) -> IResult<Span<'a>, Vec<WideLine<'a, T>>>;
+  fn bounds(
+    &self,
+    s: Span<'a>,
+    layout: Layout,
+  ) -> IResult<Span<'a>, Vec<BoundsLine<'a, T>>>;
+}
+
+/// Parses the bulk sections one line after another.
+pub(crate) struct Sequential;
+
+impl<'a, T: FastFloat> Sections<'a, T> for Sequential {
+  fn columns(
+    &self,
+    s: Span<'a>,
+    layout: Layout,
+  ) -> IResult<Span<'a>, (Vec<WideLine<'a, T>>, IntegerColumns<'a>)> {
+    Parser::<T>::columns_in(s, layout)
+  }
+
+  fn rhs(
+    &self,
+    s: Span<'a>,
+    layout: Layout,
+  ) -> IResult<Span<'a>, Vec<WideLine<'a, T>>> {
+    Parser::<T>::rhs_in(s, layout)
+  }
+
+  fn ranges(
+    &self,
+    s: Span<'a>,
+    layout: Layout,
+  ) -> IResult<Span<'a>, Vec<WideLine<'a, T>>> {
+    Parser::<T>::ranges_in(s, layout)
+  }
+
+  fn bounds(
+    &self,
+    s: Span<'a>,
+    layout: Layout,
+  ) -> IResult<Span<'a>, Vec<BoundsLine<'a, T>>> {
+    Parser::<T>::bounds_in(s, layout)
+  }
+}
 
 /// Custom line ending parser that handles both Unix (\n) and Windows (\r\n) line endings
 /// Tries Unix first for better performance since it's more common
-fn line_ending_flexible(s: Span) -> IResult<Span, Span> {
+pub(crate) fn line_ending_flexible(s: Span) -> IResult<Span, Span> {
   alt((tag("\n"), tag("\r\n")))(s)
 }
 
+/// The text of a span.
+fn fragment(s: Span<'_>) -> &str {
+  cfg_if::cfg_if! {
+    if #[cfg(feature = "trace")] {
+      s.fragment()
+    } else {
+      s
+    }
+  }
+}
+
+/// Cuts an inline comment, a `$` after two blanks or a tab, off a
+/// whitespace-separated line. A `$` elsewhere may be part of a name.
+fn strip_inline_comment(line: &str) -> &str {
+  match line.find("  $").or_else(|| line.find("\t$")) {
+    Some(pos) => &line[..pos],
+    None => line,
+  }
+}
+
+/// Splits a fixed-format data line, without its leading blank, into its six
+/// fields, trimmed. Names may contain spaces, but anything outside the fields
+/// must be blank.
+fn fixed_fields(line: &str) -> Result<[&str; 6]> {
+  let positions = [(L1, R1), (L2, R2), (L3, R3), (L4, R4), (L5, R5), (L6, R6)];
+  let mut fields = [""; 6];
+  let mut end = 0;
+  for (field, (left, right)) in positions.into_iter().enumerate() {
+    if line.len() <= left {
+      break;
+    }
+    let gap = line.get(end..left).ok_or_eyre("not a fixed-format line")?;
+    if !gap.trim().is_empty() {
+      return Err(eyre!("text outside of the fixed-format fields"));
+    }
+    end = cmp::min(right, line.len());
+    fields[field] = line
+      .get(left..end)
+      .ok_or_eyre("not a fixed-format line")?
+      .trim();
+  }
+  let rest = line.get(end..).ok_or_eyre("not a fixed-format line")?;
+  if !rest.trim().is_empty() {
+    return Err(eyre!("text outside of the fixed-format fields"));
+  }
+  Ok(fields)
+}
+
+/// Parses a fixed-format ROWS line without its leading blank.
+fn parse_row_fixed(line: &str) -> Result<RowLine<'_>> {
+  let [row_type, row_name, rest @ ..] = fixed_fields(line)?;
+  let mut chars = row_type.chars();
+  let row_type = match (chars.next(), chars.next()) {
+    (Some(c), None) => RowType::try_from(c)?,
+    _ => return Err(eyre!("invalid row type {:?}", row_type)),
+  };
+  if row_name.is_empty() || rest.iter().any(|field| !field.is_empty()) {
+    return Err(eyre!("malformed fixed-format row line"));
+  }
+  Ok(RowLine { row_type, row_name })
+}
+
+/// Parses a free-format ROWS line: a row type and a name.
+fn parse_row_free(line: &str) -> Result<RowLine<'_>> {
+  match strip_inline_comment(line)
+    .split_whitespace()
+    .collect::<Vec<_>>()[..]
+  {
+    [row_type, row_name] if row_type.len() == 1 => Ok(RowLine {
+      row_type: RowType::try_from(row_type.chars().next().unwrap())?,
+      row_name,
+    }),
+    _ => Err(eyre!("malformed free-format row line")),
+  }
+}
+
 impl<'a, T: FastFloat> Parser<'a, T> {
   /// Parses an MPS formatted string into a `Parser` instance.
   ///
@@ -65,7 +204,8 @@ impl<'a, T: FastFloat> Parser<'a, T> {
   /// Result<Parser, Error>
   ///
   /// - Ok(Parser): The parsed MPS data as a `Parser` struct
-  /// - Err(Error): A nom error if parsing failed
+  /// - Err(Error): An `Error` with the line, column, section and kind of the
+  ///   first failure
   ///
   /// # Examples
   ///
@@ -77,40 +217,112 @@ impl<'a, T: FastFloat> Parser<'a, T> {
   ///     Err(err) => { /* handle error */ }
   /// }
   /// ```
-  pub fn parse(
+  pub fn parse(input: &'a str) -> Result<Parser<'a, T>, Error> {
+    Self::parse_with(input, Layout::PerLine, &Sequential)
+  }
+
+  /// Parses an MPS formatted string whose data lines are all in `format`,
+  /// returning the format they were read in.
+  ///
+  /// `Parser::parse` reads each line of ROWS, COLUMNS, RHS, RANGES and
+  /// BOUNDS at the fixed-format columns if it can and splits it at
+  /// whitespace otherwise, which misreads a fixed-format name containing a
+  /// space or a free-format line that happens to fit the columns. Here the
+  /// whole file is read one way:
+  ///
+  /// * `Format::Fixed` reads every field at its columns, so that names may
+  ///   contain spaces; anything between the fields must be blank.
+  /// * `Format::Free` follows the CPLEX and Gurobi free MPS rules: fields are
+  ///   separated by whitespace, names do not contain spaces and may be of
+  ///   any length, and RHS, RANGES and BOUNDS lines may leave out their set
+  ///   name, as in an RHS line ` COST -12.5` giving the negated objective
+  ///   constant.
+  /// * `Format::Auto` reads the file as fixed format if it is valid fixed
+  ///   format, and as free format otherwise, and returns the format it
+  ///   detected. If neither works, the error is that of the format that read
+  ///   further.
+  ///
+  /// # Errors
+  ///
+  /// Returns the error of `Parser::parse` for the data lines read in
+  /// `format`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use mps::types::Format;
+  /// use mps::Parser;
+  ///
+  /// let input = "NAME
+  /// ROWS
+  ///  N COST
+  ///  L LIMIT
+  /// COLUMNS
+  ///  QUANTITY COST 1 LIMIT 1
+  /// RHS
+  ///  LIMIT 4
+  /// ENDATA
+  /// ";
+  /// let (parsed, format) = Parser::<f64>::parse_format(input, Format::Auto)?;
+  /// assert_eq!(format, Format::Free);
+  /// assert_eq!(parsed.columns[0].name, "QUANTITY");
+  /// # Ok::<(), mps::error::Error>(())
+  /// ```
+  pub fn parse_format(
     input: &'a str,
-  ) -> Result<Parser<'a, T>, nom::error::Error<String>> {
+    format: Format,
+  ) -> Result<(Parser<'a, T>, Format), Error> {
+    match format {
+      Format::Auto => {
+        let fixed = match Self::parse_with(input, Layout::Fixed, &Sequential) {
+          Ok(parsed) => return Ok((parsed, Format::Fixed)),
+          Err(e) => e,
+        };
+        match Self::parse_with(input, Layout::Free, &Sequential) {
+          Ok(parsed) => Ok((parsed, Format::Free)),
+          Err(free) if fixed.line > free.line => Err(fixed),
+          Err(free) => Err(free),
+        }
+      }
+      Format::Fixed => {
+        Ok((Self::parse_with(input, Layout::Fixed, &Sequential)?, format))
+      }
+      Format::Free => {
+        Ok((Self::parse_with(input, Layout::Free, &Sequential)?, format))
+      }
+    }
+  }
+
+  pub(crate) fn parse_with(
+    input: &'a str,
+    layout: Layout,
+    sections: &impl Sections<'a, T>,
+  ) -> Result<Parser<'a, T>, Error> {
+    let source = input;
     cfg_if::cfg_if! {
         if #[cfg(feature = "trace")] {
             let info = TracableInfo::new().forward(false).backward(false);
             let input = LocatedSpan::new_extra(input, info);
         }
     }
-    let (_, parsed) = Parser::<T>::mps_file(input).map_err(|e| {
-      // Extract context around the error location instead of showing entire file
-      let error_msg = match e {
+    let (_, parsed) = Parser::<T>::mps_file_with(input, layout, sections)
+      .map_err(|e| match e {
         nom::Err::Error(err) | nom::Err::Failure(err) => {
           cfg_if::cfg_if! {
             if #[cfg(feature = "trace")] {
-              let remaining = err.input.fragment();
+              let offset = err.input.location_offset();
             } else {
-              let remaining = err.input;
+              let offset = source.len() - err.input.len();
             }
           }
-          // Show only first 200 characters of the remaining input where parsing failed
-          let preview_len = std::cmp::min(200, remaining.len());
-          let preview = &remaining[..preview_len];
-          let error_context = if remaining.len() > 200 {
-            format!("{}...", preview)
-          } else {
-            preview.to_string()
-          };
-          format!("Parse error near: {}", error_context)
+          Error::from_parse_failure(source, offset, err.code)
         }
-        nom::Err::Incomplete(_) => "Incomplete input".to_string(),
-      };
-      nom::error::Error::new(error_msg, nom::error::ErrorKind::Fail)
-    })?;
+        nom::Err::Incomplete(_) => Error::from_parse_failure(
+          source,
+          source.len(),
+          nom::error::ErrorKind::Eof,
+        ),
+      })?;
     Ok(parsed)
   }
 
@@ -153,6 +365,14 @@ impl<'a, T: FastFloat> Parser<'a, T> {
   /// 19. ENDATA - End of data (required)
   #[tracable_parser]
   pub fn mps_file(s: Span<'a>) -> IResult<Span<'a>, Parser<'a, T>> {
+    Self::mps_file_with(s, Layout::PerLine, &Sequential)
+  }
+
+  pub(crate) fn mps_file_with(
+    s: Span<'a>,
+    layout: Layout,
+    sections: &impl Sections<'a, T>,
+  ) -> IResult<Span<'a>, Parser<'a, T>> {
     // 1. NAME section
     let (s, _) = many0(Self::skip_line)(s)?;
     let (s, name) = Self::name(s)?;
@@ -171,7 +391,7 @@ impl<'a, T: FastFloat> Parser<'a, T> {
     let (s, _) = many0(Self::skip_line)(s)?;
 
     // 5. ROWS section
-    let (s, rows) = Self::rows(s)?;
+    let (s, rows) = Self::rows_in(s, layout)?;
     let (s, _) = many0(Self::skip_line)(s)?;
 
     // 6. USERCUTS section (optional)
@@ -179,19 +399,19 @@ impl<'a, T: FastFloat> Parser<'a, T> {
     let (s, _) = many0(Self::skip_line)(s)?;
 
     // 7. COLUMNS section
-    let (s, columns) = Self::columns(s)?;
+    let (s, (columns, integer_columns)) = sections.columns(s, layout)?;
     let (s, _) = many0(Self::skip_line)(s)?;
 
     // 8. RHS section (optional)
-    let (s, rhs) = opt(Self::rhs)(s)?;
+    let (s, rhs) = opt(|s| sections.rhs(s, layout))(s)?;
     let (s, _) = many0(Self::skip_line)(s)?;
 
     // 9. RANGES section (optional)
-    let (s, ranges) = opt(Self::ranges)(s)?;
+    let (s, ranges) = opt(|s| sections.ranges(s, layout))(s)?;
     let (s, _) = many0(Self::skip_line)(s)?;
 
     // 10. BOUNDS section (optional)
-    let (s, bounds) = opt(Self::bounds)(s)?;
+    let (s, bounds) = opt(|s| sections.bounds(s, layout))(s)?;
     let (s, _) = many0(Self::skip_line)(s)?;
 
     // 11. SOS section (optional) - MUST come after BOUNDS per CPLEX spec
@@ -230,6 +450,7 @@ impl<'a, T: FastFloat> Parser<'a, T> {
     let (s, _) = Self::endata(s)?;
 
     // Combine QSECTION/QUADOBJ with QMATRIX and QCMATRIX sections
+    let full_quadratic_objective = qsection.is_none() && qmatrix.is_some();
     let mut quad_obj = qsection;
     if quad_obj.is_none() {
       quad_obj = qmatrix.map(|qm| {
@@ -258,12 +479,14 @@ impl<'a, T: FastFloat> Parser<'a, T> {
       reference_row,
       rows,
       columns,
+      integer_columns,
       rhs,
       ranges,
       bounds,
       user_cuts,
       special_ordered_sets,
       quadratic_objective: quad_obj,
+      full_quadratic_objective,
       quadratic_constraints: if quad_constr.is_empty() {
         None
       } else {
@@ -333,11 +556,18 @@ impl<'a, T: FastFloat> Parser<'a, T> {
   #[doc(hidden)]
   pub fn objsen(s: Span) -> IResult<Span, ObjectiveSense> {
     let (s, _) = tag("OBJSENSE")(s)?;
-    let (s, _) = space0(s)?;
-    let (s, _) = line_ending_flexible(s)?;
-    let (s, _) = space0(s)?;
-
-    let (s, sense_str) = alt((tag("MAX"), tag("MIN")))(s)?;
+    // The sense may follow on the header line, as in `OBJSENSE MAX`
+    let (s, sense_str) =
+      match preceded(space1, alt((tag("MAX"), tag("MIN"))))(s) {
+        Ok(sense) => sense,
+        Err(nom::Err::Error(_)) => {
+          let (s, _) = space0(s)?;
+          let (s, _) = line_ending_flexible(s)?;
+          let (s, _) = space0(s)?;
+          alt((tag("MAX"), tag("MIN")))(s)?
+        }
+        Err(e) => return Err(e),
+      };
     let (s, _) = space0(s)?;
     let (s, _) = line_ending_flexible(s)?;
 
@@ -403,6 +633,14 @@ impl<'a, T: FastFloat> Parser<'a, T> {
   #[doc(hidden)]
   #[tracable_parser]
   pub fn row_line_or_end(s: Span) -> IResult<Span, Option<RowLine>> {
+    Self::row_line_or_end_in(s, Layout::PerLine)
+  }
+
+  /// Like `row_line_or_end`, reading the line in `layout`.
+  pub(crate) fn row_line_or_end_in(
+    s: Span,
+    layout: Layout,
+  ) -> IResult<Span, Option<RowLine>> {
     // Try to skip comment or empty lines first
     if let Ok((s, _)) = alt((Self::comment_line, Self::empty_line))(s) {
       return Ok((s, None));
@@ -439,6 +677,11 @@ impl<'a, T: FastFloat> Parser<'a, T> {
     let mut p = map_res(
       terminated(not_line_ending, line_ending_flexible),
       |line: Span| -> Result<RowLine> {
+        match layout {
+          Layout::Fixed => return parse_row_fixed(&fragment(line)[1..]),
+          Layout::Free => return parse_row_free(fragment(line)),
+          Layout::PerLine => (),
+        }
         cfg_if::cfg_if! {
           if #[cfg(feature = "trace")] {
             let line_str = line.fragment();
@@ -472,33 +715,45 @@ impl<'a, T: FastFloat> Parser<'a, T> {
   #[doc(hidden)]
   #[tracable_parser]
   pub fn rows(s: Span) -> IResult<Span, Vec<RowLine>> {
+    Self::rows_in(s, Layout::PerLine)
+  }
+
+  /// Like `rows`, reading the lines in `layout`.
+  pub(crate) fn rows_in(
+    s: Span,
+    layout: Layout,
+  ) -> IResult<Span, Vec<RowLine>> {
     // Parse ROWS header with optional trailing spaces
     let (s, _) = tag("ROWS")(s)?;
     let (s, _) = space0(s)?; // Skip optional trailing spaces
     let (s, _) = line_ending_flexible(s)?;
 
     // Now parse the row lines
-    let mut p = map(
-      many0(Self::row_line_or_end),
+    map(
+      many0(|s| Self::row_line_or_end_in(s, layout)),
       |rows: Vec<Option<RowLine>>| {
         // Filter out None values (comment/empty lines)
         rows.into_iter().flatten().collect()
       },
-    );
-    cfg_if::cfg_if! {
-      if #[cfg(feature = "trace")] {
-        let (s, x) = p(s)?;
-        Ok((s, x))
-      } else { p(s) }
-    }
+    )(s)
   }
 
   #[doc(hidden)]
   #[tracable_parser]
   pub fn line(s: Span) -> IResult<Span, WideLine<T>> {
+    Self::line_in(s, Layout::PerLine)
+  }
+
+  /// Like `line`, reading the line in `layout`.
+  pub(crate) fn line_in(s: Span, layout: Layout) -> IResult<Span, WideLine<T>> {
     let mut p = map_res(
       terminated(preceded(tag(" "), not_line_ending), line_ending_flexible),
       |line: Span| -> Result<WideLine<T>> {
+        match layout {
+          Layout::Fixed => return Self::parse_fixed_line(fragment(line)),
+          Layout::Free => return Self::parse_free_line(fragment(line)),
+          Layout::PerLine => (),
+        }
         cfg_if::cfg_if! {
           if #[cfg(feature = "trace")] {
             let line_str = line.fragment();
@@ -546,12 +801,65 @@ impl<'a, T: FastFloat> Parser<'a, T> {
         }
       },
     );
-    cfg_if::cfg_if! {
-      if #[cfg(feature = "trace")] {
-        let (s, x) = p(s)?;
-        Ok((s, x))
-      } else { p(s) }
-    }
+    p(s)
+  }
+
+  /// Parses a fixed-format COLUMNS, RHS or RANGES line without its leading
+  /// blank.
+  fn parse_fixed_line(line: &str) -> Result<WideLine<'_, T>> {
+    let [first, name, row_name, value, second_row_name, second_value] =
+      fixed_fields(line)?;
+    if !first.is_empty() || row_name.is_empty() {
+      return Err(eyre!("malformed fixed-format line"));
+    }
+    let second_pair = if second_row_name.is_empty() {
+      if !second_value.is_empty() {
+        return Err(eyre!("value without a row name"));
+      }
+      None
+    } else {
+      Some(RowValuePair {
+        row_name: second_row_name,
+        value: fast_float2::parse(second_value)?,
+      })
+    };
+    Ok(WideLine {
+      name,
+      first_pair: RowValuePair {
+        row_name,
+        value: fast_float2::parse(value)?,
+      },
+      second_pair,
+    })
+  }
+
+  /// Parses a free-format COLUMNS, RHS or RANGES line. As in the CPLEX and
+  /// Gurobi free format, a line of two or four fields has no name, e.g. an
+  /// RHS line ` COST -12.5` without its set name.
+  fn parse_free_line(line: &str) -> Result<WideLine<'_, T>> {
+    let fields = strip_inline_comment(line)
+      .split_whitespace()
+      .collect::<Vec<_>>();
+    let (name, pairs) = match fields.len() {
+      3 | 5 => (fields[0], &fields[1..]),
+      2 | 4 => ("", &fields[..]),
+      n => return Err(eyre!("expected 2 to 5 fields, found {}", n)),
+    };
+    let pair = |i: usize| -> Result<RowValuePair<'_, T>> {
+      Ok(RowValuePair {
+        row_name: pairs[i],
+        value: fast_float2::parse(pairs[i + 1])?,
+      })
+    };
+    Ok(WideLine {
+      name,
+      first_pair: pair(0)?,
+      second_pair: if pairs.len() == 4 {
+        Some(pair(2)?)
+      } else {
+        None
+      },
+    })
   }
 
   /// Parse a line using flexible whitespace-separated format
@@ -564,16 +872,7 @@ impl<'a, T: FastFloat> Parser<'a, T> {
       }
     }
 
-    // For flexible parsing, only strip comments if $ appears after significant whitespace
-    // This helps distinguish inline comments from $ in variable names
-    // Look for "  $" pattern (two or more spaces followed by $)
-    let line_str = if let Some(pos) = line_str.find("  $") {
-      &line_str[..pos]
-    } else if let Some(pos) = line_str.find("\t$") {
-      &line_str[..pos]
-    } else {
-      line_str
-    };
+    let line_str = strip_inline_comment(line_str);
 
     let parts: Vec<&str> = line_str.split_whitespace().collect();
 
@@ -623,6 +922,14 @@ impl<'a, T: FastFloat> Parser<'a, T> {
   #[doc(hidden)]
   #[tracable_parser]
   pub fn columns_line(s: Span) -> IResult<Span, Option<WideLine<T>>> {
+    Self::columns_line_in(s, Layout::PerLine)
+  }
+
+  /// Like `columns_line`, reading the line in `layout`.
+  pub(crate) fn columns_line_in(
+    s: Span,
+    layout: Layout,
+  ) -> IResult<Span, Option<WideLine<T>>> {
     // Try to skip comment or empty lines first
     if let Ok((s, _)) = alt((Self::comment_line, Self::empty_line))(s) {
       return Ok((s, None));
@@ -652,13 +959,23 @@ impl<'a, T: FastFloat> Parser<'a, T> {
     }
 
     // Otherwise, parse as normal data line
-    let (s, wide_line) = Self::line(s)?;
-    Ok((s, Some(wide_line)))
+    let (rest, wide_line) = Self::line_in(s, layout)?;
+    // Unlike RHS and RANGES lines, a COLUMNS line must name its column
+    if layout != Layout::PerLine && wide_line.name.is_empty() {
+      return Err(nom::Err::Error(nom::error::Error::new(
+        s,
+        nom::error::ErrorKind::MapRes,
+      )));
+    }
+    Ok((rest, Some(wide_line)))
   }
 
   /// Try to parse a marker line, returning success if it is a marker
+  ///
+  /// Yields the `MarkerType` for `'INTORG'` and `'INTEND'` markers and `None`
+  /// for any other `'MARKER'` line, which is consumed and otherwise ignored.
   #[tracable_parser]
-  pub fn try_marker_line(s: Span) -> IResult<Span, ()> {
+  pub fn try_marker_line(s: Span) -> IResult<Span, Option<MarkerType>> {
     // Peek at the line content to check if it's a marker
     let (_, line_content) = peek(preceded(tag(" "), not_line_ending))(s)?;
 
@@ -672,8 +989,16 @@ impl<'a, T: FastFloat> Parser<'a, T> {
 
     // Check if this line contains MARKER syntax
     if line_str.contains("'MARKER'") {
+      let marker = if line_str.contains("'INTORG'") {
+        Some(MarkerType::IntOrg)
+      } else if line_str.contains("'INTEND'") {
+        Some(MarkerType::IntEnd)
+      } else {
+        None
+      };
       // Consume the marker line
-      Self::marker_line(s)
+      let (s, _) = Self::marker_line(s)?;
+      Ok((s, marker))
     } else {
       // Not a marker line, fail this parser
       Err(nom::Err::Error(nom::error::Error::new(
@@ -683,32 +1008,87 @@ impl<'a, T: FastFloat> Parser<'a, T> {
     }
   }
 
+  /// Parses the COLUMNS section, tracking INTORG/INTEND marker blocks.
+  ///
+  /// Every column whose lines appear inside an integer block is collected
+  /// into the returned `IntegerColumns`. A nested `'INTORG'`, an `'INTEND'`
+  /// without a matching `'INTORG'`, or an integer block still open at the end
+  /// of the section is a parse failure.
   #[doc(hidden)]
   #[tracable_parser]
-  pub fn columns(s: Span) -> IResult<Span, Vec<WideLine<T>>> {
+  pub fn columns(s: Span) -> IResult<Span, (Vec<WideLine<T>>, IntegerColumns)> {
+    Self::columns_in(s, Layout::PerLine)
+  }
+
+  /// Like `columns`, reading the lines in `layout`.
+  pub(crate) fn columns_in(
+    s: Span,
+    layout: Layout,
+  ) -> IResult<Span, (Vec<WideLine<T>>, IntegerColumns)> {
     // Parse COLUMNS header with optional trailing spaces
     let (s, _) = tag("COLUMNS")(s)?;
     let (s, _) = space0(s)?; // Skip optional trailing spaces
-    let (s, _) = line_ending_flexible(s)?;
+    let (mut s, _) = line_ending_flexible(s)?;
+
+    let mut lines = Vec::new();
+    let mut integer_columns = IntegerColumns::new();
+    let mut integer_block = false;
+    loop {
+      match Self::try_marker_line(s) {
+        Ok((rest, marker)) => {
+          match (marker, integer_block) {
+            (Some(MarkerType::IntOrg), false) => integer_block = true,
+            (Some(MarkerType::IntEnd), true) => integer_block = false,
+            // Nested INTORG or INTEND without a matching INTORG
+            (Some(_), _) => {
+              return Err(nom::Err::Failure(nom::error::Error::new(
+                s,
+                nom::error::ErrorKind::Verify,
+              )))
+            }
+            (None, _) => (),
+          }
+          s = rest;
+          continue;
+        }
+        Err(nom::Err::Error(_)) => (),
+        Err(e) => return Err(e),
+      }
+      match Self::columns_line_in(s, layout) {
+        Ok((rest, Some(line))) => {
+          if integer_block {
+            integer_columns.insert(line.name);
+          }
+          lines.push(line);
+          s = rest;
+        }
+        Ok((rest, None)) => s = rest,
+        Err(nom::Err::Error(_)) => break,
+        Err(e) => return Err(e),
+      }
+    }
 
-    let mut p = map(
-      many0(Self::columns_line),
-      |lines: Vec<Option<WideLine<T>>>| {
-        // Filter out None values (marker lines)
-        lines.into_iter().flatten().collect()
-      },
-    );
-    cfg_if::cfg_if! {
-      if #[cfg(feature = "trace")] {
-        let (s, x) = p(s)?;
-        Ok((s, x))
-      } else { p(s) }
+    // An integer block must be closed before the section ends
+    if integer_block {
+      return Err(nom::Err::Failure(nom::error::Error::new(
+        s,
+        nom::error::ErrorKind::Verify,
+      )));
     }
+    Ok((s, (lines, integer_columns)))
   }
 
   #[doc(hidden)]
   #[tracable_parser]
   pub fn rhs_line(s: Span) -> IResult<Span, Option<WideLine<T>>> {
+    Self::rhs_line_in(s, Layout::PerLine)
+  }
+
+  /// Like `rhs_line`, reading the line in `layout`.
+  pub(crate) fn rhs_line_in(
+    s: Span,
+    layout: Layout,
+  ) -> IResult<Span, Option<WideLine<T>>> {
     // Try to skip comment or empty lines first
     if let Ok((s, _)) = alt((Self::comment_line, Self::empty_line))(s) {
       return Ok((s, None));
@@ -732,34 +1112,46 @@ impl<'a, T: FastFloat> Parser<'a, T> {
       )));
     }
 
-    let (s, wide_line) = Self::line(s)?;
+    let (s, wide_line) = Self::line_in(s, layout)?;
     Ok((s, Some(wide_line)))
   }
 
   #[doc(hidden)]
   #[tracable_parser]
   pub fn rhs(s: Span) -> IResult<Span, Vec<WideLine<T>>> {
+    Self::rhs_in(s, Layout::PerLine)
+  }
+
+  /// Like `rhs`, reading the lines in `layout`.
+  pub(crate) fn rhs_in(
+    s: Span,
+    layout: Layout,
+  ) -> IResult<Span, Vec<WideLine<T>>> {
     // Parse RHS header with optional trailing spaces
     let (s, _) = tag("RHS")(s)?;
     let (s, _) = space0(s)?; // Skip optional trailing spaces
     let (s, _) = line_ending_flexible(s)?;
 
-    let mut p =
-      map(many0(Self::rhs_line), |lines: Vec<Option<WideLine<T>>>| {
+    map(
+      many0(|s| Self::rhs_line_in(s, layout)),
+      |lines: Vec<Option<WideLine<T>>>| {
         // Filter out None values (comment/empty lines)
         lines.into_iter().flatten().collect()
-      });
-    cfg_if::cfg_if! {
-      if #[cfg(feature = "trace")] {
-        let (s, x) = p(s)?;
-        Ok((s, x))
-      } else { p(s) }
-    }
+      },
+    )(s)
   }
 
   #[doc(hidden)]
   #[tracable_parser]
   pub fn ranges_line(s: Span) -> IResult<Span, Option<WideLine<T>>> {
+    Self::ranges_line_in(s, Layout::PerLine)
+  }
+
+  /// Like `ranges_line`, reading the line in `layout`.
+  pub(crate) fn ranges_line_in(
+    s: Span,
+    layout: Layout,
+  ) -> IResult<Span, Option<WideLine<T>>> {
     // Try to skip comment or empty lines first
     if let Ok((s, _)) = alt((Self::comment_line, Self::empty_line))(s) {
       return Ok((s, None));
@@ -783,31 +1175,33 @@ impl<'a, T: FastFloat> Parser<'a, T> {
       )));
     }
 
-    let (s, wide_line) = Self::line(s)?;
+    let (s, wide_line) = Self::line_in(s, layout)?;
     Ok((s, Some(wide_line)))
   }
 
   #[doc(hidden)]
   #[tracable_parser]
   pub fn ranges(s: Span) -> IResult<Span, Vec<WideLine<T>>> {
+    Self::ranges_in(s, Layout::PerLine)
+  }
+
+  /// Like `ranges`, reading the lines in `layout`.
+  pub(crate) fn ranges_in(
+    s: Span,
+    layout: Layout,
+  ) -> IResult<Span, Vec<WideLine<T>>> {
     // Parse RANGES header with optional trailing spaces
     let (s, _) = tag("RANGES")(s)?;
     let (s, _) = space0(s)?; // Skip optional trailing spaces
     let (s, _) = line_ending_flexible(s)?;
 
-    let mut p = map(
-      many0(Self::ranges_line),
+    map(
+      many0(|s| Self::ranges_line_in(s, layout)),
       |lines: Vec<Option<WideLine<T>>>| {
         // Filter out None values (comment/empty lines)
         lines.into_iter().flatten().collect()
       },
-    );
-    cfg_if::cfg_if! {
-      if #[cfg(feature = "trace")] {
-        let (s, x) = p(s)?;
-        Ok((s, x))
-      } else { p(s) }
-    }
+    )(s)
   }
 
   #[doc(hidden)]
@@ -846,6 +1240,14 @@ impl<'a, T: FastFloat> Parser<'a, T> {
   #[doc(hidden)]
   #[tracable_parser]
   pub fn bounds_line(s: Span) -> IResult<Span, Option<BoundsLine<T>>> {
+    Self::bounds_line_in(s, Layout::PerLine)
+  }
+
+  /// Like `bounds_line`, reading the line in `layout`.
+  pub(crate) fn bounds_line_in(
+    s: Span,
+    layout: Layout,
+  ) -> IResult<Span, Option<BoundsLine<T>>> {
     // Try to skip comment or empty lines first
     if let Ok((s, _)) = alt((Self::comment_line, Self::empty_line))(s) {
       return Ok((s, None));
@@ -872,6 +1274,11 @@ impl<'a, T: FastFloat> Parser<'a, T> {
     let mut p = map_res(
       terminated(preceded(tag(" "), not_line_ending), line_ending_flexible),
       |line: Span| -> Result<BoundsLine<T>> {
+        match layout {
+          Layout::Fixed => return Self::parse_bounds_fixed(fragment(line)),
+          Layout::Free => return Self::parse_bounds_free(fragment(line)),
+          Layout::PerLine => (),
+        }
         // Try strict field positioning first (no comment stripping for strict)
         let strict_result = Self::parse_bounds_strict(line);
 
@@ -888,6 +1295,63 @@ impl<'a, T: FastFloat> Parser<'a, T> {
     Ok((s, Some(bounds_line)))
   }
 
+  /// Parses a fixed-format BOUNDS line without its leading blank.
+  fn parse_bounds_fixed(line: &str) -> Result<BoundsLine<'_, T>> {
+    let [bound_type, bound_name, column_name, value, rest @ ..] =
+      fixed_fields(line)?;
+    if column_name.is_empty() || rest.iter().any(|field| !field.is_empty()) {
+      return Err(eyre!("malformed fixed-format bounds line"));
+    }
+    let bound_type = BoundType::try_from(bound_type)?;
+    let value = match bound_type {
+      BoundType::Fr | BoundType::Pl => None,
+      _ if value.is_empty() => None,
+      _ => Some(fast_float2::parse(value)?),
+    };
+    Ok(BoundsLine {
+      bound_type,
+      bound_name,
+      column_name,
+      value,
+    })
+  }
+
+  /// Parses a free-format BOUNDS line. As in the CPLEX and Gurobi free
+  /// format, the bound set name may be left out, e.g. ` UP X1 4`.
+  fn parse_bounds_free(line: &str) -> Result<BoundsLine<'_, T>> {
+    let fields = strip_inline_comment(line)
+      .split_whitespace()
+      .collect::<Vec<_>>();
+    let (&bound_type, rest) =
+      fields.split_first().ok_or_eyre("empty bounds line")?;
+    let bound_type = BoundType::try_from(bound_type)?;
+    let takes_value =
+      !matches!(bound_type, BoundType::Fr | BoundType::Mi | BoundType::Pl);
+    let (bound_name, column_name, value) = match *rest {
+      [column_name] => ("", column_name, None),
+      [column_name, value]
+        if takes_value && fast_float2::parse::<T, _>(value).is_ok() =>
+      {
+        ("", column_name, Some(value))
+      }
+      [bound_name, column_name] => (bound_name, column_name, None),
+      [bound_name, column_name, value] => {
+        (bound_name, column_name, Some(value))
+      }
+      _ => return Err(eyre!("expected 2 to 4 fields, found {}", fields.len())),
+    };
+    let value = match bound_type {
+      BoundType::Fr | BoundType::Pl => None,
+      _ => value.map(fast_float2::parse).transpose()?,
+    };
+    Ok(BoundsLine {
+      bound_type,
+      bound_name,
+      column_name,
+      value,
+    })
+  }
+
   /// Parse bounds line using strict field positioning
   fn parse_bounds_strict(line: Span) -> Result<BoundsLine<T>> {
     let length = line.len();
@@ -920,14 +1384,7 @@ impl<'a, T: FastFloat> Parser<'a, T> {
       }
     }
 
-    // For flexible parsing, only strip comments if $ appears after significant whitespace
-    let line_str = if let Some(pos) = line_str.find("  $") {
-      &line_str[..pos]
-    } else if let Some(pos) = line_str.find("\t$") {
-      &line_str[..pos]
-    } else {
-      line_str
-    };
+    let line_str = strip_inline_comment(line_str);
 
     let parts: Vec<&str> = line_str.split_whitespace().collect();
 
@@ -966,24 +1423,26 @@ impl<'a, T: FastFloat> Parser<'a, T> {
   #[doc(hidden)]
   #[tracable_parser]
   pub fn bounds(s: Span) -> IResult<Span, Vec<BoundsLine<T>>> {
+    Self::bounds_in(s, Layout::PerLine)
+  }
+
+  /// Like `bounds`, reading the lines in `layout`.
+  pub(crate) fn bounds_in(
+    s: Span,
+    layout: Layout,
+  ) -> IResult<Span, Vec<BoundsLine<T>>> {
     // Parse BOUNDS header with optional trailing spaces
     let (s, _) = tag("BOUNDS")(s)?;
     let (s, _) = space0(s)?; // Skip optional trailing spaces
     let (s, _) = line_ending_flexible(s)?;
 
-    let mut p = map(
-      many0(Self::bounds_line),
+    map(
+      many0(|s| Self::bounds_line_in(s, layout)),
       |lines: Vec<Option<BoundsLine<T>>>| {
         // Filter out None values (comment/empty lines)
         lines.into_iter().flatten().collect()
       },
-    );
-    cfg_if::cfg_if! {
-      if #[cfg(feature = "trace")] {
-        let (s, x) = p(s)?;
-        Ok((s, x))
-      } else { p(s) }
-    }
+    )(s)
   }
 
   // ============================================================================
@@ -1795,3 +2254,37 @@ impl<'a, T: FastFloat> Parser<'a, T> {
     }
   }
 }
+
+#[cfg(feature = "rayon")]
+impl<'a, T: FastFloat + Send> Parser<'a, T> {
+  /// Parses like `Parser::parse`, but spreads the COLUMNS, RHS, RANGES and
+  /// BOUNDS sections over the rayon thread pool.
+  ///
+  /// The result, and any error, is the same as that of `Parser::parse`. Only
+  /// sections of more than about 64 KiB are split, so this pays off for files
+  /// of several megabytes. With the `trace` feature, this is `Parser::parse`.
+  ///
+  /// # Errors
+  ///
+  /// Returns the error of `Parser::parse`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use mps::Parser;
+  ///
+  /// let input = std::fs::read_to_string("tests/data/netlib/afiro")?;
+  /// assert_eq!(Parser::<f64>::parse_parallel(&input)?, Parser::parse(&input)?);
+  /// # Ok::<(), color_eyre::Report>(())
+  /// ```
+  pub fn parse_parallel(input: &'a str) -> Result<Parser<'a, T>, Error> {
+    cfg_if::cfg_if! {
+      if #[cfg(feature = "trace")] {
+        Self::parse(input)
+      } else {
+        use crate::parallel::{Parallel, MIN_CHUNK};
+        Self::parse_with(input, Layout::PerLine, &Parallel { min_chunk: MIN_CHUNK })
+      }
+    }
+  }
+}
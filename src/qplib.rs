@@ -0,0 +1,730 @@
+//! Reading the QPLIB format.
+//!
+//! `Model::from_qplib` reads a `.qplib` file, the format of the QPLIB library
+//! of quadratic programs, into a `Model`. A QPLIB problem is
+//!
+//! ```text
+//! minimize or maximize  0.5 x'Q0x + b0'x + q0
+//! subject to            cl <= 0.5 x'Qix + bi'x <= cu  for every constraint i
+//!                       xl <= x <= xu
+//! ```
+//!
+//! As for `Model::from_lp`, the file is translated into the entries of the
+//! equivalent MPS file, which are checked as `Model::try_from` checks them:
+//!
+//! | QPLIB              | MPS                                    |
+//! |--------------------|----------------------------------------|
+//! | objective          | `N` row `obj`; `q0`, negated, in RHS   |
+//! | constraint         | row, as LP reads `cl <= ... <= cu`     |
+//! | `xl` and `xu`      | `LO`, `UP`, `FX`, `FR` and `MI` bounds |
+//! | integer variable   | column inside INTORG/INTEND markers    |
+//! | binary variable    | `BV` bound                             |
+//! | `Q0`, one triangle | QUADOBJ, as is                         |
+//! | `Qi`, one triangle | QCMATRIX, halved into the full matrix  |
+//!
+//! QUADOBJ lists one triangle of the `Q` of `0.5 * x'Qx`, as QPLIB does,
+//! while QCMATRIX lists the full matrix of `x'Qx`: an entry `q` of `Qi`
+//! becomes `q / 2` at `(x, x)`, or at both `(x, y)` and `(y, x)`.
+//! `Model::quadratic_constraint_forms` gives `Qi` back.
+//!
+//! Variables are named `x1`, `x2`, ... and constraints `c1`, `c2`, ...
+//! unless the file names them. Values at or beyond the infinity the file
+//! gives are infinite. Starting points are read but not kept.
+use crate::error::{Diagnostics, Error, ErrorKind};
+use crate::lp::{column_bounds, row};
+use crate::model::builder::Builder;
+use crate::model::{Model, ModelOptions};
+use crate::stream::Event;
+use crate::types::{ObjectiveSense, RowType, Section};
+use color_eyre::Result;
+use fast_float2::FastFloat;
+use indexmap::IndexMap;
+use std::iter::Enumerate;
+use std::str::{FromStr, Lines};
+
+/// A whitespace-separated field of a record.
+#[derive(Debug, Clone, Copy)]
+struct Field<'a> {
+  text: &'a str,
+  line: usize,
+  column: usize,
+}
+
+impl Field<'_> {
+  fn error(&self, kind: ErrorKind, message: impl Into<String>) -> Error {
+    Error::new(kind, message)
+      .with_token(self.text)
+      .at(self.line, self.column)
+  }
+
+  fn number<T: FastFloat>(&self, what: &str) -> Result<T, Error> {
+    fast_float2::parse(self.text).map_err(|_| {
+      self.error(
+        ErrorKind::BadNumber,
+        format!("invalid {}: {:?}", what, self.text),
+      )
+    })
+  }
+
+  fn integer<V: FromStr>(&self, what: &str) -> Result<V, Error> {
+    self.text.parse().map_err(|_| {
+      self.error(
+        ErrorKind::BadNumber,
+        format!("invalid {}: {:?}", what, self.text),
+      )
+    })
+  }
+
+  /// Reads a 1-based index into a 0-based one below `n`.
+  fn index(&self, n: usize, kind: ErrorKind) -> Result<usize, Error> {
+    let what = match kind {
+      ErrorKind::UnknownRow => "constraint",
+      _ => "variable",
+    };
+    match self.integer::<usize>(&format!("{} index", what))? {
+      i @ 1.. if i <= n => Ok(i - 1),
+      i => Err(self.error(
+        kind,
+        format!("{} index {} is not between 1 and {}", what, i, n),
+      )),
+    }
+  }
+}
+
+/// The records of a QPLIB file: one per line, with anything after the
+/// fields of the record taken as a comment.
+struct Records<'a> {
+  lines: Enumerate<Lines<'a>>,
+  /// Where the input ends, for errors at the end of the input
+  end: (usize, usize),
+}
+
+impl<'a> Records<'a> {
+  fn new(input: &'a str) -> Self {
+    let last = input.lines().last().unwrap_or_default();
+    Records {
+      lines: input.lines().enumerate(),
+      end: (input.lines().count().max(1), last.len() + 1),
+    }
+  }
+
+  /// The next non-blank line, with its line number.
+  fn line(&mut self, what: &str) -> Result<(usize, &'a str), Error> {
+    for (i, line) in self.lines.by_ref() {
+      if !line.trim().is_empty() {
+        return Ok((i + 1, line));
+      }
+    }
+    Err(
+      Error::new(
+        ErrorKind::MissingValue,
+        format!("unexpected end of input: missing {}", what),
+      )
+      .at(self.end.0, self.end.1),
+    )
+  }
+
+  /// The first `n` fields of the next record.
+  fn record(&mut self, what: &str, n: usize) -> Result<Vec<Field<'a>>, Error> {
+    let (line_number, line) = self.line(what)?;
+    let fields = line
+      .split_whitespace()
+      .take(n)
+      .map(|text| Field {
+        text,
+        line: line_number,
+        column: text.as_ptr() as usize - line.as_ptr() as usize + 1,
+      })
+      .collect::<Vec<_>>();
+    if fields.len() < n {
+      let column = line.trim_end().len() + 1;
+      return Err(
+        Error::new(
+          ErrorKind::MissingValue,
+          format!("missing {}: expected {} fields", what, n),
+        )
+        .at(line_number, column),
+      );
+    }
+    Ok(fields)
+  }
+
+  fn number<T: FastFloat>(&mut self, what: &str) -> Result<T, Error> {
+    self.record(what, 1)?[0].number(what)
+  }
+
+  fn count(&mut self, what: &str) -> Result<usize, Error> {
+    self.record(what, 1)?[0].integer(what)
+  }
+
+  /// Reads a count followed by that many records of `n` fields.
+  fn entries(
+    &mut self,
+    what: &str,
+    n: usize,
+  ) -> Result<Vec<Vec<Field<'a>>>, Error> {
+    let count = self.count(&format!("number of {}", what))?;
+    (0..count).map(|_| self.record(what, n)).collect()
+  }
+
+  /// Reads a vector of length `n` given as a default value followed by the
+  /// entries that differ from it.
+  fn vector<T: FastFloat>(
+    &mut self,
+    what: &str,
+    n: usize,
+    kind: ErrorKind,
+  ) -> Result<Vec<T>, Error> {
+    let default = self.number(&format!("default {}", what))?;
+    let mut vector = vec![default; n];
+    for entry in self.entries(what, 2)? {
+      vector[entry[0].index(n, kind)?] = entry[1].number(what)?;
+    }
+    Ok(vector)
+  }
+
+  /// Reads the names that differ from `prefix1`, `prefix2`, ...
+  fn names(
+    &mut self,
+    what: &str,
+    prefix: &str,
+    n: usize,
+    kind: ErrorKind,
+  ) -> Result<Vec<String>, Error> {
+    let mut names: Vec<_> =
+      (1..=n).map(|i| format!("{}{}", prefix, i)).collect();
+    for entry in self.entries(what, 2)? {
+      names[entry[0].index(n, kind)?] = entry[1].text.to_string();
+    }
+    Ok(names)
+  }
+}
+
+/// The variable type codes of a QPLIB file.
+const CONTINUOUS: u8 = 0;
+const INTEGER: u8 = 1;
+const BINARY: u8 = 2;
+
+/// The contents of a QPLIB file, with 0-based indices.
+struct Qplib<T> {
+  name: String,
+  objective_sense: ObjectiveSense,
+  objective_quadratic: Vec<(usize, usize, T)>,
+  objective_linear: Vec<T>,
+  objective_constant: T,
+  /// `(constraint, variable, variable, value)`
+  constraint_quadratic: Vec<(usize, usize, usize, T)>,
+  /// `(constraint, variable, value)`
+  constraint_linear: Vec<(usize, usize, T)>,
+  constraint_lower: Vec<T>,
+  constraint_upper: Vec<T>,
+  variable_lower: Vec<T>,
+  variable_upper: Vec<T>,
+  variable_types: Vec<u8>,
+  variable_names: Vec<String>,
+  constraint_names: Vec<String>,
+}
+
+impl<T: FastFloat> Qplib<T> {
+  fn read(input: &str) -> Result<Self, Error> {
+    let mut r = Records::new(input);
+    let name = r.line("problem name")?.1.trim().to_string();
+    let code = r.record("problem type", 1)?[0];
+    let (objective_type, variable_type, constraint_type) =
+      match code.text.as_bytes() {
+        &[o @ (b'L' | b'D' | b'C' | b'Q'), v @ (b'C' | b'B' | b'M' | b'I' | b'G'), c @ (b'N' | b'B' | b'L' | b'C' | b'Q')] => {
+          (o, v, c)
+        }
+        _ => {
+          return Err(
+            code
+              .error(
+                ErrorKind::Syntax,
+                format!("invalid problem type {:?}", code.text),
+              )
+              .with_hint(
+                "the type is three letters: the objective (LDCQ), the variables (CBMIG) and the constraints (NBLCQ)",
+              ),
+          )
+        }
+      };
+    let sense = r.record("objective sense", 1)?[0];
+    let objective_sense = match sense.text.to_ascii_lowercase().as_str() {
+      "minimize" => ObjectiveSense::Min,
+      "maximize" => ObjectiveSense::Max,
+      _ => {
+        return Err(sense.error(
+          ErrorKind::Syntax,
+          format!("expected minimize or maximize: found {:?}", sense.text),
+        ))
+      }
+    };
+    let n = r.count("number of variables")?;
+    let m = match constraint_type {
+      b'N' | b'B' => 0,
+      _ => r.count("number of constraints")?,
+    };
+    let mut objective_quadratic = Vec::new();
+    if objective_type != b'L' {
+      for entry in r.entries("objective quadratic entries", 3)? {
+        objective_quadratic.push((
+          entry[0].index(n, ErrorKind::UnknownColumn)?,
+          entry[1].index(n, ErrorKind::UnknownColumn)?,
+          entry[2].number("objective quadratic entry")?,
+        ));
+      }
+    }
+    let objective_linear =
+      r.vector("objective linear entry", n, ErrorKind::UnknownColumn)?;
+    let objective_constant = r.number("objective constant")?;
+    let mut constraint_quadratic = Vec::new();
+    if matches!(constraint_type, b'C' | b'Q') {
+      for entry in r.entries("constraint quadratic entries", 4)? {
+        constraint_quadratic.push((
+          entry[0].index(m, ErrorKind::UnknownRow)?,
+          entry[1].index(n, ErrorKind::UnknownColumn)?,
+          entry[2].index(n, ErrorKind::UnknownColumn)?,
+          entry[3].number("constraint quadratic entry")?,
+        ));
+      }
+    }
+    let mut constraint_linear = Vec::new();
+    if m > 0 {
+      for entry in r.entries("constraint linear entries", 3)? {
+        constraint_linear.push((
+          entry[0].index(m, ErrorKind::UnknownRow)?,
+          entry[1].index(n, ErrorKind::UnknownColumn)?,
+          entry[2].number("constraint linear entry")?,
+        ));
+      }
+    }
+    let infinity = r.number::<T>("infinity")?;
+    let infinite = |values: Vec<T>| {
+      values
+        .into_iter()
+        .map(|value| match value {
+          v if v >= infinity => T::INFINITY,
+          v if v <= -infinity => T::NEG_INFINITY,
+          v => v,
+        })
+        .collect::<Vec<_>>()
+    };
+    let (mut constraint_lower, mut constraint_upper) = (Vec::new(), Vec::new());
+    if m > 0 {
+      let lower =
+        r.vector("constraint lower bound", m, ErrorKind::UnknownRow)?;
+      let upper =
+        r.vector("constraint upper bound", m, ErrorKind::UnknownRow)?;
+      (constraint_lower, constraint_upper) = (infinite(lower), infinite(upper));
+    }
+    let (mut variable_lower, mut variable_upper) =
+      (vec![T::default(); n], vec![T::from_u64(1); n]);
+    if variable_type != b'B' {
+      let lower =
+        r.vector("variable lower bound", n, ErrorKind::UnknownColumn)?;
+      let upper =
+        r.vector("variable upper bound", n, ErrorKind::UnknownColumn)?;
+      (variable_lower, variable_upper) = (infinite(lower), infinite(upper));
+    }
+    let variable_types = match variable_type {
+      b'C' => vec![CONTINUOUS; n],
+      b'B' => vec![BINARY; n],
+      b'I' => vec![INTEGER; n],
+      _ => {
+        let field = r.record("default variable type", 1)?[0];
+        let mut types = vec![variable_code(&field)?; n];
+        for entry in r.entries("variable types", 2)? {
+          types[entry[0].index(n, ErrorKind::UnknownColumn)?] =
+            variable_code(&entry[1])?;
+        }
+        types
+      }
+    };
+    r.vector::<T>("primal starting point", n, ErrorKind::UnknownColumn)?;
+    if m > 0 {
+      r.vector::<T>(
+        "constraint dual starting point",
+        m,
+        ErrorKind::UnknownRow,
+      )?;
+    }
+    r.vector::<T>("bound dual starting point", n, ErrorKind::UnknownColumn)?;
+    let variable_names =
+      r.names("variable names", "x", n, ErrorKind::UnknownColumn)?;
+    let constraint_names =
+      r.names("constraint names", "c", m, ErrorKind::UnknownRow)?;
+    Ok(Qplib {
+      name,
+      objective_sense,
+      objective_quadratic,
+      objective_linear,
+      objective_constant,
+      constraint_quadratic,
+      constraint_linear,
+      constraint_lower,
+      constraint_upper,
+      variable_lower,
+      variable_upper,
+      variable_types,
+      variable_names,
+      constraint_names,
+    })
+  }
+
+  /// The entries of the equivalent MPS file, in MPS order.
+  fn events(self) -> Vec<Event<T>> {
+    let objective_name = "obj".to_string();
+    let mut events = vec![
+      Event::Name(self.name),
+      Event::ObjectiveSense(self.objective_sense),
+      Event::Section(Section::Rows),
+      Event::Row {
+        row_type: RowType::Nr,
+        row_name: objective_name.clone(),
+      },
+    ];
+    let mut rhs = Vec::new();
+    let mut ranges = Vec::new();
+    if self.objective_constant != T::default() {
+      rhs.push((objective_name.clone(), -self.objective_constant));
+    }
+    let rows = self.constraint_lower.iter().zip(&self.constraint_upper);
+    for (row_name, (&lower, &upper)) in self.constraint_names.iter().zip(rows) {
+      let (row_type, value, range) = row(lower, upper);
+      if value != T::default() {
+        rhs.push((row_name.clone(), value));
+      }
+      if let Some(range) = range {
+        ranges.push((row_name.clone(), range));
+      }
+      events.push(Event::Row {
+        row_type,
+        row_name: row_name.clone(),
+      });
+    }
+    events.push(Event::Section(Section::Columns));
+    let mut columns = vec![Vec::new(); self.variable_names.len()];
+    for (j, &value) in self.objective_linear.iter().enumerate() {
+      if value != T::default() {
+        columns[j].push((&objective_name, value));
+      }
+    }
+    for &(i, j, value) in &self.constraint_linear {
+      columns[j].push((&self.constraint_names[i], value));
+    }
+    for (j, mut entries) in columns.into_iter().enumerate() {
+      if entries.is_empty() {
+        entries.push((&objective_name, T::default()));
+      }
+      for (row_name, value) in entries {
+        events.push(Event::Coefficient {
+          column_name: self.variable_names[j].clone(),
+          row_name: row_name.clone(),
+          value,
+          integer: self.variable_types[j] == INTEGER,
+        });
+      }
+    }
+    if !rhs.is_empty() {
+      events.push(Event::Section(Section::Rhs));
+    }
+    for (row_name, value) in rhs {
+      events.push(Event::Rhs {
+        set_name: "RHS".to_string(),
+        row_name,
+        value,
+      });
+    }
+    if !ranges.is_empty() {
+      events.push(Event::Section(Section::Ranges));
+    }
+    for (row_name, value) in ranges {
+      events.push(Event::Range {
+        set_name: "RNG".to_string(),
+        row_name,
+        value,
+      });
+    }
+    let mut bounds = Vec::new();
+    for (j, column_name) in self.variable_names.iter().enumerate() {
+      let (lower, upper) = (self.variable_lower[j], self.variable_upper[j]);
+      let binary = self.variable_types[j] == BINARY;
+      for (bound_type, value) in
+        column_bounds(Some(lower), Some(upper), binary, false)
+      {
+        bounds.push(Event::Bound {
+          bound_type,
+          bound_name: "BND".to_string(),
+          column_name: column_name.clone(),
+          value,
+        });
+      }
+    }
+    if !bounds.is_empty() {
+      events.push(Event::Section(Section::Bounds));
+      events.extend(bounds);
+    }
+    if !self.objective_quadratic.is_empty() {
+      events.push(Event::Section(Section::QuadObj));
+    }
+    for &(j, k, coefficient) in &self.objective_quadratic {
+      events.push(Event::QuadraticObjective {
+        var1: self.variable_names[j].clone(),
+        var2: self.variable_names[k].clone(),
+        coefficient,
+      });
+    }
+    let mut constraints = IndexMap::<usize, Vec<_>>::new();
+    for &(i, j, k, value) in &self.constraint_quadratic {
+      let half = value / T::from_u64(2);
+      let terms = constraints.entry(i).or_default();
+      terms.push((j, k, half));
+      if j != k {
+        terms.push((k, j, half));
+      }
+    }
+    constraints.sort_keys();
+    for (i, terms) in constraints {
+      events.push(Event::Section(Section::QcMatrix));
+      events.push(Event::QuadraticConstraint {
+        row_name: self.constraint_names[i].clone(),
+      });
+      for (j, k, coefficient) in terms {
+        events.push(Event::QuadraticConstraintTerm {
+          var1: self.variable_names[j].clone(),
+          var2: self.variable_names[k].clone(),
+          coefficient,
+        });
+      }
+    }
+    events.push(Event::Section(Section::Endata));
+    events
+  }
+}
+
+/// Reads a variable type code: 0 for continuous, 1 for integer and 2 for
+/// binary.
+fn variable_code(field: &Field) -> Result<u8, Error> {
+  match field.integer::<u8>("variable type")? {
+    code @ (CONTINUOUS | INTEGER | BINARY) => Ok(code),
+    code => Err(
+      field
+        .error(ErrorKind::Syntax, format!("invalid variable type {}", code))
+        .with_hint("0 is continuous, 1 integer and 2 binary"),
+    ),
+  }
+}
+
+impl<T: FastFloat> Model<T> {
+  /// Reads a model in the QPLIB format; see the `qplib` module for how it
+  /// maps onto MPS.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error wrapping an `Error` with the line and column for text
+  /// that cannot be read, and the first error `Model::try_from` would find
+  /// in the equivalent MPS file otherwise.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use mps::model::Model;
+  ///
+  /// // minimize x1^2 + x1 x2 + x2^2 - x1 subject to 1 <= x1 + x2
+  /// let model = Model::<f64>::from_qplib(
+  ///   "EXAMPLE
+  /// QCL # problem type
+  /// minimize
+  /// 2 # variables
+  /// 1 # constraints
+  /// 3 # objective quadratic entries
+  /// 1 1 2
+  /// 2 1 1
+  /// 2 2 2
+  /// 0 # default objective linear entry
+  /// 1
+  /// 1 -1
+  /// 0 # objective constant
+  /// 2 # constraint linear entries
+  /// 1 1 1
+  /// 1 2 1
+  /// 1e30 # infinity
+  /// 1 # default constraint lower bound
+  /// 0
+  /// 1e30 # default constraint upper bound
+  /// 0
+  /// -1e30 # default variable lower bound
+  /// 0
+  /// 1e30 # default variable upper bound
+  /// 0
+  /// 0 # starting points
+  /// 0
+  /// 0
+  /// 0
+  /// 0
+  /// 0
+  /// 0 # names
+  /// 0",
+  /// )?;
+  /// assert_eq!(model.quadratic_objective.0.len(), 3);
+  /// assert_eq!(model.row_bounds()?.0["c1"].lower, 1.0);
+  /// # Ok::<(), color_eyre::Report>(())
+  /// ```
+  pub fn from_qplib(input: &str) -> Result<Self> {
+    let qplib = Qplib::read(input)?;
+    let mut diagnostics = Diagnostics::default();
+    let mut builder = Builder::default();
+    for event in qplib.events() {
+      builder.add(event, &mut diagnostics);
+    }
+    let model = builder.finish(ModelOptions::default(), &mut diagnostics);
+    diagnostics.first_error()?;
+    Ok(model)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Parser;
+
+  const QPLIB: &str = "QPTEST
+QMQ # minimize a quadratic over mixed variables with quadratic constraints
+minimize
+3 # variables
+2 # constraints
+2 # objective quadratic entries
+1 1 4
+2 1 2
+0 # default objective linear entry
+2
+1 2
+3 -1
+5 # objective constant
+2 # constraint quadratic entries
+2 2 1 4
+2 3 3 2
+3 # constraint linear entries
+1 1 1
+1 2 1
+2 3 1
+1e20 # infinity
+-1e20 # default constraint lower bound
+1
+1 1
+1e20 # default constraint upper bound
+2
+1 3
+2 8
+0 # default variable lower bound
+1
+2 -1e20
+1e20 # default variable upper bound
+2
+1 10
+3 1
+0 # default variable type
+2
+2 1
+3 2
+0 # default primal starting point
+0
+0 # default constraint dual starting point
+0
+0 # default bound dual starting point
+0
+0 # variable names
+1 # constraint names
+2 qc
+";
+
+  const MPS: &str = "NAME          QPTEST
+OBJSENSE
+    MIN
+ROWS
+ N  obj
+ G  c1
+ L  qc
+COLUMNS
+    x1        obj          2   c1           1
+    MARKER                 'MARKER'                 'INTORG'
+    x2        c1           1
+    MARKER                 'MARKER'                 'INTEND'
+    x3        obj         -1   qc           1
+RHS
+    RHS       obj         -5   c1           1
+    RHS       qc           8
+RANGES
+    RNG       c1           2
+BOUNDS
+ UP BND       x1          10
+ FR BND       x2
+ BV BND       x3
+QUADOBJ
+    x1        x1           4
+    x2        x1           2
+QCMATRIX      qc
+    x2        x1           2
+    x1        x2           2
+    x3        x3           1
+ENDATA
+";
+
+  #[test]
+  fn test_from_qplib() -> Result<()> {
+    let model = Model::<f64>::from_qplib(QPLIB)?;
+    assert_eq!(model, Model::try_from(Parser::<f64>::parse(MPS)?)?);
+    let row_bounds = model.row_bounds()?;
+    assert_eq!(
+      row_bounds.get("c1").map(|r| (r.lower, r.upper)),
+      Some((1.0, 3.0))
+    );
+    assert_eq!(model.to_sparse()?.objective_offset, 5.0);
+    // QCMATRIX halves the Q that QPLIB lists
+    let qc = &model.quadratic_constraint_forms()?["qc"];
+    assert_eq!(qc.row_indices, [0, 2]);
+    assert_eq!(qc.column_indices, [1, 2]);
+    assert_eq!(qc.values, [4.0, 2.0]);
+    Ok(())
+  }
+
+  #[test]
+  fn test_errors() {
+    let error = |input: &str| {
+      let report = Model::<f64>::from_qplib(input).unwrap_err();
+      let error = report.downcast_ref::<Error>().unwrap();
+      (error.kind, error.line, error.column)
+    };
+    assert_eq!(
+      error(&QPLIB.replace("2 1 2\n", "4 1 2\n")),
+      (ErrorKind::UnknownColumn, Some(8), Some(1))
+    );
+    assert_eq!(
+      error(&QPLIB.replace("2 8\n", "3 8\n")),
+      (ErrorKind::UnknownRow, Some(28), Some(1))
+    );
+    assert_eq!(
+      error(&QPLIB.replace("QMQ", "QXQ")),
+      (ErrorKind::Syntax, Some(2), Some(1))
+    );
+    assert_eq!(
+      error(&QPLIB.replace("1 2\n3 -1", "1 two\n3 -1")),
+      (ErrorKind::BadNumber, Some(11), Some(3))
+    );
+    assert_eq!(
+      error(&QPLIB.replace("1 1 1\n", "1 1\n")),
+      (ErrorKind::MissingValue, Some(18), Some(4))
+    );
+    let truncated = &QPLIB[..QPLIB.find("1e20 # infinity").unwrap()];
+    assert_eq!(error(truncated).0, ErrorKind::MissingValue);
+    // A QCMATRIX entry rebuilt from a full matrix in QPLIB is a duplicate
+    assert_eq!(
+      error(&QPLIB.replace(
+        "2 # constraint quadratic entries\n",
+        "3 # constraint quadratic entries\n2 1 2 4\n"
+      ))
+      .0,
+      ErrorKind::DuplicateEntry
+    );
+  }
+}
@@ -0,0 +1,742 @@
+//! Streaming MPS reader.
+//!
+//! `Parser::parse` needs the whole file in memory and borrows every name from
+//! it. A `Reader` instead pulls one line at a time from any `BufRead`, such as
+//! a file, standard input or a decompressor, and yields owned `Event`s in file
+//! order. Apart from the current line, it keeps no more than a few flags of
+//! state.
+//!
+//! Collect the events into a `Model` with `Model::try_from(reader)`, or
+//! process them directly:
+//!
+//! ```
+//! use mps::stream::{Event, Reader};
+//!
+//! let input = "NAME example
+//! ROWS
+//!  N  COST
+//!  L  LIM1
+//! COLUMNS
+//!     X1        COST      1.0   LIM1      1.0
+//! RHS
+//!     RHS       LIM1      4.0
+//! ENDATA
+//! ";
+//! let mut coefficients = 0;
+//! for event in Reader::<_, f64>::new(input.as_bytes()) {
+//!   if let Event::Coefficient { .. } = event? {
+//!     coefficients += 1;
+//!   }
+//! }
+//! assert_eq!(coefficients, 2);
+//! # Ok::<(), mps::error::Error>(())
+//! ```
+use crate::error::{Error, ErrorKind};
+use crate::input;
+use crate::types::{
+  BoundType, BranchDirection, ConeType, MarkerType, ObjectiveSense, Parser,
+  RowType, SOSType, Section, Span, WideLine,
+};
+use fast_float2::FastFloat;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+use std::path::Path;
+cfg_if::cfg_if! {
+  if #[cfg(feature = "trace")] {
+    use nom_locate::LocatedSpan;
+    use nom_tracable::TracableInfo;
+  }
+}
+
+/// A single item of an MPS file, in the order it appears.
+///
+/// Every section header yields a `Section` event. Data events carry the
+/// fields of one entry; a COLUMNS, RHS or RANGES line with two row/value
+/// pairs yields two events. `SpecialOrderedSetMember`, `ConeMember` and
+/// `QuadraticConstraintTerm` events belong to the most recent
+/// `SpecialOrderedSet`, `Cone` and `QuadraticConstraint` event respectively.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Event<T> {
+  Section(Section),
+  Name(String),
+  ObjectiveSense(ObjectiveSense),
+  ObjectiveName(String),
+  ReferenceRow(String),
+  Row {
+    row_type: RowType,
+    row_name: String,
+  },
+  UserCut {
+    row_type: RowType,
+    row_name: String,
+  },
+  /// A COLUMNS entry; `integer` is set inside an INTORG/INTEND block.
+  Coefficient {
+    column_name: String,
+    row_name: String,
+    value: T,
+    integer: bool,
+  },
+  Rhs {
+    set_name: String,
+    row_name: String,
+    value: T,
+  },
+  Range {
+    set_name: String,
+    row_name: String,
+    value: T,
+  },
+  Bound {
+    bound_type: BoundType,
+    bound_name: String,
+    column_name: String,
+    value: Option<T>,
+  },
+  SpecialOrderedSet {
+    sos_type: SOSType,
+    set_name: String,
+  },
+  SpecialOrderedSetMember {
+    var_name: String,
+    weight: T,
+  },
+  /// A QSECTION, QUADOBJ or QMATRIX entry; see the preceding `Section`.
+  QuadraticObjective {
+    var1: String,
+    var2: String,
+    coefficient: T,
+  },
+  /// A QCMATRIX header and the row it applies to.
+  QuadraticConstraint {
+    row_name: String,
+  },
+  QuadraticConstraintTerm {
+    var1: String,
+    var2: String,
+    coefficient: T,
+  },
+  Cone {
+    cone_name: String,
+    cone_type: ConeType,
+  },
+  ConeMember {
+    var_name: String,
+  },
+  Indicator {
+    binary_var: String,
+    trigger_value: u8,
+    constraint_name: String,
+  },
+  LazyConstraint {
+    priority: Option<i32>,
+    row_name: String,
+  },
+  BranchPriority {
+    var_name: String,
+    priority: i32,
+    direction: BranchDirection,
+  },
+}
+
+/// Reads an MPS file line by line, yielding `Event`s.
+///
+/// Sections must appear in the order `Parser::mps_file` accepts, and every
+/// line is read by the same line parsers, so a `Reader` accepts the same
+/// files as `Parser::parse`. The first error ends the iteration; iteration
+/// also ends after the ENDATA line, without reading past it.
+pub struct Reader<R, T> {
+  input: R,
+  line: String,
+  line_number: usize,
+  /// Length of the last line if it had no line ending
+  unterminated: Option<usize>,
+  section: Option<Section>,
+  events: VecDeque<Event<T>>,
+  /// The next line holds the OBJSENSE, OBJNAME or REFROW value
+  value_pending: bool,
+  integer_block: bool,
+  /// An SOS set has been opened in the current section
+  sos_open: bool,
+  /// The CSECTION cone has not been reported yet
+  cone_pending: bool,
+  /// The current line directly follows the section header
+  first_line: bool,
+  done: bool,
+}
+
+impl<R: BufRead, T: FastFloat> Reader<R, T> {
+  pub fn new(input: R) -> Self {
+    Reader {
+      input,
+      line: String::new(),
+      line_number: 0,
+      unterminated: None,
+      section: None,
+      events: VecDeque::new(),
+      value_pending: false,
+      integer_block: false,
+      sos_open: false,
+      cone_pending: false,
+      first_line: false,
+      done: false,
+    }
+  }
+}
+
+impl<T: FastFloat> Reader<Box<dyn BufRead>, T> {
+  /// Opens the file at `path`, decompressing it if needed (see `input`).
+  pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+    Ok(Reader::new(input::open(path)?))
+  }
+}
+
+impl<R: BufRead, T: FastFloat> Reader<R, T> {
+  /// The number of lines read so far.
+  pub fn line_number(&self) -> usize {
+    self.line_number
+  }
+
+  /// The section whose header was read last.
+  pub fn section(&self) -> Option<Section> {
+    self.section
+  }
+
+  /// Reads the next line, queueing its events.
+  fn read_line(&mut self) -> Result<(), Error> {
+    let mut line = std::mem::take(&mut self.line);
+    line.clear();
+    let read = self.input.read_line(&mut line).map_err(|e| {
+      let error = Error::new(ErrorKind::Io, e.to_string());
+      match self.section {
+        Some(section) => error.in_section(section),
+        None => error,
+      }
+    })?;
+    if read == 0 {
+      return Err(match self.unterminated {
+        Some(length) => {
+          Error::missing_endata().at(self.line_number, length + 1)
+        }
+        None => Error::missing_endata().at(self.line_number + 1, 1),
+      });
+    }
+    self.line_number += 1;
+    if !line.ends_with('\n') {
+      self.unterminated = Some(line.len());
+    }
+    let result = self.read(&line);
+    self.line = line;
+    result
+  }
+
+  /// Dispatches `line`, including its line ending.
+  fn read(&mut self, line: &str) -> Result<(), Error> {
+    let first_line = std::mem::take(&mut self.first_line);
+    let text = line.trim_end_matches(['\n', '\r']);
+    if std::mem::take(&mut self.value_pending) {
+      return self.value(text);
+    }
+    if text.starts_with('*') || text.trim_matches([' ', '\t']).is_empty() {
+      return Ok(());
+    }
+    if !text.starts_with(' ') {
+      return self.header(text);
+    }
+    let section = match self.section {
+      Some(section) => section,
+      None => return Err(self.fail(text, nom::error::ErrorKind::Tag)),
+    };
+    let fail = |e| self.fail(text, code(e));
+    match section {
+      Section::Rows | Section::UserCuts => {
+        let (_, row) =
+          Parser::<T>::row_line_or_end(span(line)).map_err(fail)?;
+        if let Some(row) = row {
+          let row_type = row.row_type;
+          let row_name = row.row_name.to_string();
+          self.events.push_back(match section {
+            Section::Rows => Event::Row { row_type, row_name },
+            _ => Event::UserCut { row_type, row_name },
+          });
+        }
+      }
+      Section::Columns => {
+        match Parser::<T>::try_marker_line(span(line)) {
+          Ok((_, marker)) => {
+            return match (marker, self.integer_block) {
+              (Some(MarkerType::IntOrg), false) => {
+                self.integer_block = true;
+                Ok(())
+              }
+              (Some(MarkerType::IntEnd), true) => {
+                self.integer_block = false;
+                Ok(())
+              }
+              // Nested INTORG or INTEND without a matching INTORG
+              (Some(_), _) => {
+                Err(self.fail(text, nom::error::ErrorKind::Verify))
+              }
+              (None, _) => Ok(()),
+            };
+          }
+          Err(nom::Err::Error(_)) => (),
+          Err(e) => return Err(fail(e)),
+        }
+        let (_, wide_line) = Parser::<T>::line(span(line)).map_err(fail)?;
+        let integer = self.integer_block;
+        for (row_name, value) in pairs(&wide_line) {
+          self.events.push_back(Event::Coefficient {
+            column_name: wide_line.name.to_string(),
+            row_name,
+            value,
+            integer,
+          });
+        }
+      }
+      Section::Rhs | Section::Ranges => {
+        let parsed = match section {
+          Section::Rhs => Parser::<T>::rhs_line(span(line)),
+          _ => Parser::<T>::ranges_line(span(line)),
+        };
+        if let (_, Some(wide_line)) = parsed.map_err(fail)? {
+          for (row_name, value) in pairs(&wide_line) {
+            let set_name = wide_line.name.to_string();
+            self.events.push_back(match section {
+              Section::Rhs => Event::Rhs {
+                set_name,
+                row_name,
+                value,
+              },
+              _ => Event::Range {
+                set_name,
+                row_name,
+                value,
+              },
+            });
+          }
+        }
+      }
+      Section::Bounds => {
+        if let (_, Some(b)) =
+          Parser::<T>::bounds_line(span(line)).map_err(fail)?
+        {
+          self.events.push_back(Event::Bound {
+            bound_type: b.bound_type,
+            bound_name: b.bound_name.to_string(),
+            column_name: b.column_name.to_string(),
+            value: b.value,
+          });
+        }
+      }
+      Section::Sos => {
+        let set = text.trim_start();
+        if !self.sos_open || set.starts_with("S1") || set.starts_with("S2") {
+          if let (_, Some(s)) =
+            Parser::<T>::sos_line(span(line)).map_err(fail)?
+          {
+            self.sos_open = true;
+            self.events.push_back(Event::SpecialOrderedSet {
+              sos_type: s.sos_type,
+              set_name: s.set_name.to_string(),
+            });
+          }
+        } else if let (_, Some(m)) =
+          Parser::<T>::sos_member_line(span(line)).map_err(fail)?
+        {
+          self.events.push_back(Event::SpecialOrderedSetMember {
+            var_name: m.var_name.to_string(),
+            weight: m.weight,
+          });
+        }
+      }
+      Section::QSection | Section::QuadObj | Section::QMatrix => {
+        let parsed = match section {
+          Section::QMatrix => Parser::<T>::qmatrix_line(span(line))
+            .map(|(s, q)| (s, q.map(|q| (q.var1, q.var2, q.coefficient)))),
+          _ => Parser::<T>::quadobj_line(span(line))
+            .map(|(s, q)| (s, q.map(|q| (q.var1, q.var2, q.coefficient)))),
+        };
+        if let (_, Some((var1, var2, coefficient))) = parsed.map_err(fail)? {
+          self.events.push_back(Event::QuadraticObjective {
+            var1: var1.to_string(),
+            var2: var2.to_string(),
+            coefficient,
+          });
+        }
+      }
+      Section::QcMatrix => {
+        if let (_, Some(q)) =
+          Parser::<T>::qmatrix_line(span(line)).map_err(fail)?
+        {
+          self.events.push_back(Event::QuadraticConstraintTerm {
+            var1: q.var1.to_string(),
+            var2: q.var2.to_string(),
+            coefficient: q.coefficient,
+          });
+        }
+      }
+      Section::CSection => {
+        // Only the line right after the header may name the cone type
+        let cone_type = match text.strip_prefix(' ') {
+          Some(rest) if first_line => {
+            match rest.split(|c: char| !c.is_ascii_alphabetic()).next() {
+              Some("QUAD") => Some(ConeType::Quad),
+              Some("RQUAD") => Some(ConeType::RQuad),
+              _ => None,
+            }
+          }
+          _ => None,
+        };
+        if let Some(cone_type) = cone_type {
+          self.cone(cone_type);
+          return Ok(());
+        }
+        if let (_, Some(var_name)) =
+          Parser::<T>::csection_line(span(line)).map_err(fail)?
+        {
+          self.cone(ConeType::Quad);
+          self.events.push_back(Event::ConeMember {
+            var_name: var_name.to_string(),
+          });
+        }
+      }
+      Section::Indicators => {
+        if let (_, Some(i)) =
+          Parser::<T>::indicators_line(span(line)).map_err(fail)?
+        {
+          self.events.push_back(Event::Indicator {
+            binary_var: i.binary_var.to_string(),
+            trigger_value: i.trigger_value,
+            constraint_name: i.constraint_name.to_string(),
+          });
+        }
+      }
+      Section::LazyCons => {
+        if let (_, Some(l)) =
+          Parser::<T>::lazycons_line(span(line)).map_err(fail)?
+        {
+          self.events.push_back(Event::LazyConstraint {
+            priority: l.priority,
+            row_name: l.row_name.to_string(),
+          });
+        }
+      }
+      Section::Branch => {
+        if let (_, Some(b)) =
+          Parser::<T>::branch_line(span(line)).map_err(fail)?
+        {
+          self.events.push_back(Event::BranchPriority {
+            var_name: b.var_name.to_string(),
+            priority: b.priority,
+            direction: b.direction,
+          });
+        }
+      }
+      Section::Name
+      | Section::ObjSense
+      | Section::ObjName
+      | Section::RefRow
+      | Section::Endata => {
+        return Err(self.fail(text, nom::error::ErrorKind::Tag))
+      }
+    }
+    Ok(())
+  }
+
+  /// Reads a section header.
+  fn header(&mut self, text: &str) -> Result<(), Error> {
+    // Some MIP files mark lazy rows with a LAZYCONS line inside ROWS
+    if matches!(self.section, Some(Section::Rows | Section::UserCuts))
+      && text.starts_with("LAZYCONS")
+    {
+      return Ok(());
+    }
+    let token = text.split_whitespace().next().unwrap_or_default();
+    let argument = text[token.len()..].trim();
+    let previous = self.section;
+    let section = Section::try_from(token).ok().filter(|&section| {
+      let position = previous.map_or(0, |p| p.position());
+      // NAME, ROWS and COLUMNS may not be skipped
+      let skipped = [Section::Name, Section::Rows, Section::Columns]
+        .into_iter()
+        .any(|required| {
+          position < required.position()
+            && section.position() > required.position()
+        });
+      let in_order = section.position() > position
+        || (section == Section::QcMatrix && previous == Some(section));
+      // OBJSENSE may take its value on the header line
+      let takes_argument = matches!(section, Section::Name | Section::QcMatrix)
+        || (section == Section::ObjSense && ["MAX", "MIN"].contains(&argument));
+      in_order && !skipped && (takes_argument || argument.is_empty())
+    });
+    let section = match section {
+      Some(section) => section,
+      None => return Err(self.fail(text, nom::error::ErrorKind::Tag)),
+    };
+    if self.integer_block {
+      // An integer block must be closed before the section ends
+      return Err(self.fail(text, nom::error::ErrorKind::Verify));
+    }
+    if std::mem::take(&mut self.cone_pending) {
+      self.events.push_back(Event::Cone {
+        cone_name: "CONE".to_string(),
+        cone_type: ConeType::Quad,
+      });
+    }
+    self.section = Some(section);
+    self.first_line = true;
+    self.sos_open = false;
+    self.events.push_back(Event::Section(section));
+    match section {
+      Section::Name => self.events.push_back(Event::Name(argument.to_string())),
+      Section::ObjSense if !argument.is_empty() => self.value(argument)?,
+      Section::ObjSense | Section::ObjName | Section::RefRow => {
+        self.value_pending = true
+      }
+      Section::QcMatrix => self.events.push_back(Event::QuadraticConstraint {
+        row_name: argument.to_string(),
+      }),
+      Section::CSection => self.cone_pending = true,
+      Section::Endata => self.done = true,
+      _ => (),
+    }
+    Ok(())
+  }
+
+  /// Reads the line after an OBJSENSE, OBJNAME or REFROW header.
+  fn value(&mut self, text: &str) -> Result<(), Error> {
+    let value = text.trim_matches([' ', '\t']);
+    let event = match self.section {
+      Some(Section::ObjSense) => match value {
+        "MAX" => Event::ObjectiveSense(ObjectiveSense::Max),
+        "MIN" => Event::ObjectiveSense(ObjectiveSense::Min),
+        _ => return Err(self.fail(text, nom::error::ErrorKind::Tag)),
+      },
+      Some(Section::ObjName) => Event::ObjectiveName(value.to_string()),
+      _ => Event::ReferenceRow(value.to_string()),
+    };
+    self.events.push_back(event);
+    Ok(())
+  }
+
+  /// Reports the CSECTION cone before its first member.
+  fn cone(&mut self, cone_type: ConeType) {
+    if std::mem::take(&mut self.cone_pending) {
+      self.events.push_back(Event::Cone {
+        cone_name: "CONE".to_string(),
+        cone_type,
+      });
+    }
+  }
+
+  fn fail(&self, text: &str, code: nom::error::ErrorKind) -> Error {
+    Error::from_line(text, self.line_number, self.section, code)
+  }
+}
+
+impl<R: BufRead, T: FastFloat> Iterator for Reader<R, T> {
+  type Item = Result<Event<T>, Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    while self.events.is_empty() {
+      if self.done {
+        return None;
+      }
+      if let Err(e) = self.read_line() {
+        self.done = true;
+        self.events.clear();
+        return Some(Err(e));
+      }
+    }
+    self.events.pop_front().map(Ok)
+  }
+}
+
+/// Wraps a single line for the line parsers.
+fn span(line: &str) -> Span<'_> {
+  cfg_if::cfg_if! {
+    if #[cfg(feature = "trace")] {
+      let info = TracableInfo::new().forward(false).backward(false);
+      LocatedSpan::new_extra(line, info)
+    } else {
+      line
+    }
+  }
+}
+
+fn code<I>(e: nom::Err<nom::error::Error<I>>) -> nom::error::ErrorKind {
+  match e {
+    nom::Err::Error(e) | nom::Err::Failure(e) => e.code,
+    nom::Err::Incomplete(_) => nom::error::ErrorKind::Eof,
+  }
+}
+
+/// The row/value pairs of a COLUMNS, RHS or RANGES line.
+fn pairs<T: FastFloat>(wide_line: &WideLine<'_, T>) -> Vec<(String, T)> {
+  std::iter::once(&wide_line.first_pair)
+    .chain(&wide_line.second_pair)
+    .map(|pair| (pair.row_name.to_string(), pair.value))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::Model;
+  use color_eyre::Result;
+
+  fn events(input: &str) -> Result<Vec<Event<f64>>, Error> {
+    Reader::new(input.as_bytes()).collect()
+  }
+
+  #[test]
+  fn test_events() -> Result<()> {
+    let input = "* comment
+NAME          TEST
+OBJSENSE
+    MAX
+ROWS
+ N  COST
+ L  LIM1
+COLUMNS
+    MARKER                 'MARKER'                 'INTORG'
+    X1        COST      1.0        LIM1      2.0
+    MARKER                 'MARKER'                 'INTEND'
+RHS
+    RHS       LIM1      4.0
+CSECTION
+ RQUAD
+    X1
+ENDATA
+ignored after ENDATA";
+    let coefficient = |row_name: &str, value| Event::Coefficient {
+      column_name: "X1".to_string(),
+      row_name: row_name.to_string(),
+      value,
+      integer: true,
+    };
+    let expected = vec![
+      Event::Section(Section::Name),
+      Event::Name("TEST".to_string()),
+      Event::Section(Section::ObjSense),
+      Event::ObjectiveSense(ObjectiveSense::Max),
+      Event::Section(Section::Rows),
+      Event::Row {
+        row_type: RowType::Nr,
+        row_name: "COST".to_string(),
+      },
+      Event::Row {
+        row_type: RowType::Leq,
+        row_name: "LIM1".to_string(),
+      },
+      Event::Section(Section::Columns),
+      coefficient("COST", 1.0),
+      coefficient("LIM1", 2.0),
+      Event::Section(Section::Rhs),
+      Event::Rhs {
+        set_name: "RHS".to_string(),
+        row_name: "LIM1".to_string(),
+        value: 4.0,
+      },
+      Event::Section(Section::CSection),
+      Event::Cone {
+        cone_name: "CONE".to_string(),
+        cone_type: ConeType::RQuad,
+      },
+      Event::ConeMember {
+        var_name: "X1".to_string(),
+      },
+      Event::Section(Section::Endata),
+    ];
+    assert_eq!(events(input)?, expected);
+    Ok(())
+  }
+
+  #[test]
+  fn test_model() -> Result<()> {
+    let input = "NAME          EXTTEST
+OBJSENSE
+    MAX
+OBJNAME
+    cost
+ROWS
+ N  cost
+ L  c1
+ E  c2
+USERCUTS
+ L  cut1
+COLUMNS
+    x1        cost                 1.0   c1                    1.0
+    x1        cut1                 1.0
+    x2        cost                 2.0   c2                    1.0
+RHS
+    rhs1      c1                  20.0   cut1                 10.0
+RANGES
+    rng1      c1                   5.0
+BOUNDS
+ UP bnd1      x2                   1.0
+SOS
+ S1 set1
+    x1 1.0
+    x2 2.0
+ S2 set2
+    x2 1.0
+QUADOBJ
+    x1        x1                   2.0
+    x1        x2                   1.0
+QMATRIX
+    x2        x2                   3.0
+QCMATRIX      c1
+    x1        x1                   1.0
+QCMATRIX      c2
+    x2        x2                   1.0
+CSECTION
+    x1
+    x2
+INDICATORS
+ IF c1 x2 1
+LAZYCONS
+    c2
+BRANCH
+ UP x2 10
+ENDATA
+";
+    let parsed = Model::try_from(Parser::<f64>::parse(input)?)?;
+    let streamed = Model::try_from(Reader::<_, f64>::new(input.as_bytes()))?;
+    assert_eq!(streamed, parsed);
+    Ok(())
+  }
+
+  #[test]
+  fn test_errors() -> Result<()> {
+    let header = "NAME TEST\nROWS\n N  COST\n L  LIM1\nCOLUMNS\n";
+    let cases = [
+      "    X1        COST      1.0x\nENDATA\n",
+      "    X1        COST\nENDATA\n",
+      "    MARKER                 'MARKER'                 'INTEND'\nENDATA\n",
+      "    MARKER                 'MARKER'                 'INTORG'\nENDATA\n",
+      "    X1        COST      1.0\nRHS\n    RHS       LIM1      4.0\nROWS\n",
+      "    X1        COST      1.0\nFOO\nENDATA\n",
+      "    X1        COST      1.0\nBOUNDS\n XX BND       X1        1.0\n",
+      "    X1        COST      1.0\n",
+      "    X1        COST      1.0",
+    ];
+    for case in cases {
+      let input = format!("{header}{case}");
+      let expected = Parser::<f64>::parse(&input).unwrap_err();
+      let found = events(&input).unwrap_err();
+      assert_eq!(found, expected, "{input}");
+    }
+    // Missing sections and data before NAME
+    for input in ["ROWS\n N  COST\nENDATA\n", " N  COST\n", "NAME\nENDATA\n"] {
+      let expected = Parser::<f64>::parse(input).unwrap_err();
+      assert_eq!(events(input).unwrap_err(), expected, "{input}");
+    }
+    Ok(())
+  }
+}
@@ -1,7 +1,8 @@
 use color_eyre::{eyre::eyre, Result};
 use fast_float2::FastFloat;
+use indexmap::IndexSet;
 #[cfg(feature = "serde")]
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 cfg_if::cfg_if! {
   if #[cfg(feature = "trace")] {
@@ -41,6 +42,7 @@ cfg_if::cfg_if! {
 /// * `name`: Problem name from NAME section
 /// * `rows`: Row definitions (constraints) from ROWS section
 /// * `columns`: Column definitions (variables) and coefficients from COLUMNS section
+/// * `integer_columns`: Columns declared between INTORG/INTEND markers in COLUMNS section
 /// * `rhs`: Right-hand side values from optional RHS section
 /// * `ranges`: Range constraints from optional RANGES section
 /// * `bounds`: Variable bounds from optional BOUNDS section
@@ -53,6 +55,7 @@ cfg_if::cfg_if! {
 /// * `user_cuts`: User-defined cuts from optional USERCUTS section
 /// * `special_ordered_sets`: SOS definitions from optional SOS section (must follow BOUNDS)
 /// * `quadratic_objective`: Quadratic objective terms from QSECTION/QUADOBJ/QMATRIX sections
+/// * `full_quadratic_objective`: Whether the quadratic objective terms came from QMATRIX
 /// * `quadratic_constraints`: Quadratic constraint terms from optional QCMATRIX sections
 /// * `indicators`: Indicator constraints from optional INDICATORS section
 /// * `lazy_constraints`: Lazy constraints from optional LAZYCONS section
@@ -91,6 +94,8 @@ pub struct Parser<'a, T: FastFloat> {
   pub rows: Rows<'a>,
   /// Column variables from COLUMNS section
   pub columns: Columns<'a, T>,
+  /// Integer columns declared between INTORG/INTEND markers in COLUMNS section
+  pub integer_columns: IntegerColumns<'a>,
   /// Right-hand side values from optional RHS section
   pub rhs: Option<Rhs<'a, T>>,
   /// Range constraints from optional RANGES section
@@ -103,6 +108,9 @@ pub struct Parser<'a, T: FastFloat> {
   pub special_ordered_sets: Option<SpecialOrderedSets<'a, T>>,
   /// Quadratic objective terms from QSECTION/QUADOBJ/QMATRIX sections
   pub quadratic_objective: Option<QuadraticObjective<'a, T>>,
+  /// Whether `quadratic_objective` was read from a QMATRIX section, which
+  /// lists the full matrix rather than one triangle
+  pub full_quadratic_objective: bool,
   /// Quadratic constraint terms from optional QCMATRIX sections
   pub quadratic_constraints: Option<QuadraticConstraints<'a, T>>,
   /// Indicator constraints from optional INDICATORS section
@@ -147,7 +155,7 @@ pub struct RowLine<'a> {
 /// * `Geq`: Represents a greater than or equal to constraint (`G` in MPS format).
 /// * `Nr`: Represents a special type or non-standard row (`N` in MPS format).
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RowType {
   #[default]
   Eq,
@@ -184,7 +192,7 @@ impl TryFrom<char> for RowType {
 
 /// Enumeration representing the objective function sense (minimize or maximize)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ObjectiveSense {
   /// Minimize the objective function
   Min,
@@ -192,6 +200,154 @@ pub enum ObjectiveSense {
   Max,
 }
 
+/// Enumeration representing the sections of an MPS file.
+///
+/// Variants are listed in the order `Parser::mps_file` reads them; `QSECTION`
+/// and `QUADOBJ` are alternatives that share a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Section {
+  Name,
+  ObjSense,
+  ObjName,
+  RefRow,
+  Rows,
+  UserCuts,
+  Columns,
+  Rhs,
+  Ranges,
+  Bounds,
+  Sos,
+  QSection,
+  QuadObj,
+  QMatrix,
+  QcMatrix,
+  CSection,
+  Indicators,
+  LazyCons,
+  Branch,
+  Endata,
+}
+
+impl Section {
+  /// The header keyword that opens the section.
+  pub fn header(&self) -> &'static str {
+    match self {
+      Section::Name => "NAME",
+      Section::ObjSense => "OBJSENSE",
+      Section::ObjName => "OBJNAME",
+      Section::RefRow => "REFROW",
+      Section::Rows => "ROWS",
+      Section::UserCuts => "USERCUTS",
+      Section::Columns => "COLUMNS",
+      Section::Rhs => "RHS",
+      Section::Ranges => "RANGES",
+      Section::Bounds => "BOUNDS",
+      Section::Sos => "SOS",
+      Section::QSection => "QSECTION",
+      Section::QuadObj => "QUADOBJ",
+      Section::QMatrix => "QMATRIX",
+      Section::QcMatrix => "QCMATRIX",
+      Section::CSection => "CSECTION",
+      Section::Indicators => "INDICATORS",
+      Section::LazyCons => "LAZYCONS",
+      Section::Branch => "BRANCH",
+      Section::Endata => "ENDATA",
+    }
+  }
+
+  /// The position of the section in a file, shared by alternatives.
+  pub fn position(&self) -> usize {
+    match self {
+      Section::Name => 1,
+      Section::ObjSense => 2,
+      Section::ObjName => 3,
+      Section::RefRow => 4,
+      Section::Rows => 5,
+      Section::UserCuts => 6,
+      Section::Columns => 7,
+      Section::Rhs => 8,
+      Section::Ranges => 9,
+      Section::Bounds => 10,
+      Section::Sos => 11,
+      Section::QSection | Section::QuadObj => 12,
+      Section::QMatrix => 13,
+      Section::QcMatrix => 14,
+      Section::CSection => 15,
+      Section::Indicators => 16,
+      Section::LazyCons => 17,
+      Section::Branch => 18,
+      Section::Endata => 19,
+    }
+  }
+}
+
+impl TryFrom<&str> for Section {
+  type Error = color_eyre::Report;
+
+  /// Attempts to convert a section header keyword into a `Section`.
+  fn try_from(s: &str) -> Result<Self> {
+    match s {
+      "NAME" => Ok(Section::Name),
+      "OBJSENSE" => Ok(Section::ObjSense),
+      "OBJNAME" => Ok(Section::ObjName),
+      "REFROW" => Ok(Section::RefRow),
+      "ROWS" => Ok(Section::Rows),
+      "USERCUTS" => Ok(Section::UserCuts),
+      "COLUMNS" => Ok(Section::Columns),
+      "RHS" => Ok(Section::Rhs),
+      "RANGES" => Ok(Section::Ranges),
+      "BOUNDS" => Ok(Section::Bounds),
+      "SOS" => Ok(Section::Sos),
+      "QSECTION" => Ok(Section::QSection),
+      "QUADOBJ" => Ok(Section::QuadObj),
+      "QMATRIX" => Ok(Section::QMatrix),
+      "QCMATRIX" => Ok(Section::QcMatrix),
+      "CSECTION" => Ok(Section::CSection),
+      "INDICATORS" => Ok(Section::Indicators),
+      "LAZYCONS" => Ok(Section::LazyCons),
+      "BRANCH" => Ok(Section::Branch),
+      "ENDATA" => Ok(Section::Endata),
+      _ => Err(eyre!("invalid section: {}", s)),
+    }
+  }
+}
+
+/// Enumeration representing the layout of MPS data lines.
+///
+/// # Variants
+///
+/// * `Fixed`: Fields start at fixed columns (2, 5, 15, 25, 40 and 50), names
+///   are at most 8 characters and values at most 12.
+/// * `Free`: Fields are separated by whitespace and names may be of any length,
+///   but may not contain spaces.
+/// * `Auto`: Fixed format where possible and free format otherwise, decided
+///   for the whole file: when parsing, if the file is not valid fixed format,
+///   and when writing, if a name or value does not fit its fixed-format
+///   field.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Format {
+  #[default]
+  Fixed,
+  Free,
+  Auto,
+}
+
+/// How the parser reads the data lines of ROWS, COLUMNS, RHS, RANGES and
+/// BOUNDS.
+///
+/// `Fixed` and `Free` read every line as the `Format` of the same name.
+/// `PerLine` is the heuristic of `Parser::parse`: each line is read at the
+/// fixed-format columns if it can be, and split at whitespace otherwise.
+/// Unlike `Format::Auto`, it may read a file partly in each format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Layout {
+  Fixed,
+  Free,
+  PerLine,
+}
+
 /// Type alias for a collection of `RowLine` instances.
 ///
 /// This type represents the ROWS section of an MPS file, containing all the row
@@ -204,6 +360,39 @@ pub type Rows<'a> = Vec<RowLine<'a>>;
 /// RHS values, ranges, or bounds, each represented as a `WideLine`.
 pub type Columns<'a, T> = Vec<WideLine<'a, T>>;
 
+/// Type alias for the set of integer column names.
+///
+/// Collects, in order of first appearance, every column whose COLUMNS lines
+/// appear between a `'MARKER' 'INTORG'` and a `'MARKER' 'INTEND'` line.
+pub type IntegerColumns<'a> = IndexSet<&'a str>;
+
+/// Enumeration representing the integrality of a column.
+///
+/// # Variants
+///
+/// * `Continuous`: The column may take any real value within its bounds.
+/// * `Integer`: The column is restricted to integer values.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ColumnType {
+  #[default]
+  Continuous,
+  Integer,
+}
+
+/// Enumeration representing the recognised `'MARKER'` lines in the COLUMNS section.
+///
+/// # Variants
+///
+/// * `IntOrg`: Opens an integer block (`'INTORG'`).
+/// * `IntEnd`: Closes an integer block (`'INTEND'`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MarkerType {
+  IntOrg,
+  IntEnd,
+}
+
 /// Represents a pairing of a row name with a numeric value.
 ///
 /// This struct is utilized in `WideLine` to represent data associated with rows
@@ -310,20 +499,20 @@ pub type Bounds<'a, T> = Vec<BoundsLine<'a, T>>;
 /// * `Fr`: Free Variable (denoted as `-inf <= x_j <= inf` in MPS format).
 ///   Represents a variable without any bounds.
 ///
-/// * `Mi`: Unbounded Below (denoted as `-inf <= x_j <= 0` in MPS format).
-///   Indicates that the variable has no lower bound but is bounded above by zero.
+/// * `Mi`: Unbounded Below (denoted as `-inf <= x_j <= u_j` in MPS format).
+///   Removes the lower bound of the variable, leaving its upper bound unchanged.
 ///
 /// * `Pl`: Unbounded Above (denoted as `0 <= x_j <= inf` in MPS format).
 ///   Specifies that the variable has no upper bound but is bounded below by zero.
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BoundType {
   #[default]
   Lo, // lower bound     :  l_j <= x_j <= inf
   Up, // upper bound     :    0 <= x_j <= u_j
   Fx, // fixed variable  :  l_j == x_j == u_j
   Fr, // free variable   : -inf <= x_j <= inf
-  Mi, // Unbounded below : -inf <= x_j <= 0
+  Mi, // Unbounded below : -inf <= x_j <= u_j
   Pl, // Unbounded above :    0 <= x_j <= inf
   Bv, // Binary variable :  x_j in {0, 1}
   Li, // Lower integer   :  l_j <= x_j <= inf, x_j integer
@@ -386,6 +575,30 @@ impl TryFrom<&str> for BoundType {
   }
 }
 
+/// Enumeration representing the resolved domain of a column.
+///
+/// Combines the integrality declared by `'MARKER'` blocks in the COLUMNS
+/// section with the integer and semi-continuous bound types of the BOUNDS
+/// section.
+///
+/// # Variants
+///
+/// * `Continuous`: `l_j <= x_j <= u_j`.
+/// * `Integer`: `l_j <= x_j <= u_j`, `x_j` integer.
+/// * `Binary`: `x_j` in `{0, 1}`.
+/// * `SemiContinuous`: `x_j = 0` or `l_j <= x_j <= u_j`.
+/// * `SemiInteger`: `x_j = 0` or `l_j <= x_j <= u_j`, `x_j` integer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum VariableType {
+  #[default]
+  Continuous,
+  Integer,
+  Binary,
+  SemiContinuous,
+  SemiInteger,
+}
+
 /// Enumeration representing range types in an MPS (Mathematical Programming System) file.
 ///
 /// These types correspond to different rules for applying ranges to rows in the RANGES section
@@ -400,13 +613,13 @@ impl TryFrom<&str> for BoundType {
 /// | `_Ge`      | GE (>=)  | + or -      | b_i             | b_i + \|R_i\|   |
 /// | `_Ep`      | EP (==)  | +           | b_i             | b_i + \|R_i\|   |
 /// | `_Em`      | EM (==)  | -           | b_i - \|R_i\|   | b_i             |
-/// | `_Ez`      | EZ (==)  | -           | b_i             | b_i             |
+/// | `_Ez`      | EZ (==)  | 0           | b_i             | b_i             |
 ///
 /// Note: In cases where R_i is zero, both L_i and U_i are set to the respective Rhs value b_i, as per Maros CTSM p.91.
 ///
 /// Reference: Maros, I. Computational Techniques of the Simplex Method (CTSM).
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RangeType {
   #[default]
   _Le, // Less than or Equal
@@ -416,6 +629,27 @@ pub enum RangeType {
   _Ez, // Equality with unspecified R_i
 }
 
+impl<T: FastFloat> TryFrom<(&RowType, T)> for RangeType {
+  type Error = color_eyre::Report;
+
+  /// Attempts to classify a range value `R_i` applied to a row of the given type.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error for `N` rows, which carry no range.
+  fn try_from(t: (&RowType, T)) -> Result<Self> {
+    let (row_type, r) = t;
+    match row_type {
+      RowType::Leq => Ok(RangeType::_Le),
+      RowType::Geq => Ok(RangeType::_Ge),
+      RowType::Eq if r > T::default() => Ok(RangeType::_Ep),
+      RowType::Eq if r < T::default() => Ok(RangeType::_Em),
+      RowType::Eq => Ok(RangeType::_Ez),
+      RowType::Nr => Err(eyre!("range specified for row of type Nr")),
+    }
+  }
+}
+
 // ============================================================================
 // MIP/QP Extension Types
 // ============================================================================
@@ -470,7 +704,7 @@ pub type QuadraticObjective<'a, T> = Vec<QuadraticObjectiveTerm<'a, T>>;
 
 /// Type of Special Ordered Set
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SOSType {
   /// Type 1: At most one variable can be non-zero
   S1,
@@ -542,7 +776,7 @@ pub type QuadraticConstraints<'a, T> = Vec<QuadraticConstraint<'a, T>>;
 
 /// Type of cone constraint
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ConeType {
   /// Quadratic/Second-order cone
   Quad,
@@ -596,7 +830,7 @@ pub type ConeConstraints<'a, T> = Vec<ConeConstraint<'a, T>>;
 /// Specifies the direction preference for branch-and-bound when exploring the search tree.
 /// Variables with higher priorities are branched first.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BranchDirection {
   /// Branch up first (prefer increasing variable values)
   Up,
@@ -0,0 +1,814 @@
+use crate::model::Model;
+use crate::parse::{L1, L2, L3, L4, L5, L6, R1, R2, R3, R4, R5, R6};
+use crate::types::*;
+use color_eyre::{eyre::eyre, Result};
+use fast_float2::FastFloat;
+use indexmap::IndexMap;
+use std::io;
+
+/// Accumulates MPS text one section at a time.
+///
+/// Data lines are built from `(field, token)` pairs, where `field` is one of
+/// the six MPS fields. Each token is placed at the field's column, as read
+/// back by `Parser::line` and `parse_bounds_strict`, with the values of
+/// fields 4 and 6 right-justified. In free format a token
+/// that overruns its field is allowed and pushes the rest of the line right,
+/// so that lines whose tokens all fit still read back as fixed format.
+struct Writer {
+  format: Format,
+  output: String,
+}
+
+impl Writer {
+  fn new(format: Format) -> Self {
+    Writer {
+      format,
+      output: String::new(),
+    }
+  }
+
+  /// The `[start, end)` columns of a field. Data lines begin with a blank
+  /// that the parser strips before slicing, hence the offset of one.
+  fn field(field: usize) -> (usize, usize) {
+    let (l, r) = match field {
+      1 => (L1, R1),
+      2 => (L2, R2),
+      3 => (L3, R3),
+      4 => (L4, R4),
+      5 => (L5, R5),
+      _ => (L6, R6),
+    };
+    (l + 1, r + 1)
+  }
+
+  fn header(&mut self, header: &str) {
+    self.output.push_str(header);
+    self.output.push('\n');
+  }
+
+  /// Writes a section header followed by a name starting at `column`, e.g.
+  /// `NAME` or `QCMATRIX`.
+  fn named_header(&mut self, header: &str, column: usize, name: &str) {
+    if name.is_empty() {
+      self.header(header);
+    } else {
+      self.header(&format!("{:<width$}{}", header, name, width = column));
+    }
+  }
+
+  /// Writes a single-entry section such as `OBJSENSE` or `OBJNAME`.
+  fn entry_section(&mut self, header: &str, entry: &str) {
+    self.header(header);
+    self.header(&format!("    {}", entry));
+  }
+
+  fn line(&mut self, tokens: &[(usize, &str)]) -> Result<()> {
+    let mut line = String::new();
+    for &(field, token) in tokens {
+      let (start, end) = Self::field(field);
+      match self.format {
+        Format::Fixed if token.len() > end - start => {
+          return Err(eyre!(format!(
+            "{:?} does not fit in fixed-format field {} of {} characters",
+            token,
+            field,
+            end - start
+          )))
+        }
+        Format::Free
+          if token.is_empty() || token.contains(char::is_whitespace) =>
+        {
+          return Err(eyre!(format!(
+            "{:?} is not a valid free-format field",
+            token
+          )))
+        }
+        _ => (),
+      }
+      if line.len() < start {
+        line.push_str(&" ".repeat(start - line.len()));
+      } else {
+        line.push(' ');
+      }
+      // Values are right-justified, as `Parser::line` only slices a value
+      // field that extends to its last column
+      if field == 4 || field == 6 {
+        line.push_str(&" ".repeat((end - start).saturating_sub(token.len())));
+      }
+      line.push_str(token);
+    }
+    self.header(&line);
+    Ok(())
+  }
+
+  /// Formats a value with the shortest representation that parses back to
+  /// the same `T`.
+  ///
+  /// Fails in fixed format if that representation does not fit in the 12
+  /// characters of a value field, since any shorter one would change the
+  /// value.
+  fn value<T: FastFloat>(&self, value: T) -> Result<String> {
+    let shortest = format_value(value);
+    let (start, end) = Self::field(4);
+    if self.format == Format::Fixed && shortest.len() > end - start {
+      return Err(eyre!(
+        "{} does not fit in a {}-character fixed-format value field",
+        shortest,
+        end - start
+      ));
+    }
+    Ok(shortest)
+  }
+
+  /// Writes `(row, value)` entries under `name`, two per line.
+  fn pairs<T: FastFloat>(
+    &mut self,
+    name: &str,
+    entries: &[(&str, T)],
+  ) -> Result<()> {
+    for chunk in entries.chunks(2) {
+      let first = self.value(chunk[0].1)?;
+      let mut tokens = self.set_name(name);
+      tokens.extend([(3, chunk[0].0), (4, first.as_str())]);
+      let second = match chunk.get(1) {
+        Some(&(row, value)) => Some((row, self.value(value)?)),
+        None => None,
+      };
+      if let Some((row, value)) = &second {
+        tokens.extend([(5, *row), (6, value.as_str())]);
+      }
+      self.line(&tokens)?;
+    }
+    Ok(())
+  }
+
+  /// The name in field 2, which free format leaves out if it is empty, as
+  /// for an RHS, RANGES or BOUNDS line without a set name.
+  fn set_name<'a>(&self, name: &'a str) -> Vec<(usize, &'a str)> {
+    if self.format == Format::Free && name.is_empty() {
+      Vec::new()
+    } else {
+      vec![(2, name)]
+    }
+  }
+
+  fn row(&mut self, row_type: &RowType, row_name: &str) -> Result<()> {
+    self.line(&[(1, row_type_str(row_type)), (2, row_name)])
+  }
+
+  fn marker(&mut self, marker: MarkerType) -> Result<()> {
+    let marker = match marker {
+      MarkerType::IntOrg => "'INTORG'",
+      MarkerType::IntEnd => "'INTEND'",
+    };
+    self.line(&[(2, "MARKER"), (3, "'MARKER'"), (5, marker)])
+  }
+
+  fn bound<T: FastFloat>(
+    &mut self,
+    bound_type: &BoundType,
+    bound_name: &str,
+    column_name: &str,
+    value: Option<T>,
+  ) -> Result<()> {
+    let value = value.map(|v| self.value(v)).transpose()?;
+    let mut tokens = vec![(1, bound_type_str(bound_type))];
+    tokens.extend(self.set_name(bound_name));
+    tokens.push((3, column_name));
+    if let Some(value) = &value {
+      tokens.push((4, value.as_str()));
+    }
+    self.line(&tokens)
+  }
+
+  fn quadratic_term<T: FastFloat>(
+    &mut self,
+    var1: &str,
+    var2: &str,
+    coefficient: T,
+  ) -> Result<()> {
+    let coefficient = self.value(coefficient)?;
+    self.line(&[(2, var1), (3, var2), (4, &coefficient)])
+  }
+
+  fn sos_set(&mut self, sos_type: &SOSType, set_name: &str) -> Result<()> {
+    let sos_type = match sos_type {
+      SOSType::S1 => "S1",
+      SOSType::S2 => "S2",
+    };
+    self.line(&[(1, sos_type), (2, set_name)])
+  }
+
+  fn sos_member<T: FastFloat>(
+    &mut self,
+    var_name: &str,
+    weight: T,
+  ) -> Result<()> {
+    let weight = self.value(weight)?;
+    self.line(&[(2, var_name), (4, &weight)])
+  }
+
+  /// Writes a CSECTION block. The cone type line is read with a single
+  /// leading blank, and members are listed by name only.
+  fn cone<'a>(
+    &mut self,
+    cone_type: &ConeType,
+    members: impl Iterator<Item = &'a str>,
+  ) -> Result<()> {
+    self.header("CSECTION");
+    self.header(match cone_type {
+      ConeType::Quad => " QUAD",
+      ConeType::RQuad => " RQUAD",
+    });
+    for var_name in members {
+      self.line(&[(2, var_name)])?;
+    }
+    Ok(())
+  }
+
+  fn indicator(
+    &mut self,
+    row_name: &str,
+    column_name: &str,
+    trigger_value: u8,
+  ) -> Result<()> {
+    let trigger_value = trigger_value.to_string();
+    self.line(&[
+      (1, "IF"),
+      (2, row_name),
+      (3, column_name),
+      (4, &trigger_value),
+    ])
+  }
+
+  fn lazy_constraint(
+    &mut self,
+    row_name: &str,
+    priority: Option<i32>,
+  ) -> Result<()> {
+    match priority {
+      Some(priority) => self.line(&[(2, &priority.to_string()), (3, row_name)]),
+      None => self.line(&[(2, row_name)]),
+    }
+  }
+
+  fn branch(
+    &mut self,
+    var_name: &str,
+    priority: i32,
+    direction: BranchDirection,
+  ) -> Result<()> {
+    let priority = priority.to_string();
+    let direction = match direction {
+      BranchDirection::Up => Some("UP"),
+      BranchDirection::Down => Some("DN"),
+      BranchDirection::Rounding => Some("RD"),
+      BranchDirection::ClosestBound => Some("CB"),
+      BranchDirection::Auto => None,
+    };
+    match direction {
+      Some(direction) => {
+        self.line(&[(1, direction), (2, var_name), (3, &priority)])
+      }
+      None => self.line(&[(2, var_name), (3, &priority)]),
+    }
+  }
+
+  fn objective_sense(&mut self, objective_sense: ObjectiveSense) {
+    self.entry_section(
+      "OBJSENSE",
+      match objective_sense {
+        ObjectiveSense::Min => "MIN",
+        ObjectiveSense::Max => "MAX",
+      },
+    );
+  }
+}
+
+/// Formats a value with the shortest representation that parses back to the
+/// same `T`.
+pub(crate) fn format_value<T: FastFloat>(value: T) -> String {
+  let shortest = value.to_string();
+  match shortest.parse::<f64>() {
+    Ok(x) if x.is_finite() => shortest_repr(x),
+    _ => shortest,
+  }
+}
+
+/// The shorter of the plain and scientific representations of `x`, with the
+/// leading zero of `0.x` dropped.
+fn shortest_repr(x: f64) -> String {
+  let plain = x.to_string();
+  let plain = match plain.strip_prefix("0.") {
+    Some(fraction) => format!(".{}", fraction),
+    None => match plain.strip_prefix("-0.") {
+      Some(fraction) => format!("-.{}", fraction),
+      None => plain,
+    },
+  };
+  let scientific = format!("{:e}", x);
+  if scientific.len() < plain.len() {
+    scientific
+  } else {
+    plain
+  }
+}
+
+fn row_type_str(row_type: &RowType) -> &'static str {
+  match row_type {
+    RowType::Eq => "E",
+    RowType::Leq => "L",
+    RowType::Geq => "G",
+    RowType::Nr => "N",
+  }
+}
+
+fn bound_type_str(bound_type: &BoundType) -> &'static str {
+  match bound_type {
+    BoundType::Lo => "LO",
+    BoundType::Up => "UP",
+    BoundType::Fx => "FX",
+    BoundType::Fr => "FR",
+    BoundType::Mi => "MI",
+    BoundType::Pl => "PL",
+    BoundType::Bv => "BV",
+    BoundType::Li => "LI",
+    BoundType::Ui => "UI",
+    BoundType::Sc => "SC",
+  }
+}
+
+impl<T: FastFloat> Parser<'_, T> {
+  /// Serialises the parsed sections back to MPS text in the given `Format`.
+  ///
+  /// Sections are written in the order `mps_file` reads them. INTORG/INTEND
+  /// markers are placed around every run of COLUMNS lines whose column is in
+  /// `integer_columns`, and a quadratic objective read from QMATRIX is written
+  /// as QUADOBJ.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if a name does not fit its fixed-format field, or is
+  /// empty or contains whitespace in free format. Fixed format also fails
+  /// for a value whose shortest exact representation is longer than the 12
+  /// characters of its field, rather than round it. `Format::Auto` falls back
+  /// to free format for the whole file rather than fail for a name or value
+  /// that does not fit.
+  pub fn to_mps(&self, format: Format) -> Result<String> {
+    if format == Format::Auto {
+      return self
+        .to_mps(Format::Fixed)
+        .or_else(|_| self.to_mps(Format::Free));
+    }
+    let mut w = Writer::new(format);
+    w.named_header("NAME", Writer::field(3).0, self.name);
+    if let Some(objective_sense) = self.objective_sense {
+      w.objective_sense(objective_sense);
+    }
+    if let Some(objective_name) = self.objective_name {
+      w.entry_section("OBJNAME", objective_name);
+    }
+    if let Some(reference_row) = self.reference_row {
+      w.entry_section("REFROW", reference_row);
+    }
+    w.header("ROWS");
+    for r in &self.rows {
+      w.row(&r.row_type, r.row_name)?;
+    }
+    if let Some(user_cuts) = &self.user_cuts {
+      w.header("USERCUTS");
+      for r in user_cuts {
+        w.row(&r.row_type, r.row_name)?;
+      }
+    }
+    w.header("COLUMNS");
+    let mut integer_block = false;
+    for c in &self.columns {
+      let is_integer = self.integer_columns.contains(c.name);
+      if is_integer != integer_block {
+        w.marker(if is_integer {
+          MarkerType::IntOrg
+        } else {
+          MarkerType::IntEnd
+        })?;
+        integer_block = is_integer;
+      }
+      write_wide_line(&mut w, c)?;
+    }
+    if integer_block {
+      w.marker(MarkerType::IntEnd)?;
+    }
+    for (header, lines) in [("RHS", &self.rhs), ("RANGES", &self.ranges)] {
+      if let Some(lines) = lines {
+        w.header(header);
+        for l in lines {
+          write_wide_line(&mut w, l)?;
+        }
+      }
+    }
+    if let Some(bounds) = &self.bounds {
+      w.header("BOUNDS");
+      for b in bounds {
+        w.bound(&b.bound_type, b.bound_name, b.column_name, b.value)?;
+      }
+    }
+    if let Some(sets) = &self.special_ordered_sets {
+      w.header("SOS");
+      for s in sets {
+        w.sos_set(&s.sos_type, s.set_name)?;
+        for m in &s.members {
+          w.sos_member(m.var_name, m.weight)?;
+        }
+      }
+    }
+    if let Some(terms) = &self.quadratic_objective {
+      w.header(if self.full_quadratic_objective {
+        "QMATRIX"
+      } else {
+        "QUADOBJ"
+      });
+      for q in terms {
+        w.quadratic_term(q.var1, q.var2, q.coefficient)?;
+      }
+    }
+    for c in self.quadratic_constraints.iter().flatten() {
+      w.named_header("QCMATRIX", 11, c.row_name);
+      for q in &c.terms {
+        w.quadratic_term(q.var1, q.var2, q.coefficient)?;
+      }
+    }
+    for c in self.cone_constraints.iter().flatten() {
+      w.cone(&c.cone_type, c.members.iter().map(|m| m.var_name))?;
+    }
+    if let Some(indicators) = &self.indicators {
+      w.header("INDICATORS");
+      for i in indicators {
+        w.indicator(i.constraint_name, i.binary_var, i.trigger_value)?;
+      }
+    }
+    if let Some(lazy_constraints) = &self.lazy_constraints {
+      w.header("LAZYCONS");
+      for l in lazy_constraints {
+        w.lazy_constraint(l.row_name, l.priority)?;
+      }
+    }
+    if let Some(branch_priorities) = &self.branch_priorities {
+      w.header("BRANCH");
+      for b in branch_priorities {
+        w.branch(b.var_name, b.priority, b.direction)?;
+      }
+    }
+    w.header("ENDATA");
+    Ok(w.output)
+  }
+
+  /// Writes the output of `to_mps` to `writer`.
+  pub fn write<W: io::Write>(
+    &self,
+    writer: &mut W,
+    format: Format,
+  ) -> Result<()> {
+    writer.write_all(self.to_mps(format)?.as_bytes())?;
+    Ok(())
+  }
+}
+
+fn write_wide_line<T: FastFloat>(
+  w: &mut Writer,
+  l: &WideLine<T>,
+) -> Result<()> {
+  let mut entries = vec![(l.first_pair.row_name, l.first_pair.value)];
+  if let Some(second_pair) = &l.second_pair {
+    entries.push((second_pair.row_name, second_pair.value));
+  }
+  w.pairs(l.name, &entries)
+}
+
+/// Writes every set of a `RhsMap` or `RangesMap`.
+fn write_sets<T: FastFloat>(
+  w: &mut Writer,
+  header: &str,
+  sets: &IndexMap<String, IndexMap<String, T>>,
+) -> Result<()> {
+  if sets.is_empty() {
+    return Ok(());
+  }
+  w.header(header);
+  for (set_name, values) in sets {
+    let entries = values
+      .iter()
+      .map(|(row_name, &value)| (row_name.as_str(), value))
+      .collect::<Vec<_>>();
+    w.pairs(set_name, &entries)?;
+  }
+  Ok(())
+}
+
+impl<T: FastFloat> Model<T> {
+  /// Serialises the model to MPS text in the given `Format`.
+  ///
+  /// OBJNAME is only written when the objective is not the first `N` row.
+  /// Integer columns are wrapped in INTORG/INTEND markers. A column without
+  /// any coefficient, e.g. after `drop_free_rows`, is written with a zero
+  /// objective coefficient so that it is not lost; `free_row_values` and
+  /// `options` are not written.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if a name does not fit its fixed-format field, or is
+  /// empty or contains whitespace in free format. Fixed format also fails
+  /// for a value whose shortest exact representation is longer than the 12
+  /// characters of its field, rather than round it. `Format::Auto` falls back
+  /// to free format for the whole file rather than fail for a name or value
+  /// that does not fit.
+  pub fn to_mps(&self, format: Format) -> Result<String> {
+    if format == Format::Auto {
+      return self
+        .to_mps(Format::Fixed)
+        .or_else(|_| self.to_mps(Format::Free));
+    }
+    let mut w = Writer::new(format);
+    w.named_header("NAME", Writer::field(3).0, &self.name);
+    if let Some(objective_sense) = self.objective_sense {
+      w.objective_sense(objective_sense);
+    }
+    let objective_name = self.objective_name.as_deref();
+    if let Some(name) = objective_name {
+      if self.row_types.objective(None) != Some(name) {
+        w.entry_section("OBJNAME", name);
+      }
+    }
+    if let Some(reference_row) = &self.reference_row {
+      w.entry_section("REFROW", reference_row);
+    }
+    w.header("ROWS");
+    for (row_name, row_type) in &self.row_types.0 {
+      w.row(row_type, row_name)?;
+    }
+    if !self.user_cuts.0.is_empty() {
+      w.header("USERCUTS");
+      for (row_name, row_type) in &self.user_cuts.0 {
+        w.row(row_type, row_name)?;
+      }
+    }
+    w.header("COLUMNS");
+    let mut entries = self
+      .column_types
+      .0
+      .keys()
+      .map(|column_name| (column_name.as_str(), Vec::new()))
+      .collect::<IndexMap<_, _>>();
+    for ((row_name, column_name), &value) in &self.values.0 {
+      if let Some(column) = entries.get_mut(column_name.as_str()) {
+        column.push((row_name.as_str(), value));
+      }
+    }
+    let anchor_row = objective_name
+      .or_else(|| self.row_types.0.keys().next().map(String::as_str));
+    let mut integer_block = false;
+    for (column_name, mut column) in entries {
+      let is_integer = self.column_types.is_integer(column_name);
+      if is_integer != integer_block {
+        w.marker(if is_integer {
+          MarkerType::IntOrg
+        } else {
+          MarkerType::IntEnd
+        })?;
+        integer_block = is_integer;
+      }
+      if column.is_empty() {
+        let row_name = anchor_row.ok_or_else(|| {
+          eyre!(format!(
+            "column {:?} has no row to be written in",
+            column_name
+          ))
+        })?;
+        column.push((row_name, T::default()));
+      }
+      w.pairs(column_name, &column)?;
+    }
+    if integer_block {
+      w.marker(MarkerType::IntEnd)?;
+    }
+    write_sets(&mut w, "RHS", &self.rhs.0)?;
+    write_sets(&mut w, "RANGES", &self.ranges.0)?;
+    if !self.bounds.0.is_empty() {
+      w.header("BOUNDS");
+      for (bound_name, bound_set) in &self.bounds.0 {
+        for ((column_name, bound_type), &value) in bound_set {
+          w.bound(bound_type, bound_name, column_name, value)?;
+        }
+      }
+    }
+    if !self.special_ordered_sets.0.is_empty() {
+      w.header("SOS");
+      for (set_name, set) in &self.special_ordered_sets.0 {
+        w.sos_set(&set.sos_type, set_name)?;
+        for (var_name, &weight) in &set.members {
+          w.sos_member(var_name, weight)?;
+        }
+      }
+    }
+    if !self.quadratic_objective.0.is_empty() {
+      w.header("QUADOBJ");
+      for ((var1, var2), &coefficient) in &self.quadratic_objective.0 {
+        w.quadratic_term(var1, var2, coefficient)?;
+      }
+    }
+    for (row_name, terms) in &self.quadratic_constraints.0 {
+      w.named_header("QCMATRIX", 11, row_name);
+      for ((var1, var2), &coefficient) in terms {
+        w.quadratic_term(var1, var2, coefficient)?;
+      }
+    }
+    for cone in self.cone_constraints.0.values() {
+      w.cone(&cone.cone_type, cone.members.keys().map(String::as_str))?;
+    }
+    if !self.indicators.0.is_empty() {
+      w.header("INDICATORS");
+      for (row_name, indicator) in &self.indicators.0 {
+        w.indicator(row_name, &indicator.column_name, indicator.trigger_value)?;
+      }
+    }
+    if !self.lazy_constraints.0.is_empty() {
+      w.header("LAZYCONS");
+      for (row_name, &priority) in &self.lazy_constraints.0 {
+        w.lazy_constraint(row_name, priority)?;
+      }
+    }
+    if !self.branch_priorities.0.is_empty() {
+      w.header("BRANCH");
+      for (var_name, branching) in &self.branch_priorities.0 {
+        w.branch(var_name, branching.priority, branching.direction)?;
+      }
+    }
+    w.header("ENDATA");
+    Ok(w.output)
+  }
+
+  /// Writes the output of `to_mps` to `writer`.
+  pub fn write<W: io::Write>(
+    &self,
+    writer: &mut W,
+    format: Format,
+  ) -> Result<()> {
+    writer.write_all(self.to_mps(format)?.as_bytes())?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const EXTENSIONS: &str = "NAME          EXTTEST
+OBJSENSE
+    MAX
+OBJNAME
+    cost
+REFROW
+    c1
+ROWS
+ N  obj
+ N  cost
+ L  c1
+ E  c2
+USERCUTS
+ L  cut1
+COLUMNS
+    x1        cost                 1.0   c1                    1.0
+    x1        cut1                 1.0
+    MARKER                 'MARKER'                 'INTORG'
+    x2        cost                 2.0   c2                    1.0
+    MARKER                 'MARKER'                 'INTEND'
+    x3        obj                  1.0
+RHS
+    rhs1      c1                  20.0   cut1                 10.0
+RANGES
+    rng1      c2                  -4.5
+BOUNDS
+ UP bnd1      x2                   1.0
+ BV bnd1      x3
+ FR bnd2      x1
+SOS
+ S1 set1
+    x1 1.0
+    x2 2.0
+QUADOBJ
+    x1        x1                   2.0
+    x1        x2                   1.0
+QCMATRIX      c1
+    x1        x1                   1.0
+CSECTION
+ RQUAD
+    x1
+    x2
+INDICATORS
+ IF c1 x2 1
+LAZYCONS
+    c2
+    3 c1
+BRANCH
+ UP x2 10
+    x1 5
+ENDATA";
+
+  #[test]
+  fn test_write_fixed_fields() -> Result<()> {
+    let parsed = Parser::<f64>::parse(
+      "NAME          FIELDS
+ROWS
+ N  obj
+ G  c1
+COLUMNS
+    x         obj       1   c1   -0.5
+RHS
+    rhs       c1        1e-20
+BOUNDS
+ MI bnd       x
+ENDATA",
+    )?;
+    let expected = "NAME          FIELDS
+ROWS
+ N  obj
+ G  c1
+COLUMNS
+    x         obj                  1   c1                 -.5
+RHS
+    rhs       c1               1e-20
+BOUNDS
+ MI bnd       x
+ENDATA
+";
+    assert_eq!(parsed.to_mps(Format::Fixed)?, expected);
+    assert_eq!(Parser::<f64>::parse(expected)?, parsed);
+    let free = parsed.to_mps(Format::Free)?;
+    assert_eq!(free, expected);
+    assert_eq!(Parser::<f64>::parse(&free)?, parsed);
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_values() -> Result<()> {
+    let fixed = Writer::new(Format::Fixed);
+    let free = Writer::new(Format::Free);
+    assert_eq!(fixed.value(0.000001f64)?, "1e-6");
+    assert_eq!(fixed.value(-123000000.0f64)?, "-1.23e8");
+    assert_eq!(fixed.value(f64::INFINITY)?, "inf");
+    assert_eq!(fixed.value(0.1f32)?, ".1");
+    assert_eq!(free.value(0.123456789012345f64)?, ".123456789012345");
+    assert!(fixed.value(0.123456789012345f64).is_err());
+    assert!(fixed.value(-1234567.891234567e-30f64).is_err());
+    assert_eq!(fixed.value(-1.2345e-24f64)?, "-1.2345e-24");
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_extensions() -> Result<()> {
+    let parsed = Parser::<f64>::parse(EXTENSIONS)?;
+    for format in [Format::Fixed, Format::Free] {
+      let written = parsed.to_mps(format)?;
+      assert_eq!(Parser::<f64>::parse(&written)?, parsed);
+    }
+    let model = Model::try_from(parsed)?;
+    for format in [Format::Fixed, Format::Free] {
+      let written = model.to_mps(format)?;
+      assert_eq!(Model::try_from(Parser::<f64>::parse(&written)?)?, model);
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_dropped_free_rows() -> Result<()> {
+    let mut model = Model::try_from(Parser::<f64>::parse(EXTENSIONS)?)?;
+    model.drop_free_rows();
+    let written = model.to_mps(Format::Fixed)?;
+    assert!(!written.contains(" N  obj"));
+    assert!(written.contains("\n    x3        cost                 0\n"));
+    let reparsed = Model::try_from(Parser::<f64>::parse(&written)?)?;
+    assert_eq!(reparsed.column_types, model.column_types);
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_invalid_names() -> Result<()> {
+    let parsed = Parser::<f64>::parse(
+      "NAME          LONG
+ROWS
+ N  obj
+COLUMNS
+ a_long_column_name obj 1.0
+ENDATA",
+    )?;
+    let error = eyre!(
+      "\"a_long_column_name\" does not fit in fixed-format field 2 of 8 characters"
+    );
+    match parsed.to_mps(Format::Fixed) {
+      Ok(_) => panic!(),
+      Err(e) => assert_eq!(e.to_string(), error.to_string()),
+    };
+    let free = parsed.to_mps(Format::Free)?;
+    assert_eq!(Parser::<f64>::parse(&free)?, parsed);
+    Ok(())
+  }
+}
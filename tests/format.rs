@@ -0,0 +1,195 @@
+mod tests {
+  use color_eyre::Result;
+  use mps::error::ErrorKind;
+  use mps::model::Model;
+  use mps::stream::Reader;
+  use mps::types::{BoundType, Format, ObjectiveSense};
+  use mps::Parser;
+  use std::fs;
+  use std::path::PathBuf;
+
+  /// Every non-empty file under `tests/data/netlib`, sorted by name.
+  fn netlib() -> Result<Vec<(String, String)>> {
+    let dir =
+      PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/netlib");
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+      let path = entry?.path();
+      let contents = fs::read_to_string(&path)?;
+      if !contents.is_empty() {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        files.push((name, contents));
+      }
+    }
+    files.sort();
+    Ok(files)
+  }
+
+  /// A fixed-format file whose names contain spaces.
+  const FIXED: &str = "NAME          SPACES
+ROWS
+ N  COST
+ L  LIM 1
+COLUMNS
+    X ONE     COST      1              LIM 1     1
+    X TWO     COST      2
+RHS
+    RHS       LIM 1     4
+BOUNDS
+ UP BND       X ONE     3
+ENDATA
+";
+
+  /// A free-format file with long names, the objective sense on the
+  /// OBJSENSE line and set names left out.
+  const FREE: &str = "NAME LONG_NAMES
+OBJSENSE MAX
+ROWS
+ N PROFIT
+ L CAPACITY_CONSTRAINT
+COLUMNS
+ PRODUCTION_QUANTITY PROFIT 3 CAPACITY_CONSTRAINT 1
+ STORAGE_QUANTITY PROFIT 2
+RHS
+ CAPACITY_CONSTRAINT 4
+ PROFIT -12.5
+RANGES
+ RNG CAPACITY_CONSTRAINT 2
+BOUNDS
+ UP PRODUCTION_QUANTITY 3
+ BV BND STORAGE_QUANTITY
+ENDATA
+";
+
+  #[test]
+  fn test_fixed() -> Result<()> {
+    let (parsed, format) = Parser::<f64>::parse_format(FIXED, Format::Auto)?;
+    assert_eq!(format, Format::Fixed);
+    assert_eq!(parsed, Parser::parse_format(FIXED, Format::Fixed)?.0);
+    assert_eq!(parsed.rows[1].row_name, "LIM 1");
+    assert_eq!(parsed.columns[0].name, "X ONE");
+    assert_eq!(parsed.columns[0].first_pair.row_name, "COST");
+    assert_eq!(
+      parsed.columns[0].second_pair.as_ref().unwrap().row_name,
+      "LIM 1"
+    );
+    assert_eq!(parsed.rhs.as_ref().unwrap()[0].first_pair.row_name, "LIM 1");
+    assert_eq!(parsed.bounds.as_ref().unwrap()[0].column_name, "X ONE");
+    // Splitting at whitespace misreads the names
+    assert!(Parser::<f64>::parse_format(FIXED, Format::Free).is_err());
+    assert!(Parser::<f64>::parse(FIXED).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_free() -> Result<()> {
+    let (parsed, format) = Parser::<f64>::parse_format(FREE, Format::Auto)?;
+    assert_eq!(format, Format::Free);
+    assert_eq!(parsed, Parser::parse_format(FREE, Format::Free)?.0);
+    assert_eq!(parsed.objective_sense, Some(ObjectiveSense::Max));
+    assert_eq!(parsed.columns[0].name, "PRODUCTION_QUANTITY");
+    let rhs = parsed.rhs.as_ref().unwrap();
+    assert_eq!((rhs[1].name, rhs[1].first_pair.value), ("", -12.5));
+    let bounds = parsed.bounds.as_ref().unwrap();
+    assert_eq!(
+      (bounds[0].bound_name, bounds[0].column_name, bounds[0].value),
+      ("", "PRODUCTION_QUANTITY", Some(3.0))
+    );
+    assert_eq!(
+      (&bounds[1].bound_type, bounds[1].bound_name, bounds[1].value),
+      (&BoundType::Bv, "BND", None)
+    );
+    // The RHS of the objective row is the negated objective constant
+    let model = Model::try_from(parsed)?.to_sparse()?;
+    assert_eq!(model.objective_offset, 12.5);
+    assert!(Parser::<f64>::parse_format(FREE, Format::Fixed).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_auto_errors() {
+    // A free-format file is reported with the free-format error
+    let e = Parser::<f64>::parse_format(
+      FREE.trim_end_matches("ENDATA\n"),
+      Format::Auto,
+    )
+    .unwrap_err();
+    assert_eq!(e.kind, ErrorKind::MissingSection);
+    // and a fixed-format file with the fixed-format one
+    let e = Parser::<f64>::parse_format(
+      &FIXED.replace(" UP BND", " XX BND"),
+      Format::Auto,
+    )
+    .unwrap_err();
+    assert_eq!((e.kind, e.line), (ErrorKind::BadBoundType, Some(11)));
+  }
+
+  #[test]
+  fn test_write_auto() -> Result<()> {
+    for (input, format) in [(FIXED, Format::Fixed), (FREE, Format::Free)] {
+      let model =
+        Model::try_from(Parser::<f64>::parse_format(input, format)?.0)?;
+      let written = model.to_mps(Format::Auto)?;
+      assert_eq!(written, model.to_mps(format)?);
+      let (parsed, detected) =
+        Parser::<f64>::parse_format(&written, Format::Auto)?;
+      assert_eq!(detected, format);
+      assert_eq!(Model::try_from(parsed)?, model);
+    }
+    // A value too long for its fixed-format field is kept whole in free format
+    let input = "NAME
+ROWS
+ N  COST
+COLUMNS
+ X COST 0.12345678901234
+ENDATA
+";
+    let model = Model::try_from(Parser::<f64>::parse(input)?)?;
+    assert!(model.to_mps(Format::Fixed).is_err());
+    let written = model.to_mps(Format::Auto)?;
+    let (parsed, detected) =
+      Parser::<f64>::parse_format(&written, Format::Auto)?;
+    assert_eq!(detected, Format::Free);
+    assert_eq!(Model::try_from(parsed)?, model);
+    Ok(())
+  }
+
+  /// The objective sense may follow OBJSENSE on the same line.
+  #[test]
+  fn test_objsense_line() -> Result<()> {
+    let input = "NAME
+OBJSENSE MAX
+ROWS
+ N  COST
+COLUMNS
+    X         COST      1
+ENDATA
+";
+    let parsed = Model::try_from(Parser::<f64>::parse(input)?)?;
+    assert_eq!(parsed.objective_sense, Some(ObjectiveSense::Max));
+    let streamed = Model::try_from(Reader::<_, f64>::new(input.as_bytes()))?;
+    assert_eq!(streamed, parsed);
+    let input = input.replace("MAX", "FOO");
+    assert!(Parser::<f64>::parse(&input).is_err());
+    assert!(Model::<f64>::try_from(Reader::new(input.as_bytes())).is_err());
+    Ok(())
+  }
+
+  /// Netlib is fixed format throughout.
+  #[test]
+  fn test_netlib_auto() -> Result<()> {
+    let mut failures = Vec::new();
+    for (name, contents) in netlib()? {
+      match Parser::<f64>::parse_format(&contents, Format::Auto) {
+        Ok((parsed, Format::Fixed)) => {
+          if Parser::<f64>::parse(&contents).ok() != Some(parsed) {
+            failures.push(name);
+          }
+        }
+        result => failures.push(format!("{}: {:?}", name, result.map(|r| r.1))),
+      }
+    }
+    assert!(failures.is_empty(), "{:#?}", failures);
+    Ok(())
+  }
+}
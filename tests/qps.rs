@@ -0,0 +1,167 @@
+mod tests {
+  use color_eyre::{eyre::eyre, Result};
+  use mps::model::Model;
+  use mps::stream::Reader;
+  use mps::types::Format;
+  use mps::Parser;
+  use std::fs;
+  use std::path::PathBuf;
+
+  /// Every file under `tests/data/maros_meszaros`, in the QPS layout of the
+  /// Maros–Mészáros test set, sorted by name.
+  fn maros_meszaros() -> Result<Vec<(String, String)>> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+      .join("tests/data/maros_meszaros");
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+      let path = entry?.path();
+      let name = path.file_name().unwrap().to_string_lossy().to_string();
+      files.push((name, fs::read_to_string(&path)?));
+    }
+    files.sort();
+    Ok(files)
+  }
+
+  /// A known optimum of each problem and its objective value.
+  fn optimum(name: &str) -> Option<(Vec<f64>, f64)> {
+    match name {
+      "hs21" => Some((vec![2.0, 0.0], -99.96)),
+      "hs35" | "hs35_qmatrix" => {
+        Some((vec![4.0 / 3.0, 7.0 / 9.0, 4.0 / 9.0], 1.0 / 9.0))
+      }
+      _ => None,
+    }
+  }
+
+  /// The objective `0.5 * x'Qx + c'x + offset` of `model` at `x`, and
+  /// whether `x` satisfies its rows and bounds.
+  fn evaluate(model: &Model<f64>, x: &[f64]) -> Result<(f64, bool)> {
+    let sparse = model.to_sparse()?;
+    let mut objective = sparse.objective_offset;
+    for (j, c) in sparse.objective.iter().enumerate() {
+      objective += c * x[j];
+    }
+    objective += model.quadratic_objective_form()?.evaluate(x);
+    let mut activity = vec![0.0; sparse.row_names.len()];
+    let matrix = sparse.coo();
+    for k in 0..matrix.values.len() {
+      activity[matrix.row_indices[k]] +=
+        matrix.values[k] * x[matrix.column_indices[k]];
+    }
+    let within = |value: f64, lower: f64, upper: f64| {
+      lower - 1e-9 <= value && value <= upper + 1e-9
+    };
+    let feasible = (0..activity.len())
+      .all(|i| within(activity[i], sparse.row_lower[i], sparse.row_upper[i]))
+      && (0..x.len())
+        .all(|j| within(x[j], sparse.column_lower[j], sparse.column_upper[j]));
+    Ok((objective, feasible))
+  }
+
+  /// Each problem reads into a model with the known optimal objective value,
+  /// and keeps it when written as MPS or LP and read back.
+  #[test]
+  fn test_maros_meszaros() -> Result<()> {
+    for (name, contents) in maros_meszaros()? {
+      let (x, expected) =
+        optimum(&name).ok_or_else(|| eyre!("{}: no known optimum", name))?;
+      let model = Model::try_from(Parser::<f64>::parse(&contents)?)?;
+      let written = model.to_mps(Format::Free)?;
+      let lp = model.to_lp()?;
+      for model in [
+        model,
+        Model::try_from(Parser::<f64>::parse(&written)?)?,
+        Model::from_lp(&lp)?,
+      ] {
+        let (objective, feasible) = evaluate(&model, &x)?;
+        assert!(feasible, "{}: optimum is infeasible", name);
+        assert!(
+          (objective - expected).abs() < 1e-9,
+          "{}: objective {} instead of {}",
+          name,
+          objective,
+          expected
+        );
+      }
+    }
+    Ok(())
+  }
+
+  /// QMATRIX lists the full matrix that QUADOBJ lists one triangle of.
+  #[test]
+  fn test_qmatrix_matches_quadobj() -> Result<()> {
+    let files = maros_meszaros()?;
+    let model = |name: &str| -> Result<Model<f64>> {
+      let (_, contents) = files.iter().find(|(n, _)| n == name).unwrap();
+      Model::try_from(Parser::<f64>::parse(contents)?)
+    };
+    let (quadobj, qmatrix) = (model("hs35")?, model("hs35_qmatrix")?);
+    assert_eq!(
+      qmatrix.quadratic_objective_form()?,
+      quadobj.quadratic_objective_form()?
+    );
+    assert_eq!(qmatrix.values, quadobj.values);
+    assert_eq!(qmatrix.rhs, quadobj.rhs);
+    let (_, contents) =
+      files.iter().find(|(n, _)| n == "hs35_qmatrix").unwrap();
+    let streamed = Model::try_from(Reader::<_, f64>::new(contents.as_bytes()))?;
+    assert_eq!(streamed, qmatrix);
+    Ok(())
+  }
+
+  /// A QMATRIX entry without its mirror is not symmetric, rather than one
+  /// triangle of a QUADOBJ.
+  #[test]
+  fn test_qmatrix_missing_mirror() -> Result<()> {
+    let files = maros_meszaros()?;
+    let (_, contents) =
+      files.iter().find(|(n, _)| n == "hs35_qmatrix").unwrap();
+    let start = contents.find("QMATRIX\n").unwrap() + "QMATRIX\n".len();
+    let end = contents.find("ENDATA").unwrap();
+    let single = format!(
+      "{}    C------1  C------2  0.200000e+01\n{}",
+      &contents[..start],
+      &contents[end..]
+    );
+    let parsed = Parser::<f64>::parse(&single)?;
+    assert_eq!(parsed.quadratic_objective.as_ref().unwrap().len(), 1);
+    let e = Model::try_from(parsed).unwrap_err();
+    assert_eq!(
+      e.to_string(),
+      "QMATRIX is not symmetric: entries for (\"C------1\", \"C------2\") and (\"C------2\", \"C------1\") differ: found 2.0 and no entry"
+    );
+    let streamed = Model::try_from(Reader::<_, f64>::new(single.as_bytes()));
+    assert!(streamed.is_err());
+
+    // The diagonal is its own mirror
+    let diagonal = single.replace("C------2  0.2", "C------1  0.2");
+    let model = Model::try_from(Parser::<f64>::parse(&diagonal)?)?;
+    assert_eq!(model.quadratic_objective_form()?.values, [2.0]);
+    let streamed = Model::try_from(Reader::<_, f64>::new(diagonal.as_bytes()))?;
+    assert_eq!(streamed, model);
+    Ok(())
+  }
+
+  /// The QPLIB form of HS35 is the same problem as its QPS form.
+  #[test]
+  fn test_qplib_hs35() -> Result<()> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data");
+    let qplib = fs::read_to_string(dir.join("qplib/hs35.qplib"))?;
+    let qps = fs::read_to_string(dir.join("maros_meszaros/hs35"))?;
+    let model = Model::<f64>::from_qplib(&qplib)?;
+    let expected = Model::try_from(Parser::<f64>::parse(&qps)?)?;
+    assert_eq!(
+      model.quadratic_objective_form()?,
+      expected.quadratic_objective_form()?
+    );
+    let (x, optimum) = optimum("hs35").unwrap();
+    let (objective, feasible) = evaluate(&model, &x)?;
+    assert!(feasible);
+    assert!((objective - optimum).abs() < 1e-9);
+    // Just outside the constraint both forms share
+    let x = [x[0] + 0.1, x[1], x[2]];
+    assert!(!evaluate(&model, &x)?.1);
+    assert!(!evaluate(&expected, &x)?.1);
+    Ok(())
+  }
+}
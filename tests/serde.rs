@@ -0,0 +1,90 @@
+#[cfg(feature = "serde")]
+mod tests {
+  use color_eyre::Result;
+  use mps::model::Model;
+  use mps::types::{BoundType, RowType, Section};
+  use mps::{Parser, ParserOwned};
+
+  const NETLIB: [&str; 4] = ["afiro", "adlittle", "boeing1", "ship04l"];
+
+  fn read(name: &str) -> Result<String> {
+    Ok(std::fs::read_to_string(format!(
+      "tests/data/netlib/{}",
+      name
+    ))?)
+  }
+
+  #[test]
+  fn test_model_bincode() -> Result<()> {
+    for name in NETLIB {
+      let model = Model::try_from(Parser::<f64>::parse(&read(name)?)?)?;
+      let bytes = bincode::serialize(&model)?;
+      let restored: Model<f64> = bincode::deserialize(&bytes)?;
+      assert_eq!(restored, model, "{}", name);
+    }
+    Ok(())
+  }
+
+  /// Tuple-keyed maps, such as the matrix values, bounds and quadratic
+  /// terms, go through JSON as entry sequences.
+  #[test]
+  fn test_model_json() -> Result<()> {
+    let qcmatrix = "NAME QC
+ROWS
+ N obj
+ L c1
+COLUMNS
+ x obj 1 c1 1
+ y obj 1 c1 1
+RHS
+ rhs c1 4
+BOUNDS
+ UP bnd x 2
+QCMATRIX c1
+ x x 1
+ x y 0.5
+ y x 0.5
+ENDATA
+";
+    let mut inputs = Vec::new();
+    for name in NETLIB {
+      inputs.push((name, read(name)?));
+    }
+    inputs.push((
+      "hs21",
+      std::fs::read_to_string("tests/data/maros_meszaros/hs21")?,
+    ));
+    inputs.push(("qcmatrix", qcmatrix.to_string()));
+    for (name, input) in inputs {
+      let model = Model::try_from(Parser::<f64>::parse(&input)?)?;
+      let json = serde_json::to_string(&model)?;
+      let restored: Model<f64> = serde_json::from_str(&json)?;
+      assert_eq!(restored, model, "{}", name);
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_parser_owned_json() -> Result<()> {
+    for name in NETLIB {
+      let input = read(name)?;
+      let owned = Parser::<f64>::parse(&input)?.into_owned();
+      let json = serde_json::to_string(&owned)?;
+      let restored: ParserOwned<f64> = serde_json::from_str(&json)?;
+      assert_eq!(restored, owned, "{}", name);
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_types_json() -> Result<()> {
+    assert_eq!(serde_json::to_string(&RowType::Leq)?, "\"Leq\"");
+    assert_eq!(serde_json::from_str::<RowType>("\"Leq\"")?, RowType::Leq);
+    assert_eq!(serde_json::from_str::<BoundType>("\"Mi\"")?, BoundType::Mi);
+    assert_eq!(
+      serde_json::from_str::<Section>("\"Columns\"")?,
+      Section::Columns
+    );
+    Ok(())
+  }
+}